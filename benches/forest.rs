@@ -0,0 +1,77 @@
+#[macro_use]
+extern crate criterion;
+extern crate mleap;
+
+use criterion::Criterion;
+use mleap::bundle::{dsl, frame};
+use mleap::bundle::frame::Transformer;
+use mleap::bundle::tform::decision_tree_regression::DecisionTreeRegressionModel;
+use mleap::bundle::tform::ensemble::Ensemble;
+use mleap::bundle::tform::random_forest_regression::{RandomForestRegression, RandomForestRegressionModel};
+
+const NUM_TREES: usize = 500;
+const NUM_FEATURES: usize = 50;
+const DEPTH: usize = 8;
+
+// small LCG so the forest is the same on every run
+struct Lcg(u64);
+
+impl Lcg {
+  fn next(&mut self) -> f64 {
+    self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    (self.0 >> 11) as f64 / (1u64 << 53) as f64
+  }
+}
+
+fn grow(rng: &mut Lcg, depth: usize, nodes: &mut Vec<dsl::TreeNode>) {
+  if depth == 0 {
+    nodes.push(dsl::TreeNode::Leaf(vec![rng.next()]));
+  } else {
+    let feature_index = (rng.next() * NUM_FEATURES as f64) as usize;
+    nodes.push(dsl::TreeNode::Internal(dsl::Split::Continuous { feature_index: feature_index, threshold: rng.next() }));
+    grow(rng, depth - 1, nodes);
+    grow(rng, depth - 1, nodes);
+  }
+}
+
+fn forest() -> RandomForestRegression {
+  let mut rng = Lcg(42);
+  let trees: Vec<DecisionTreeRegressionModel> = (0..NUM_TREES).map(|_| {
+    let mut nodes = Vec::new();
+    grow(&mut rng, DEPTH, &mut nodes);
+    DecisionTreeRegressionModel::try_new(&nodes, NUM_FEATURES).unwrap()
+  }).collect();
+
+  let ensemble = Ensemble::try_new(trees, vec![1.0; NUM_TREES]).unwrap();
+  RandomForestRegression::new(String::from("forest"),
+                              String::from("features"),
+                              String::from("prediction"),
+                              RandomForestRegressionModel::new(ensemble, NUM_FEATURES))
+}
+
+fn features(size: usize) -> Vec<dsl::DenseTensor<f64>> {
+  let mut rng = Lcg(7);
+  (0..size).map(|_| {
+    dsl::DenseTensor::new(vec![NUM_FEATURES], (0..NUM_FEATURES).map(|_| rng.next()).collect())
+  }).collect()
+}
+
+fn score(c: &mut Criterion) {
+  let forest = forest();
+
+  for &size in [1, 100].iter() {
+    let rows = features(size);
+
+    c.bench_function(&format!("random_forest_500_trees_{}_rows", size), |b| {
+      b.iter(|| {
+        let mut frame = frame::LeapFrame::with_size(size);
+        frame.try_with_double_tensors(String::from("features"), rows.clone()).unwrap();
+        forest.transform(&mut frame).unwrap();
+        frame
+      })
+    });
+  }
+}
+
+criterion_group!(benches, score);
+criterion_main!(benches);
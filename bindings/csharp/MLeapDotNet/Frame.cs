@@ -19,6 +19,16 @@ namespace MLeapDotNet
 
         public int RowsCount => _rowsCount;
 
+        // Rows left after transformers that skip invalid rows
+        public int Size => (int) NativeMethods.mleap_frame_get_size(_frame);
+
+        public int[] GetSkippedRows()
+        {
+            var rows = new UIntPtr[(ulong) NativeMethods.mleap_frame_get_skipped_rows_len(_frame)];
+            NativeMethods.mleap_frame_get_skipped_rows(_frame, rows);
+            return Array.ConvertAll(rows, r => (int) r);
+        }
+
         public void AddDoubles(string name, params double[] values)
         {
             if (values.Length != _rowsCount)
@@ -37,16 +47,49 @@ namespace MLeapDotNet
             NativeMethods.mleap_frame_with_strings(_frame, name, values);
         }
 
+        // Null entries are marked invalid
+        public void AddNullableDoubles(string name, params double?[] values)
+        {
+            if (values.Length != _rowsCount)
+            {
+                throw new InvalidOperationException("Row count mismatch");
+            }
+            NativeMethods.mleap_frame_with_doubles_nullable(_frame, name,
+                Array.ConvertAll(values, v => v ?? 0.0), Validity(values, v => v.HasValue));
+        }
+
+        public void AddNullableStrings(string name, params string[] values)
+        {
+            if (values.Length != _rowsCount)
+            {
+                throw new InvalidOperationException("Row count mismatch");
+            }
+            NativeMethods.mleap_frame_with_strings_nullable(_frame, name, values, Validity(values, v => v != null));
+        }
+
+        public bool[] GetValidity(string name)
+        {
+            var result = new byte[Size];
+            NativeMethods.mleap_frame_get_validity(_frame, name, result);
+            return Array.ConvertAll(result, v => v != 0);
+        }
+
+        private static byte[] Validity<T>(T[] values, Func<T, bool> isValid)
+        {
+            return Array.ConvertAll(values, v => isValid(v) ? (byte) 1 : (byte) 0);
+        }
+
         public double[] GetDoubles(string name)
         {
-            var result = new double[_rowsCount];
+            var result = new double[Size];
             NativeMethods.mleap_frame_get_doubles(_frame, name, result);
             return result;
         }
 
         public IEnumerable<DoubleTensor> GetTensors(string name)
         {
-            for (int i = 0; i < _rowsCount; i++)
+            var size = Size;
+            for (int i = 0; i < size; i++)
             {
                 UIntPtr dimensionsLength;
                 UIntPtr valuesLength;
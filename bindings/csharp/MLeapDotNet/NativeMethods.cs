@@ -25,9 +25,32 @@ namespace MLeapDotNet
         [DllImport("mleap", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
         public static extern void mleap_frame_with_strings(IntPtr c_frame, string c_name, string[] c_values);
 
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
+        public static extern void mleap_frame_with_doubles_nullable(IntPtr c_frame, string c_name, double[] c_values,
+            byte[] c_validity);
+
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
+        public static extern void mleap_frame_with_strings_nullable(IntPtr c_frame, string c_name, string[] c_values,
+            byte[] c_validity);
+
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
+        public static extern void mleap_frame_get_validity(IntPtr c_frame, string c_name, byte[] c_buffer);
+
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl)]
+        public static extern UIntPtr mleap_frame_get_size(IntPtr c_frame);
+
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl)]
+        public static extern UIntPtr mleap_frame_get_skipped_rows_len(IntPtr c_frame);
+
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl)]
+        public static extern void mleap_frame_get_skipped_rows(IntPtr c_frame, UIntPtr[] c_buffer);
+
         [DllImport("mleap", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
         public static extern IntPtr mleap_transformer_load(string c_path);
 
+        [DllImport("mleap", CallingConvention = CallingConvention.Cdecl)]
+        public static extern IntPtr mleap_transformer_load_from_bytes(byte[] c_bytes, UIntPtr c_len);
+
         [DllImport("mleap", CallingConvention = CallingConvention.Cdecl, CharSet = CharSet.Ansi)]
         public static extern IntPtr mleap_transformer_load_ex(string c_path, LoadModelDelegate c_load_model,
             TransformDelegate c_transform);
@@ -34,11 +34,21 @@ namespace MLeapDotNet
             _model = model;
         }
 
+        internal Transformer(byte[] bundle)
+        {
+            _transformer = NativeMethods.mleap_transformer_load_from_bytes(bundle, (UIntPtr) bundle.Length);
+        }
+
         public static Transformer<bool> LoadFrom(string modelDirectoryPath)
         {
             return new Transformer<bool>(modelDirectoryPath, null, null);
         }
 
+        public static Transformer<bool> LoadFrom(byte[] bundle)
+        {
+            return new Transformer<bool>(bundle);
+        }
+
         public void Transfrom(Frame frame)
         {
             _currentFrames[frame.NativePointer] = frame;
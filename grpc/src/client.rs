@@ -0,0 +1,46 @@
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Endpoint, Error};
+use tonic::{Request, Status};
+
+use crate::pb;
+
+/// Client of the `ml.combust.mleap.pb.Mleap` service.
+#[derive(Clone)]
+pub struct MleapClient {
+  inner: tonic::client::Grpc<Channel>
+}
+
+impl MleapClient {
+  pub async fn connect<D>(dst: D) -> Result<MleapClient, Error>
+    where D: TryInto<Endpoint>,
+          D::Error: Into<Box<dyn std::error::Error + Send + Sync>> {
+    let channel = Endpoint::new(dst)?.connect().await?;
+    Ok(MleapClient::new(channel))
+  }
+
+  pub fn new(channel: Channel) -> MleapClient {
+    MleapClient { inner: tonic::client::Grpc::new(channel) }
+  }
+
+  async fn unary<M1, M2>(&mut self, request: M1, path: &'static str) -> Result<M2, Status>
+    where M1: prost::Message + Send + Sync + 'static,
+          M2: prost::Message + Default + Send + Sync + 'static {
+    self.inner.ready().await.map_err(|err| Status::unavailable(format!("service is not ready: {}", err)))?;
+
+    let codec = ProstCodec::<M1, M2>::default();
+    self.inner.unary(Request::new(request), PathAndQuery::from_static(path), codec).await.map(|r| r.into_inner())
+  }
+
+  pub async fn get_bundle_meta(&mut self, request: pb::GetBundleMetaRequest) -> Result<pb::BundleMeta, Status> {
+    self.unary(request, pb::GET_BUNDLE_META).await
+  }
+
+  pub async fn load_model(&mut self, request: pb::LoadModelRequest) -> Result<pb::Model, Status> {
+    self.unary(request, pb::LOAD_MODEL).await
+  }
+
+  pub async fn transform(&mut self, request: pb::TransformFrameRequest) -> Result<pb::TransformFrameResponse, Status> {
+    self.unary(request, pb::TRANSFORM).await
+  }
+}
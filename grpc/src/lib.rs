@@ -0,0 +1,13 @@
+//! gRPC scoring service for MLeap bundles, mirroring the `Mleap` service of
+//! MLeap serving on the wire, see `proto/mleap.proto`.
+//!
+//! Frames are sent as bytes in the JSON format or as
+//! `ml.combust.mleap.pb.LeapFrame` messages, converted with
+//! `mleap::bundle::frame::proto`, so the service and protobuf bundles share
+//! one encoding of values.
+pub mod client;
+pub mod pb;
+pub mod server;
+
+pub use client::MleapClient;
+pub use server::{MleapServer, Models};
@@ -0,0 +1,70 @@
+//! gRPC scoring server for MLeap bundles.
+//!
+//! ```text
+//! mleap-grpc [--addr host:port] [--bundle-root dir] [<name>=]<bundle> ...
+//! ```
+//!
+//! Each bundle is loaded under its given name, or the name in its
+//! `bundle.json`. Clients may load more models with the `LoadModel` call
+//! only from bundles under `--bundle-root`.
+use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process;
+
+use mleap_grpc::{MleapServer, Models};
+use tonic::transport::Server;
+
+const USAGE: &str = "usage: mleap-grpc [--addr host:port] [--bundle-root dir] [<name>=]<bundle> ...";
+const DEFAULT_ADDR: &str = "127.0.0.1:65327";
+
+async fn run(args: &[String]) -> Result<(), String> {
+  let mut addr = String::from(DEFAULT_ADDR);
+  let mut bundle_root = None;
+  let mut bundles = Vec::new();
+  let mut i = 0;
+
+  while i < args.len() {
+    if args[i] == "--addr" || args[i] == "--bundle-root" {
+      match args.get(i + 1) {
+        Some(a) if args[i] == "--addr" => addr = a.clone(),
+        Some(a) => bundle_root = Some(PathBuf::from(a)),
+        None => return Err(String::from(USAGE))
+      }
+      i += 2;
+      continue;
+    }
+
+    bundles.push(match args[i].find('=') {
+      Some(n) => (Some(args[i][..n].to_string()), PathBuf::from(&args[i][n + 1..])),
+      None => (None, PathBuf::from(&args[i]))
+    });
+    i += 1;
+  }
+
+  let models = Models::new(bundle_root.as_deref()).map_err(|err| format!("{}: {}", bundle_root.unwrap().display(), err))?;
+
+  for (name, path) in bundles {
+    let model = models.load(name, path).await.map_err(|err| err.message().to_string())?;
+    println!("loaded {}", model.name);
+  }
+
+  let addr: SocketAddr = addr.parse().map_err(|err| format!("{}: {}", addr, err))?;
+  println!("serving on {}", addr);
+
+  Server::builder()
+    .add_service(MleapServer::new(models))
+    .serve(addr)
+    .await
+    .map_err(|err| format!("{}: {}", addr, err))
+}
+
+#[tokio::main]
+async fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+
+  if let Err(err) = run(&args).await {
+    eprintln!("{}", err);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,183 @@
+// Messages and service of the ml.combust.mleap.pb package of MLeap serving,
+// written out by hand like the bundle messages so building does not depend
+// on protoc. The wire contract is ../proto/mleap.proto, test_proto_file
+// checks both agree.
+// Frames and schemas are the runtime messages of the mleap crate.
+
+pub use mleap::bundle::proto::ml_bundle::Bundle;
+pub use mleap::bundle::proto::runtime::{DataType, LeapFrame, Row, Schema, StructField, TensorShape};
+
+pub const SERVICE: &str = "ml.combust.mleap.pb.Mleap";
+pub const GET_BUNDLE_META: &str = "/ml.combust.mleap.pb.Mleap/GetBundleMeta";
+pub const LOAD_MODEL: &str = "/ml.combust.mleap.pb.Mleap/LoadModel";
+pub const TRANSFORM: &str = "/ml.combust.mleap.pb.Mleap/Transform";
+
+/// Frame formats of `TransformFrameRequest`, an empty format is read as
+/// `FORMAT_PROTO`.
+pub const FORMAT_JSON: &str = "ml.combust.mleap.json";
+pub const FORMAT_PROTO: &str = "ml.combust.mleap.proto";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum SelectMode {
+  SelectModeStrict = 0,
+  SelectModeRelaxed = 1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum TransformStatus {
+  StatusOk = 0,
+  StatusError = 1
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransformOptions {
+  #[prost(enumeration="SelectMode", tag="1")]
+  pub select_mode: i32,
+  #[prost(string, repeated, tag="2")]
+  pub select: Vec<String>
+}
+
+/// Meta of the bundle at `uri`, a loaded model or a bundle under the
+/// bundle root.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetBundleMetaRequest {
+  #[prost(string, tag="1")]
+  pub uri: String,
+  #[prost(message, optional, tag="2")]
+  pub timeout: Option<i64>
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BundleMeta {
+  #[prost(message, optional, tag="1")]
+  pub bundle: Option<Bundle>,
+  #[prost(message, optional, tag="2")]
+  pub input_schema: Option<Schema>,
+  #[prost(message, optional, tag="3")]
+  pub output_schema: Option<Schema>
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ModelConfig {
+  #[prost(message, optional, tag="1")]
+  pub memory_timeout: Option<i64>,
+  #[prost(message, optional, tag="2")]
+  pub disk_timeout: Option<i64>
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LoadModelRequest {
+  #[prost(string, tag="1")]
+  pub model_name: String,
+  #[prost(string, tag="2")]
+  pub uri: String,
+  #[prost(message, optional, tag="3")]
+  pub config: Option<ModelConfig>,
+  #[prost(bool, tag="4")]
+  pub force: bool,
+  #[prost(message, optional, tag="5")]
+  pub timeout: Option<i64>
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Model {
+  #[prost(string, tag="1")]
+  pub name: String,
+  #[prost(string, tag="2")]
+  pub uri: String,
+  #[prost(message, optional, tag="3")]
+  pub config: Option<ModelConfig>
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransformFrameRequest {
+  #[prost(string, tag="1")]
+  pub model_name: String,
+  #[prost(string, tag="2")]
+  pub format: String,
+  #[prost(bytes="vec", tag="3")]
+  pub frame: Vec<u8>,
+  #[prost(message, optional, tag="4")]
+  pub options: Option<TransformOptions>,
+  #[prost(uint64, tag="5")]
+  pub tag: u64,
+  #[prost(message, optional, tag="6")]
+  pub timeout: Option<i64>
+}
+
+/// `frame` is in the format of the request.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransformFrameResponse {
+  #[prost(uint64, tag="1")]
+  pub tag: u64,
+  #[prost(bytes="vec", tag="2")]
+  pub frame: Vec<u8>,
+  #[prost(enumeration="TransformStatus", tag="3")]
+  pub status: i32,
+  #[prost(string, tag="4")]
+  pub error: String,
+  #[prost(string, tag="5")]
+  pub backtrace: String
+}
+
+#[cfg(test)]
+#[path = "../../src/bundle/proto/descriptor.rs"]
+mod descriptor;
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use super::descriptor;
+  use prost::Message;
+
+  #[test]
+  fn test_proto_file() {
+    let imports = [include_str!("../../proto/bundle.proto"), include_str!("../../proto/mleap/runtime.proto")];
+
+    descriptor::check(&imports, include_str!("../proto/mleap.proto"), |name, buf| {
+      match name {
+        "TransformOptions" => Some(descriptor::round_trip::<TransformOptions>(buf)),
+        "GetBundleMetaRequest" => Some(descriptor::round_trip::<GetBundleMetaRequest>(buf)),
+        "BundleMeta" => Some(descriptor::round_trip::<BundleMeta>(buf)),
+        "ModelConfig" => Some(descriptor::round_trip::<ModelConfig>(buf)),
+        "LoadModelRequest" => Some(descriptor::round_trip::<LoadModelRequest>(buf)),
+        "Model" => Some(descriptor::round_trip::<Model>(buf)),
+        "TransformFrameRequest" => Some(descriptor::round_trip::<TransformFrameRequest>(buf)),
+        "TransformFrameResponse" => Some(descriptor::round_trip::<TransformFrameResponse>(buf)),
+        _ => None
+      }
+    }, |name, value| {
+      match name {
+        "SelectMode" => SelectMode::try_from(value).ok().map(|v| format!("{:?}", v)),
+        "TransformStatus" => TransformStatus::try_from(value).ok().map(|v| format!("{:?}", v)),
+        _ => None
+      }
+    });
+  }
+
+  #[test]
+  fn test_field_numbers() {
+    let request = TransformFrameRequest {
+      model_name: String::from("m"),
+      format: String::from("f"),
+      frame: vec![1],
+      options: Some(TransformOptions { select_mode: SelectMode::SelectModeRelaxed as i32, select: vec![String::from("s")] }),
+      tag: 5,
+      timeout: Some(6)
+    };
+    assert_eq!(request.encode_to_vec(), vec![0x0a, 1, b'm', 0x12, 1, b'f', 0x1a, 1, 1,
+                                             0x22, 5, 0x08, 1, 0x12, 1, b's',
+                                             0x28, 5, 0x32, 2, 0x08, 6]);
+
+    let response = TransformFrameResponse {
+      tag: 1,
+      frame: vec![2],
+      status: TransformStatus::StatusError as i32,
+      error: String::from("e"),
+      backtrace: String::from("b")
+    };
+    assert_eq!(response.encode_to_vec(), vec![0x08, 1, 0x12, 1, 2, 0x18, 1, 0x22, 1, b'e', 0x2a, 1, b'b']);
+  }
+}
@@ -0,0 +1,496 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+
+use mleap::bundle::frame::{self, LeapFrame};
+use mleap::bundle::{json, ser, tform};
+use prost::Message;
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{http, Body, BoxFuture, Service, StdError};
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::{Request, Response, Status};
+use tower::service_fn;
+
+use crate::pb;
+
+type Job = Box<dyn FnOnce(&dyn tform::DefaultNode) + Send>;
+
+struct Worker {
+  model: pb::Model,
+  meta: pb::BundleMeta,
+  jobs: mpsc::Sender<Job>
+}
+
+/// The loaded models, each owned by a thread of its own.
+///
+/// Transformers are not `Send`, so every model is loaded and run on its
+/// worker thread while frames are decoded and encoded on the caller's
+/// task. A slow load or a panicking transformer only holds up its own
+/// model.
+#[derive(Clone)]
+pub struct Models {
+  bundle_root: Option<PathBuf>,
+  workers: Arc<Mutex<HashMap<String, Worker>>>
+}
+
+fn read_bundle(path: &Path) -> Result<(pb::BundleMeta, Box<dyn tform::DefaultNode>), Status> {
+  // a FileBuilder creates missing directories, which is not what reading wants
+  if !path.exists() {
+    return Err(Status::not_found(format!("{}: no such bundle", path.display())));
+  }
+
+  let registry = tform::default_registry();
+  let (bundle, transformer) = path.to_str()
+    .ok_or_else(|| Status::invalid_argument(format!("{}: not a UTF-8 path", path.display())))
+    .and_then(|p| {
+      ser::open_builder(p)
+        .and_then(|builder| ser::Context::new(builder, &registry).read_bundle())
+        .map_err(|err| Status::invalid_argument(format!("{}: {:?}", p, err)))
+    })?;
+
+  let meta = pb::BundleMeta {
+    bundle: Some(pb::Bundle::from(&bundle)),
+    input_schema: Some(pb::Schema::from(&transformer.input_schema())),
+    output_schema: Some(pb::Schema::from(&transformer.output_schema()))
+  };
+
+  Ok((meta, transformer))
+}
+
+fn decode_frame(format: &str, bytes: &[u8]) -> Result<LeapFrame, Status> {
+  let frame = match format {
+    "" | pb::FORMAT_PROTO => pb::LeapFrame::decode(bytes)
+      .map_err(|err| format!("{}", err))
+      .and_then(|frame| json::TryFrom::try_from(&frame).map_err(|err| format!("{:?}", err))),
+    pb::FORMAT_JSON => serde_json::from_slice::<Value>(bytes)
+      .map_err(|err| format!("{}", err))
+      .and_then(|value| json::TryFrom::try_from(&value).map_err(|err| format!("{:?}", err))),
+    _ => return Err(Status::invalid_argument(format!("unsupported format {}", format)))
+  };
+
+  frame.map_err(|err| Status::invalid_argument(format!("invalid frame: {}", err)))
+}
+
+fn encode_frame(format: &str, frame: &LeapFrame) -> Vec<u8> {
+  if format == pb::FORMAT_JSON {
+    Value::from(frame).to_string().into_bytes()
+  } else {
+    pb::LeapFrame::from(frame).encode_to_vec()
+  }
+}
+
+fn transform(transformer: &dyn tform::DefaultNode, mut frame: LeapFrame, options: &pb::TransformOptions) -> Result<LeapFrame, String> {
+  // a panicking transformer must not take its worker down with it
+  let result = panic::catch_unwind(AssertUnwindSafe(|| -> frame::Result<()> {
+    transformer.transform(&mut frame)?;
+
+    if !options.select.is_empty() {
+      let relaxed = options.select_mode() == pb::SelectMode::SelectModeRelaxed;
+      let names: Vec<&str> = options.select.iter()
+        .map(|s| s.as_str())
+        .filter(|name| !relaxed || frame.get_col(name).is_some())
+        .collect();
+      frame.select(&names)?;
+    }
+
+    Ok(())
+  }));
+
+  match result {
+    Ok(Ok(())) => Ok(frame),
+    Ok(Err(err)) => Err(format!("{:?}", err)),
+    Err(_) => Err(String::from("the transformer panicked"))
+  }
+}
+
+impl Models {
+  /// Models that clients may load from bundles under `bundle_root`, or
+  /// only from the server side without one.
+  pub fn new(bundle_root: Option<&Path>) -> io::Result<Models> {
+    let bundle_root = match bundle_root {
+      Some(root) => Some(root.canonicalize()?),
+      None => None
+    };
+
+    Ok(Models { bundle_root, workers: Arc::new(Mutex::new(HashMap::new())) })
+  }
+
+  /// The path of a bundle `uri` sent by a client, which must lie under the
+  /// bundle root.
+  fn resolve(&self, uri: &str) -> Result<PathBuf, Status> {
+    let root = self.bundle_root.as_ref()
+      .ok_or_else(|| Status::permission_denied("loading bundles is disabled, there is no bundle root"))?;
+    let path = root.join(uri.strip_prefix("file://").unwrap_or(uri)).canonicalize()
+      .map_err(|_| Status::not_found(format!("{}: no such bundle", uri)))?;
+
+    if path.starts_with(root) {
+      Ok(path)
+    } else {
+      Err(Status::permission_denied(format!("{}: outside of the bundle root", uri)))
+    }
+  }
+
+  /// Starts the worker of a model, named `name` or else after its bundle,
+  /// replacing a loaded model of that name if `force` is set.
+  async fn start<F>(&self, name: Option<String>, uri: String, config: Option<pb::ModelConfig>, force: bool, load: F) -> Result<pb::Model, Status>
+    where F: FnOnce() -> Result<(pb::BundleMeta, Box<dyn tform::DefaultNode>), Status> + Send + 'static {
+    let (meta_tx, meta_rx) = oneshot::channel();
+    let (jobs, rx) = mpsc::channel::<Job>();
+
+    thread::Builder::new().name(String::from("mleap-model")).spawn(move || {
+      let transformer = match load() {
+        Ok((meta, transformer)) => { let _ = meta_tx.send(Ok(meta)); transformer },
+        Err(err) => { let _ = meta_tx.send(Err(err)); return; }
+      };
+
+      for job in rx {
+        job(transformer.as_ref());
+      }
+    }).map_err(|err| Status::internal(format!("cannot start a model thread: {}", err)))?;
+
+    let meta = meta_rx.await.unwrap_or_else(|_| Err(Status::internal("the model thread stopped while loading")))?;
+    let name = name.unwrap_or_else(|| meta.bundle.as_ref().map(|b| b.name.clone()).unwrap_or_default());
+
+    let model = pb::Model { name: name.clone(), uri, config };
+    let worker = Worker { model: model.clone(), meta, jobs };
+
+    // dropping the jobs of a model that is not inserted stops its thread
+    match self.workers.lock().unwrap().entry(name) {
+      Entry::Occupied(mut entry) if force => { entry.insert(worker); },
+      Entry::Occupied(entry) => return Err(Status::already_exists(format!("model {} is already loaded", entry.key()))),
+      Entry::Vacant(entry) => { entry.insert(worker); }
+    }
+
+    Ok(model)
+  }
+
+  /// Loads the bundle at `path` from the server side, without checking it
+  /// against the bundle root.
+  pub async fn load(&self, name: Option<String>, path: PathBuf) -> Result<pb::Model, Status> {
+    let uri = path.display().to_string();
+    self.start(name, uri, None, false, move || read_bundle(&path)).await
+  }
+
+  pub async fn load_model(&self, request: pb::LoadModelRequest) -> Result<pb::Model, Status> {
+    if request.model_name.is_empty() {
+      return Err(Status::invalid_argument("missing model_name"));
+    }
+    if !request.force && self.workers.lock().unwrap().contains_key(&request.model_name) {
+      return Err(Status::already_exists(format!("model {} is already loaded", request.model_name)));
+    }
+
+    let path = self.resolve(&request.uri)?;
+    self.start(Some(request.model_name), request.uri, request.config, request.force, move || read_bundle(&path)).await
+  }
+
+  pub async fn get_bundle_meta(&self, request: pb::GetBundleMetaRequest) -> Result<pb::BundleMeta, Status> {
+    let loaded = self.workers.lock().unwrap().values()
+      .find(|worker| worker.model.uri == request.uri)
+      .map(|worker| worker.meta.clone());
+    if let Some(meta) = loaded {
+      return Ok(meta);
+    }
+
+    let path = self.resolve(&request.uri)?;
+    tokio::task::spawn_blocking(move || read_bundle(&path).map(|(meta, _)| meta)).await
+      .unwrap_or_else(|_| Err(Status::internal("reading the bundle panicked")))
+  }
+
+  /// Malformed requests fail with a status, failed transforms are reported
+  /// in the response.
+  pub async fn transform(&self, request: pb::TransformFrameRequest) -> Result<pb::TransformFrameResponse, Status> {
+    let jobs = self.workers.lock().unwrap().get(&request.model_name)
+      .map(|worker| worker.jobs.clone())
+      .ok_or_else(|| Status::not_found(format!("no model named {}", request.model_name)))?;
+
+    let frame = decode_frame(&request.format, &request.frame)?;
+    let options = request.options.unwrap_or_default();
+
+    let (tx, rx) = oneshot::channel();
+    jobs.send(Box::new(move |transformer| { let _ = tx.send(transform(transformer, frame, &options)); }))
+      .map_err(|_| Status::unavailable(format!("the thread of model {} has stopped", request.model_name)))?;
+    let result = rx.await
+      .map_err(|_| Status::unavailable(format!("the thread of model {} has stopped", request.model_name)))?;
+
+    let response = match result {
+      Ok(frame) => pb::TransformFrameResponse {
+        tag: request.tag,
+        frame: encode_frame(&request.format, &frame),
+        status: pb::TransformStatus::StatusOk as i32,
+        error: String::new(),
+        backtrace: String::new()
+      },
+      Err(err) => pb::TransformFrameResponse {
+        tag: request.tag,
+        frame: Vec::new(),
+        status: pb::TransformStatus::StatusError as i32,
+        error: err,
+        backtrace: String::new()
+      }
+    };
+
+    Ok(response)
+  }
+}
+
+/// The `ml.combust.mleap.pb.Mleap` gRPC service, for use with
+/// `tonic::transport::Server::add_service`.
+#[derive(Clone)]
+pub struct MleapServer {
+  models: Models
+}
+
+impl MleapServer {
+  pub fn new(models: Models) -> MleapServer {
+    MleapServer { models }
+  }
+}
+
+impl NamedService for MleapServer {
+  const NAME: &'static str = pb::SERVICE;
+}
+
+fn unary<S, M1, M2, B>(service: S, request: http::Request<B>) -> BoxFuture<http::Response<BoxBody>, Infallible>
+  where S: UnaryService<M1, Response = M2> + Send + 'static,
+        S::Future: Send,
+        M1: prost::Message + Default + Send + 'static,
+        M2: prost::Message + Send + 'static,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static {
+  Box::pin(async move {
+    let mut grpc = Grpc::new(ProstCodec::<M2, M1>::default());
+    Ok(grpc.unary(service, request).await)
+  })
+}
+
+impl<B> Service<http::Request<B>> for MleapServer
+  where B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static {
+  type Response = http::Response<BoxBody>;
+  type Error = Infallible;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, request: http::Request<B>) -> Self::Future {
+    let models = self.models.clone();
+
+    match request.uri().path() {
+      pb::GET_BUNDLE_META => unary(service_fn(move |r: Request<pb::GetBundleMetaRequest>| {
+        let models = models.clone();
+        async move { models.get_bundle_meta(r.into_inner()).await.map(Response::new) }
+      }), request),
+      pb::LOAD_MODEL => unary(service_fn(move |r: Request<pb::LoadModelRequest>| {
+        let models = models.clone();
+        async move { models.load_model(r.into_inner()).await.map(Response::new) }
+      }), request),
+      pb::TRANSFORM => unary(service_fn(move |r: Request<pb::TransformFrameRequest>| {
+        let models = models.clone();
+        async move { models.transform(r.into_inner()).await.map(Response::new) }
+      }), request),
+      _ => Box::pin(async move {
+        Ok(Status::unimplemented(format!("no method {}", request.uri().path())).into_http())
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::client::MleapClient;
+  use tokio::net::TcpListener;
+  use tokio_stream::wrappers::TcpListenerStream;
+  use tonic::Code;
+
+  const BUNDLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/bundles");
+
+  fn strings(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+  }
+
+  fn airbnb_frame() -> LeapFrame {
+    let mut frame = LeapFrame::with_size(2);
+    frame.try_with_strings(String::from("room_type"), strings(&["Entire home/apt", "Private room"])).unwrap().
+      try_with_strings(String::from("state"), strings(&["NY", "CA"])).unwrap().
+      try_with_doubles(String::from("bathrooms"), vec![2.0, 1.0]).unwrap().
+      try_with_doubles(String::from("bedrooms"), vec![3.0, 1.0]).unwrap();
+
+    frame
+  }
+
+  struct Panicking;
+
+  impl mleap::bundle::ser::OpNode for Panicking {
+    fn op(&self) -> &'static str { "panicking" }
+  }
+
+  impl frame::Transformer for Panicking {
+    fn transform(&self, _frame: &mut LeapFrame) -> frame::Result<()> { panic!("boom") }
+  }
+
+  impl tform::DefaultNode for Panicking {
+    fn name(&self) -> &str { "panicking" }
+    fn model(&self) -> &dyn std::any::Any { self }
+    fn create_shape(&self) -> mleap::bundle::dsl::Shape { mleap::bundle::dsl::Shape::new(vec![], vec![]) }
+    fn input_schema(&self) -> frame::Schema { frame::Schema::new(vec![]) }
+    fn output_schema(&self) -> frame::Schema { frame::Schema::new(vec![]) }
+  }
+
+  fn models() -> Models {
+    Models::new(Some(Path::new(BUNDLES))).unwrap()
+  }
+
+  async fn serve(models: Models) -> MleapClient {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(tonic::transport::Server::builder()
+      .add_service(MleapServer::new(models))
+      .serve_with_incoming(TcpListenerStream::new(listener)));
+
+    MleapClient::connect(format!("http://{}", addr)).await.unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_load_and_transform() {
+    let mut client = serve(models()).await;
+    let uri = String::from("file://airbnb");
+
+    let model = client.load_model(pb::LoadModelRequest { model_name: String::from("airbnb"), uri: uri.clone(), ..Default::default() }).await.unwrap();
+    assert_eq!(model.name, "airbnb");
+    assert_eq!(model.uri, uri);
+
+    let err = client.load_model(pb::LoadModelRequest { model_name: String::from("airbnb"), uri: uri.clone(), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::AlreadyExists);
+    client.load_model(pb::LoadModelRequest { model_name: String::from("airbnb"), uri: uri.clone(), force: true, ..Default::default() }).await.unwrap();
+
+    let meta = client.get_bundle_meta(pb::GetBundleMetaRequest { uri, ..Default::default() }).await.unwrap();
+    assert_eq!(meta.bundle.unwrap().name, "airbnb");
+    assert_eq!(meta.input_schema.unwrap().fields[0].name, "room_type");
+    assert_eq!(meta.output_schema.unwrap().fields.last().unwrap().name, "price_prediction");
+
+    let response = client.transform(pb::TransformFrameRequest {
+      model_name: String::from("airbnb"),
+      format: String::from(pb::FORMAT_PROTO),
+      frame: pb::LeapFrame::from(&airbnb_frame()).encode_to_vec(),
+      options: Some(pb::TransformOptions { select: strings(&["price_prediction"]), ..Default::default() }),
+      tag: 7,
+      ..Default::default()
+    }).await.unwrap();
+    assert_eq!(response.tag, 7);
+    assert_eq!(response.status, pb::TransformStatus::StatusOk as i32);
+
+    let frame = decode_frame(pb::FORMAT_PROTO, &response.frame).unwrap();
+    assert_eq!(frame.cols().len(), 1);
+    assert_eq!(frame.try_doubles("price_prediction").unwrap(), &[172.5, 97.5]);
+  }
+
+  #[tokio::test]
+  async fn test_json_format() {
+    let mut client = serve(models()).await;
+    client.load_model(pb::LoadModelRequest { model_name: String::from("airbnb"), uri: String::from("airbnb"), ..Default::default() }).await.unwrap();
+
+    let response = client.transform(pb::TransformFrameRequest {
+      model_name: String::from("airbnb"),
+      format: String::from(pb::FORMAT_JSON),
+      frame: Value::from(&airbnb_frame()).to_string().into_bytes(),
+      options: Some(pb::TransformOptions {
+        select_mode: pb::SelectMode::SelectModeRelaxed as i32,
+        select: strings(&["state", "price_prediction", "nope"])
+      }),
+      ..Default::default()
+    }).await.unwrap();
+    assert_eq!(response.status, pb::TransformStatus::StatusOk as i32);
+
+    let json: Value = serde_json::from_slice(&response.frame).unwrap();
+    let frame: LeapFrame = json::TryFrom::try_from(&json).unwrap();
+    assert_eq!(frame.cols().iter().map(|c| c.name()).collect::<Vec<&str>>(), vec!["state", "price_prediction"]);
+    assert_eq!(frame.try_doubles("price_prediction").unwrap(), &[172.5, 97.5]);
+  }
+
+  #[tokio::test]
+  async fn test_errors() {
+    let mut client = serve(models()).await;
+    client.load_model(pb::LoadModelRequest { model_name: String::from("lr"), uri: String::from("linear_regression"), ..Default::default() }).await.unwrap();
+
+    let err = client.load_model(pb::LoadModelRequest { model_name: String::from("nope"), uri: String::from("nope"), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = client.get_bundle_meta(pb::GetBundleMetaRequest { uri: String::from("nope"), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = client.transform(pb::TransformFrameRequest { model_name: String::from("lr"), frame: vec![0xff], ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let err = client.transform(pb::TransformFrameRequest { model_name: String::from("lr"), format: String::from("ml.combust.mleap.avro"), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let err = client.transform(pb::TransformFrameRequest { model_name: String::from("nope"), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let response = client.transform(pb::TransformFrameRequest {
+      model_name: String::from("lr"),
+      frame: pb::LeapFrame::from(&airbnb_frame()).encode_to_vec(),
+      ..Default::default()
+    }).await.unwrap();
+    assert_eq!(response.status, pb::TransformStatus::StatusError as i32);
+    assert!(response.frame.is_empty());
+    assert!(!response.error.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_bundle_root() {
+    let mut client = serve(models()).await;
+
+    for uri in ["../../Cargo.toml", "/etc", "file:///"].iter() {
+      let err = client.load_model(pb::LoadModelRequest { model_name: String::from("x"), uri: uri.to_string(), ..Default::default() }).await.unwrap_err();
+      assert_eq!(err.code(), Code::PermissionDenied, "{}", uri);
+    }
+
+    let meta = client.get_bundle_meta(pb::GetBundleMetaRequest { uri: format!("{}/airbnb", BUNDLES), ..Default::default() }).await.unwrap();
+    assert_eq!(meta.bundle.unwrap().name, "airbnb");
+
+    let path = Path::new(BUNDLES).join("airbnb");
+    let models = Models::new(None).unwrap();
+    models.load(Some(String::from("airbnb")), path.clone()).await.unwrap();
+    let mut client = serve(models).await;
+
+    let err = client.load_model(pb::LoadModelRequest { model_name: String::from("lr"), uri: String::from("linear_regression"), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    let err = client.get_bundle_meta(pb::GetBundleMetaRequest { uri: String::from("linear_regression"), ..Default::default() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    client.get_bundle_meta(pb::GetBundleMetaRequest { uri: path.display().to_string(), ..Default::default() }).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_transformer_panic() {
+    let models = models();
+    models.start(Some(String::from("panicking")), String::new(), None, false, || {
+      Ok((pb::BundleMeta::default(), Box::new(Panicking) as Box<dyn tform::DefaultNode>))
+    }).await.unwrap();
+    let mut client = serve(models).await;
+
+    // the worker survives the panic and keeps serving
+    for _ in 0..2 {
+      let response = client.transform(pb::TransformFrameRequest {
+        model_name: String::from("panicking"),
+        frame: pb::LeapFrame::from(&airbnb_frame()).encode_to_vec(),
+        ..Default::default()
+      }).await.unwrap();
+      assert_eq!(response.status, pb::TransformStatus::StatusError as i32);
+      assert_eq!(response.error, "the transformer panicked");
+    }
+  }
+}
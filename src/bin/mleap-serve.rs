@@ -0,0 +1,345 @@
+//! HTTP scoring server for MLeap bundles.
+//!
+//! ```text
+//! mleap-serve [--addr host:port] [<name>=]<bundle> ...
+//! ```
+//!
+//! Each bundle is served under its given name, or the name in its
+//! `bundle.json`:
+//!
+//! - `POST /models/{name}/transform` scores a LeapFrame in the MLeap JSON
+//!   format and returns the transformed frame
+//! - `GET /models/{name}/schema` returns the input and output schemas
+//! - `GET /health` lists the loaded models
+//!
+//! Errors are returned as `{"error": "..."}`. Frames that do not match the
+//! input schema of the model are rejected before scoring, and request
+//! bodies are limited to 64 MiB.
+extern crate mleap;
+extern crate serde_json;
+extern crate tiny_http;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path;
+use std::process;
+use serde_json::Value;
+use serde_json::map::Map;
+use tiny_http::{Header, Method, Response, Server};
+
+use mleap::bundle::{frame, ser, tform};
+use mleap::bundle::json::TryFrom;
+
+type Models = BTreeMap<String, Box<dyn tform::DefaultNode>>;
+
+const USAGE: &str = "usage: mleap-serve [--addr host:port] [<name>=]<bundle> ...";
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+const MAX_BODY: u64 = 64 << 20;
+
+/// Reads the bundle at `path`, named `name` or else after the bundle.
+fn load_model(name: Option<&str>, path: &str) -> Result<(String, Box<dyn tform::DefaultNode>), String> {
+  // a FileBuilder creates missing directories, which is not what reading wants
+  if !path::Path::new(path).exists() {
+    return Err(format!("{}: no such bundle", path));
+  }
+
+  let registry = tform::default_registry();
+
+  ser::open_builder(path).and_then(|builder| {
+    ser::Context::new(builder, &registry).read_bundle()
+  }).map(|(bundle, transformer)| {
+    (name.unwrap_or(bundle.name()).to_string(), transformer)
+  }).map_err(|err| format!("{}: {:?}", path, err))
+}
+
+fn error(message: String) -> Value {
+  let mut map = Map::with_capacity(1);
+  map.insert(String::from("error"), Value::from(message));
+  Value::Object(map)
+}
+
+fn health(models: &Models) -> Value {
+  let mut map = Map::with_capacity(2);
+  map.insert(String::from("status"), Value::from("ok"));
+  map.insert(String::from("models"), Value::from(models.keys().cloned().collect::<Vec<String>>()));
+  Value::Object(map)
+}
+
+fn schema(transformer: &dyn tform::DefaultNode) -> Value {
+  let mut map = Map::with_capacity(2);
+  map.insert(String::from("input"), Value::from(&transformer.input_schema()));
+  map.insert(String::from("output"), Value::from(&transformer.output_schema()));
+  Value::Object(map)
+}
+
+fn transform(transformer: &dyn tform::DefaultNode, body: &mut dyn Read) -> (u16, Value) {
+  let json: Value = match serde_json::from_reader(body) {
+    Ok(json) => json,
+    Err(err) => return (400, error(format!("invalid JSON: {}", err)))
+  };
+  // a panic while reading or transforming the frame must not take the
+  // server down with it
+  let mut frame = match panic::catch_unwind(AssertUnwindSafe(|| frame::LeapFrame::try_from(&json))) {
+    Ok(Ok(frame)) => frame,
+    Ok(Err(err)) => return (400, error(format!("invalid LeapFrame: {:?}", err))),
+    Err(_) => return (500, error(String::from("reading the LeapFrame panicked")))
+  };
+
+  if let Err(err) = transformer.input_schema().try_validate(&frame.schema()) {
+    return (422, error(format!("{:?}", err)));
+  }
+
+  match panic::catch_unwind(AssertUnwindSafe(|| transformer.transform(&mut frame))) {
+    Ok(Ok(_)) => (200, Value::from(&frame)),
+    Ok(Err(err)) => (422, error(format!("{:?}", err))),
+    Err(_) => (500, error(String::from("the transformer panicked")))
+  }
+}
+
+/// The status code and JSON body of the response to a request.
+fn handle(models: &Models, method: &Method, url: &str, body: &mut dyn Read) -> (u16, Value) {
+  let path = url.split('?').next().unwrap_or("");
+  let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+  match (method, segments.as_slice()) {
+    (&Method::Get, &["health"]) => (200, health(models)),
+    (_, &["health"]) => (405, error(format!("{} is not allowed on {}", method, path))),
+    (_, &["models", name, action]) => {
+      let transformer = match models.get(name) {
+        Some(transformer) => transformer,
+        None => return (404, error(format!("no model named {}", name)))
+      };
+
+      match (method, action) {
+        (&Method::Post, "transform") => transform(transformer.as_ref(), body),
+        (&Method::Get, "schema") => (200, schema(transformer.as_ref())),
+        (_, "transform") | (_, "schema") => (405, error(format!("{} is not allowed on {}", method, path))),
+        _ => (404, error(format!("not found: {}", path)))
+      }
+    },
+    _ => (404, error(format!("not found: {}", path)))
+  }
+}
+
+/// Serves requests one at a time until the server fails.
+fn serve(server: &Server, models: &Models) {
+  let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+
+  for mut request in server.incoming_requests() {
+    let (status, json) = if request.body_length().is_some_and(|n| n as u64 > MAX_BODY) {
+      (413, error(format!("request bodies are limited to {} bytes", MAX_BODY)))
+    } else {
+      let method = request.method().clone();
+      let url = request.url().to_string();
+      handle(models, &method, &url, &mut request.as_reader().take(MAX_BODY))
+    };
+    let response = Response::from_string(json.to_string()).
+      with_status_code(status).
+      with_header(content_type.clone());
+
+    if let Err(err) = request.respond(response) {
+      let _ = writeln!(io::stderr(), "cannot send response: {}", err);
+    }
+  }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+  let mut addr = String::from(DEFAULT_ADDR);
+  let mut models = Models::new();
+  let mut i = 0;
+
+  while i < args.len() {
+    if args[i] == "--addr" {
+      match args.get(i + 1) {
+        Some(a) => addr = a.clone(),
+        None => return Err(String::from(USAGE))
+      }
+      i += 2;
+      continue;
+    }
+
+    let r = match args[i].find('=') {
+      Some(n) => load_model(Some(&args[i][..n]), &args[i][n + 1..]),
+      None => load_model(None, &args[i])
+    };
+
+    match r {
+      Ok((name, _)) if models.contains_key(&name) => return Err(format!("model {} is loaded twice", name)),
+      Ok((name, transformer)) => { models.insert(name, transformer); },
+      Err(err) => return Err(err)
+    }
+    i += 1;
+  }
+
+  if models.is_empty() {
+    return Err(String::from(USAGE));
+  }
+
+  Server::http(addr.as_str()).map_err(|err| format!("{}: {}", addr, err)).map(|server| {
+    println!("serving {} on {}", models.keys().cloned().collect::<Vec<String>>().join(", "), server.server_addr());
+    serve(&server, &models)
+  })
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+
+  if let Err(err) = run(&args) {
+    let _ = writeln!(io::stderr(), "{}", err);
+    process::exit(1);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::any::Any;
+  use std::net::{SocketAddr, TcpStream};
+  use std::sync::mpsc;
+  use std::thread;
+
+  const BUNDLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bundles");
+
+  const FRAME: &str = r#"{
+    "schema": {"fields": [{"name": "room_type", "type": "string"},
+                          {"name": "state", "type": "string"},
+                          {"name": "bathrooms", "type": "double"},
+                          {"name": "bedrooms", "type": "double"}]},
+    "rows": [["Entire home/apt", "NY", 2.0, 3.0], ["Private room", "CA", 1.0, 1.0]]
+  }"#;
+
+  struct Panicking;
+
+  impl ser::OpNode for Panicking {
+    fn op(&self) -> &'static str { "panicking" }
+  }
+
+  impl frame::Transformer for Panicking {
+    fn transform(&self, _frame: &mut frame::LeapFrame) -> frame::Result<()> { panic!("boom") }
+  }
+
+  impl tform::DefaultNode for Panicking {
+    fn name(&self) -> &str { "panicking" }
+    fn model(&self) -> &dyn Any { self as &dyn Any }
+    fn create_shape(&self) -> mleap::bundle::dsl::Shape { mleap::bundle::dsl::Shape::new(vec![], vec![]) }
+    fn input_schema(&self) -> frame::Schema { frame::Schema::new(vec![]) }
+    fn output_schema(&self) -> frame::Schema { frame::Schema::new(vec![]) }
+  }
+
+  fn models() -> Models {
+    let mut models = Models::new();
+
+    for name in ["airbnb", "linear_regression"].iter() {
+      let (name, transformer) = load_model(None, &format!("{}/{}", BUNDLES, name)).unwrap();
+      models.insert(name, transformer);
+    }
+
+    models
+  }
+
+  fn request(addr: SocketAddr, method: &str, url: &str, body: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+           method, url, body.len(), body).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+    let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+    (status, serde_json::from_str(body).unwrap())
+  }
+
+  #[test]
+  fn test_handle() {
+    let models = models();
+    let mut empty: &[u8] = &[];
+
+    let (status, json) = handle(&models, &Method::Get, "/health", &mut empty);
+    assert_eq!(status, 200);
+    assert_eq!(json["models"], serde_json::json!(["airbnb", "linear_regression"]));
+
+    let (status, json) = handle(&models, &Method::Get, "/models/airbnb/schema", &mut empty);
+    assert_eq!(status, 200);
+    assert_eq!(json["input"]["fields"][0]["name"], "room_type");
+    assert_eq!(json["output"]["fields"].as_array().unwrap().last().unwrap()["name"], "price_prediction");
+
+    assert_eq!(handle(&models, &Method::Get, "/models/nope/schema", &mut empty).0, 404);
+    assert_eq!(handle(&models, &Method::Get, "/models/airbnb/transform", &mut empty).0, 405);
+    assert_eq!(handle(&models, &Method::Get, "/models/airbnb/predict", &mut empty).0, 404);
+    assert_eq!(handle(&models, &Method::Post, "/models/airbnb/transform", &mut "{".as_bytes()).0, 400);
+    assert_eq!(handle(&models, &Method::Post, "/models/airbnb/transform", &mut "{\"rows\": []}".as_bytes()).0, 400);
+
+    let missing = r#"{"schema": {"fields": [{"name": "state", "type": "string"}]}, "rows": [["NY"]]}"#;
+    let (status, json) = handle(&models, &Method::Post, "/models/airbnb/transform", &mut missing.as_bytes());
+    assert_eq!(status, 422);
+    assert!(json["error"].as_str().unwrap().contains("room_type"));
+
+    let mistyped = FRAME.replace(r#""name": "bathrooms", "type": "double""#, r#""name": "bathrooms", "type": "string""#).
+      replace("2.0, 3.0", "\"2\", 3.0").replace("1.0, 1.0", "\"1\", 1.0");
+    let (status, json) = handle(&models, &Method::Post, "/models/airbnb/transform", &mut mistyped.as_bytes());
+    assert_eq!(status, 422);
+    assert!(json["error"].as_str().unwrap().contains("bathrooms: expected numeric or tensor<numeric>, got string"));
+  }
+
+  #[test]
+  fn test_large_tensor_dimensions() {
+    let models = models();
+    let field = r#"{"name": "big", "type": {"type": "tensor", "base": "double", "dimensions": [100000, 100000]}}"#;
+    let frame = FRAME.replace(r#"{"name": "room_type""#, &format!(r#"{}, {{"name": "room_type""#, field));
+
+    let overflowing = frame.replace(r#"["Entire home/apt""#, r#"[{"dimensions": [4294967296, 4294967296], "values": []}, "Entire home/apt""#).
+      replace(r#"["Private room""#, r#"[null, "Private room""#);
+    let (status, json) = handle(&models, &Method::Post, "/models/airbnb/transform", &mut overflowing.as_bytes());
+    assert_eq!(status, 400);
+    assert!(json["error"].as_str().unwrap().contains("too large"));
+
+    // null rows of a field with huge dimensions are not allocated
+    let nulls = frame.replace(r#"["Entire home/apt""#, r#"[null, "Entire home/apt""#).
+      replace(r#"["Private room""#, r#"[null, "Private room""#);
+    let (status, json) = handle(&models, &Method::Post, "/models/airbnb/transform", &mut nulls.as_bytes());
+    assert_eq!(status, 200);
+    assert_eq!(json["rows"][0][0], Value::Null);
+  }
+
+  #[test]
+  fn test_transformer_panic() {
+    let mut models = models();
+    models.insert(String::from("panicking"), Box::new(Panicking));
+
+    let (status, json) = handle(&models, &Method::Post, "/models/panicking/transform", &mut FRAME.as_bytes());
+    assert_eq!(status, 500);
+    assert_eq!(json["error"], "the transformer panicked");
+
+    assert_eq!(handle(&models, &Method::Post, "/models/airbnb/transform", &mut FRAME.as_bytes()).0, 200);
+  }
+
+  #[test]
+  fn test_serve() {
+    let (tx, rx) = mpsc::channel();
+
+    // the transformers are not Send, so the server thread loads its own
+    thread::spawn(move || {
+      let models = models();
+      let server = Server::http("127.0.0.1:0").unwrap();
+      tx.send(server.server_addr().to_ip().unwrap()).unwrap();
+      serve(&server, &models);
+    });
+    let addr = rx.recv().unwrap();
+
+    let (status, json) = request(addr, "GET", "/health", "");
+    assert_eq!(status, 200);
+    assert_eq!(json["status"], "ok");
+
+    let (status, json) = request(addr, "POST", "/models/airbnb/transform", FRAME);
+    assert_eq!(status, 200);
+    let frame = frame::LeapFrame::try_from(&json).unwrap();
+    assert_eq!(frame.try_doubles("price_prediction").unwrap(), &[172.5, 97.5]);
+
+    let (status, json) = request(addr, "POST", "/models/nope/transform", FRAME);
+    assert_eq!(status, 404);
+    assert_eq!(json["error"], "no model named nope");
+  }
+}
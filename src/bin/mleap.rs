@@ -0,0 +1,529 @@
+//! Command line tool for MLeap bundles.
+//!
+//! ```text
+//! mleap inspect <bundle>
+//! mleap score <bundle> [--input json|csv] [--cols a,b,...] [--chunk-size n] [--flatten]
+//! mleap convert <bundle> <out> [--format json|proto|mixed]
+//! mleap validate <bundle>
+//! ```
+//!
+//! Bundles are directories or zip files, `convert` writes a zip file when
+//! `<out>` ends in `.zip` and a directory otherwise.
+extern crate mleap;
+extern crate serde_json;
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::path;
+use std::process;
+use serde_json::Value;
+
+use mleap::bundle::{dsl, frame, ser, tform};
+use mleap::bundle::json::TryFrom;
+
+type Node = Box<tform::DefaultNode>;
+
+const USAGE: &'static str = "usage:
+  mleap inspect <bundle>
+  mleap score <bundle> [--input json|csv] [--cols a,b,...] [--chunk-size n] [--flatten]
+  mleap convert <bundle> <out> [--format json|proto|mixed]
+  mleap validate <bundle>";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Input {
+  Json,
+  Csv
+}
+
+struct ScoreOptions {
+  input: Input,
+  cols: Option<Vec<String>>,
+  chunk_size: usize,
+  tensor_format: frame::csv::TensorFormat
+}
+
+impl Default for ScoreOptions {
+  fn default() -> ScoreOptions {
+    ScoreOptions {
+      input: Input::Json,
+      cols: None,
+      chunk_size: 1024,
+      tensor_format: frame::csv::TensorFormat::Json
+    }
+  }
+}
+
+fn open_builder(path: &str) -> Result<Box<ser::Builder>, String> {
+  // a FileBuilder creates missing directories, which is not what reading wants
+  if !path::Path::new(path).exists() {
+    return Err(format!("{}: no such bundle", path));
+  }
+
+  ser::open_builder(path).map_err(|err| format!("{}: {:?}", path, err))
+}
+
+fn format_name(format: &dsl::Format) -> String {
+  match Value::from(format) {
+    Value::String(s) => s,
+    v => v.to_string()
+  }
+}
+
+fn parse_format(s: &str) -> Result<dsl::Format, String> {
+  dsl::Format::try_from(&Value::from(s)).map_err(|_| format!("unknown bundle format: {}", s))
+}
+
+/// Pipelines list their children in the `nodes` attribute, each one stored
+/// under `<name>.node`.
+fn children(model: &dsl::Model) -> Vec<String> {
+  model.get_string_vector("nodes").map(|names| names.to_vec()).unwrap_or_default()
+}
+
+fn write_sockets<W: Write>(out: &mut W, indent: &str, label: &str, sockets: &[dsl::Socket]) -> io::Result<()> {
+  for socket in sockets.iter() {
+    match writeln!(out, "{}  {}: {} ({})", indent, label, socket.name(), socket.port()) {
+      Ok(_) => (),
+      Err(err) => return Err(err)
+    }
+  }
+
+  Ok(())
+}
+
+fn inspect_node<W: Write>(ctx: &ser::Context<Node>, out: &mut W, depth: usize) -> Result<(), String> {
+  let model = match ctx.read_dsl_model() {
+    Ok(model) => model,
+    Err(err) => return Err(format!("cannot read model: {:?}", err))
+  };
+  let node = match ctx.read_dsl_node() {
+    Ok(node) => node,
+    Err(err) => return Err(format!("cannot read node: {:?}", err))
+  };
+
+  let indent = "  ".repeat(depth);
+  let r = writeln!(out, "{}{} ({})", indent, node.name(), model.op()).and_then(|_| {
+    write_sockets(out, &indent, "input", node.shape().inputs())
+  }).and_then(|_| {
+    write_sockets(out, &indent, "output", node.shape().outputs())
+  });
+
+  if let Err(err) = r {
+    return Err(format!("{}", err));
+  }
+
+  for name in children(&model).iter() {
+    let r = ctx.try_next(&format!("{}.node", name)).
+      map_err(|err| format!("{}: {:?}", name, err)).
+      and_then(|ctx| inspect_node(&ctx, out, depth + 1));
+
+    match r {
+      Ok(_) => (),
+      Err(err) => return Err(err)
+    }
+  }
+
+  Ok(())
+}
+
+fn inspect<W: Write>(builder: Box<ser::Builder>, out: &mut W) -> Result<(), String> {
+  let registry = tform::default_registry();
+  let ctx = ser::Context::new(builder, &registry);
+
+  let bundle = match ctx.read_dsl_bundle() {
+    Ok(bundle) => bundle,
+    Err(err) => return Err(format!("cannot read bundle.json: {:?}", err))
+  };
+
+  let r = writeln!(out, "name: {}", bundle.name()).
+    and_then(|_| writeln!(out, "uid: {}", bundle.uid())).
+    and_then(|_| writeln!(out, "format: {}", format_name(bundle.format()))).
+    and_then(|_| writeln!(out, "version: {}", bundle.version())).
+    and_then(|_| writeln!(out, "root:"));
+
+  if let Err(err) = r {
+    return Err(format!("{}", err));
+  }
+
+  ctx.try_next("root").
+    map_err(|err| format!("root: {:?}", err)).
+    and_then(|ctx| inspect_node(&ctx.with_format(*bundle.format()), out, 1))
+}
+
+fn validate_node(ctx: &ser::Context<Node>, path: &str, problems: &mut Vec<String>) {
+  let model = match ctx.read_dsl_model() {
+    Ok(model) => model,
+    Err(err) => return problems.push(format!("{}: cannot read model: {:?}", path, err))
+  };
+  let node = match ctx.read_dsl_node() {
+    Ok(node) => node,
+    Err(err) => return problems.push(format!("{}: cannot read node: {:?}", path, err))
+  };
+  let names = children(&model);
+
+  match ctx.registry().get_op_for_name(model.op()) {
+    None => problems.push(format!("{}: op {} is not registered", path, model.op())),
+    // loading a pipeline loads all of its children, which are checked one
+    // by one below instead
+    Some(_) if !names.is_empty() => (),
+    Some(op) => {
+      let r = op.load_model(&model, ctx).and_then(|m| op.load(&node, m, ctx));
+
+      if let Err(err) = r {
+        problems.push(format!("{}: invalid {} model: {:?}", path, model.op(), err));
+      }
+    }
+  }
+
+  for name in names.iter() {
+    let c_path = format!("{}/{}", path, name);
+
+    match ctx.try_next(&format!("{}.node", name)) {
+      Ok(ctx) => validate_node(&ctx, &c_path, problems),
+      Err(err) => problems.push(format!("{}: {:?}", c_path, err))
+    }
+  }
+}
+
+/// Every problem found in the bundle, empty if all of its ops are
+/// registered and load.
+fn validate(builder: Box<ser::Builder>) -> Vec<String> {
+  let registry = tform::default_registry();
+  let ctx = ser::Context::new(builder, &registry);
+  let mut problems = Vec::new();
+
+  let r = ctx.read_dsl_bundle().and_then(|bundle| {
+    ctx.try_next("root").map(|ctx| ctx.with_format(*bundle.format()))
+  });
+
+  match r {
+    Ok(ctx) => validate_node(&ctx, "root", &mut problems),
+    Err(err) => problems.push(format!("cannot read bundle: {:?}", err))
+  }
+
+  problems
+}
+
+fn convert(builder: Box<ser::Builder>, out: &str, format: Option<dsl::Format>) -> Result<(), String> {
+  let registry = tform::default_registry();
+  let ctx = ser::Context::new(builder, &registry);
+
+  let (bundle, root) = match ctx.read_bundle() {
+    Ok(r) => r,
+    Err(err) => return Err(format!("cannot read bundle: {:?}", err))
+  };
+  let bundle = dsl::Bundle::new(bundle.uid().clone(),
+                                bundle.name().to_string(),
+                                format.unwrap_or(*bundle.format()),
+                                bundle.version().clone());
+
+  let r = if out.ends_with(".zip") {
+    ser::ZipBuilder::try_create(out).and_then(|zip| {
+      ser::Context::new(Box::new(zip.clone()), &registry).write_bundle(&bundle, &root).and_then(|_| zip.finish())
+    })
+  } else {
+    ser::FileBuilder::try_new(out).and_then(|dir| {
+      ser::Context::new(Box::new(dir), &registry).write_bundle(&bundle, &root)
+    })
+  };
+
+  r.map_err(|err| format!("{}: {:?}", out, err))
+}
+
+fn score_json<R: Read, W: Write>(transformer: &tform::DefaultNode,
+                                 mut input: R,
+                                 out: &mut W,
+                                 options: &ScoreOptions) -> Result<(), String> {
+  let mut buf = String::new();
+  if let Err(err) = input.read_to_string(&mut buf) {
+    return Err(format!("{}", err));
+  }
+
+  let mut frame = match serde_json::from_str(&buf).map_err(|err| format!("{}", err)).and_then(|json: Value| {
+    frame::LeapFrame::try_from(&json).map_err(|err| format!("{:?}", err))
+  }) {
+    Ok(frame) => frame,
+    Err(err) => return Err(err)
+  };
+
+  let r = transformer.transform(&mut frame).and_then(|_| {
+    match options.cols {
+      Some(ref cols) => {
+        let names: Vec<&str> = cols.iter().map(|c| c.as_str()).collect();
+        frame.select(&names).map(|_| ())
+      },
+      None => Ok(())
+    }
+  });
+
+  if let Err(err) = r {
+    return Err(format!("{:?}", err));
+  }
+
+  serde_json::to_writer(&mut *out, &Value::from(&frame)).
+    map_err(|err| format!("{}", err)).
+    and_then(|_| writeln!(out).map_err(|err| format!("{}", err)))
+}
+
+fn score_csv<R: Read, W: Write>(transformer: &tform::DefaultNode,
+                                input: R,
+                                out: W,
+                                options: &ScoreOptions) -> Result<(), String> {
+  let reader = match frame::csv::CsvReader::new(input, transformer.input_schema(), options.chunk_size) {
+    Ok(reader) => reader,
+    Err(err) => return Err(format!("{:?}", err))
+  };
+  let mut out = Some(out);
+  let mut writer = None;
+
+  for chunk in reader {
+    let frame = match chunk.and_then(|mut frame| transformer.transform(&mut frame).map(|_| frame)) {
+      Ok(frame) => frame,
+      Err(err) => return Err(format!("{:?}", err))
+    };
+
+    // without --cols every column of the scored frame is written
+    if writer.is_none() {
+      let cols = options.cols.clone().unwrap_or_else(|| {
+        frame.cols().iter().map(|c| c.name().to_string()).collect()
+      });
+      writer = out.take().map(|out| frame::csv::CsvWriter::new(out, cols, options.tensor_format));
+    }
+
+    let r = writer.as_mut().map(|w| w.write_frame(&frame)).unwrap_or(Ok(()));
+    if let Err(err) = r {
+      return Err(format!("{:?}", err));
+    }
+  }
+
+  match writer {
+    Some(mut w) => w.flush().map_err(|err| format!("{:?}", err)),
+    None => Ok(())
+  }
+}
+
+fn score<R: Read, W: Write>(builder: Box<ser::Builder>,
+                            input: R,
+                            mut out: W,
+                            options: &ScoreOptions) -> Result<(), String> {
+  let registry = tform::default_registry();
+  let ctx = ser::Context::new(builder, &registry);
+
+  let transformer = match ctx.read_bundle() {
+    Ok((_, transformer)) => transformer,
+    Err(err) => return Err(format!("cannot read bundle: {:?}", err))
+  };
+
+  match options.input {
+    Input::Json => score_json(transformer.as_ref(), input, &mut out, options),
+    Input::Csv => score_csv(transformer.as_ref(), input, out, options)
+  }
+}
+
+fn parse_score_options(args: &[String]) -> Result<ScoreOptions, String> {
+  let mut options = ScoreOptions::default();
+  let mut i = 0;
+
+  while i < args.len() {
+    let value = args.get(i + 1).map(|s| s.as_str());
+
+    match (args[i].as_str(), value) {
+      ("--input", Some("json")) => options.input = Input::Json,
+      ("--input", Some("csv")) => options.input = Input::Csv,
+      ("--cols", Some(cols)) => options.cols = Some(cols.split(',').map(|c| c.trim().to_string()).collect()),
+      ("--chunk-size", Some(n)) => {
+        options.chunk_size = match n.parse() {
+          Ok(n) => n,
+          Err(_) => return Err(format!("invalid chunk size: {}", n))
+        }
+      },
+      ("--flatten", _) => {
+        options.tensor_format = frame::csv::TensorFormat::Flatten;
+        i += 1;
+        continue;
+      },
+      (arg, _) => return Err(format!("invalid argument: {}", arg))
+    }
+
+    i += 2;
+  }
+
+  Ok(options)
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+  let command = args.get(0).map(|s| s.as_str());
+
+  match (command, args.get(1)) {
+    (Some("inspect"), Some(bundle)) if args.len() == 2 => {
+      let stdout = io::stdout();
+      open_builder(bundle).and_then(|b| inspect(b, &mut stdout.lock()))
+    },
+    (Some("score"), Some(bundle)) => {
+      parse_score_options(&args[2..]).and_then(|options| {
+        open_builder(bundle).and_then(|b| {
+          let stdin = io::stdin();
+          let stdout = io::stdout();
+          score(b, stdin.lock(), io::BufWriter::new(stdout.lock()), &options)
+        })
+      })
+    },
+    (Some("convert"), Some(bundle)) if args.len() >= 3 => {
+      let format = match (args.get(3).map(|s| s.as_str()), args.get(4)) {
+        (None, _) => Ok(None),
+        (Some("--format"), Some(f)) if args.len() == 5 => parse_format(f).map(Some),
+        _ => Err(String::from(USAGE))
+      };
+
+      format.and_then(|format| {
+        open_builder(bundle).and_then(|b| convert(b, &args[2], format))
+      })
+    },
+    (Some("validate"), Some(bundle)) if args.len() == 2 => {
+      open_builder(bundle).and_then(|b| {
+        let problems = validate(b);
+
+        if problems.is_empty() {
+          println!("{}: ok", bundle);
+          Ok(())
+        } else {
+          Err(problems.join("\n"))
+        }
+      })
+    },
+    _ => Err(String::from(USAGE))
+  }
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+
+  if let Err(err) = run(&args) {
+    let _ = writeln!(io::stderr(), "{}", err);
+    process::exit(1);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::collections::HashMap;
+  use std::fs;
+
+  const BUNDLES: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bundles");
+
+  fn bundle(name: &str) -> Box<ser::Builder> {
+    open_builder(&format!("{}/{}", BUNDLES, name)).unwrap()
+  }
+
+  fn copy_bundle(name: &str) -> ser::MemoryBuilder {
+    fn copy(dir: &path::Path, prefix: &str, entries: &mut HashMap<String, Vec<u8>>) {
+      for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        let name = format!("{}{}", prefix, entry.file_name().to_str().unwrap());
+
+        if entry.path().is_dir() {
+          copy(&entry.path(), &format!("{}/", name), entries);
+        } else {
+          entries.insert(name, fs::read(entry.path()).unwrap());
+        }
+      }
+    }
+
+    let mut entries = HashMap::new();
+    copy(&path::Path::new(BUNDLES).join(name), "", &mut entries);
+    ser::MemoryBuilder::from_map(entries)
+  }
+
+  #[test]
+  fn test_inspect() {
+    let mut out = Vec::new();
+    inspect(bundle("airbnb"), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.starts_with("name: airbnb\n"));
+    assert!(out.contains("format: json\n"));
+    assert!(out.contains("\n    room_type_indexer (string_indexer)\n      input: room_type (input)\n      output: room_type_index (output)\n"));
+    assert!(out.contains("\n      output: price_prediction (prediction)\n"));
+  }
+
+  #[test]
+  fn test_validate() {
+    assert!(validate(bundle("airbnb")).is_empty());
+
+    let mut entries = copy_bundle("airbnb").to_map();
+    entries.remove("root/price_regression.node/model.json");
+    let json = String::from_utf8(entries["root/state_indexer.node/model.json"].clone()).unwrap();
+    entries.insert(String::from("root/state_indexer.node/model.json"), json.replace("string_indexer", "state_lookup").into_bytes());
+
+    let problems = validate(Box::new(ser::MemoryBuilder::from_map(entries)));
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].starts_with("root/state_indexer: op state_lookup is not registered"));
+    assert!(problems[1].starts_with("root/price_regression: cannot read model"));
+  }
+
+  #[test]
+  fn test_validate_missing_attribute() {
+    let mut entries = copy_bundle("linear_regression").to_map();
+    let json = String::from_utf8(entries["root/model.json"].clone()).unwrap();
+    entries.insert(String::from("root/model.json"), json.replace("\"intercept\"", "\"offset\"").into_bytes());
+
+    let problems = validate(Box::new(ser::MemoryBuilder::from_map(entries)));
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].starts_with("root: invalid linear_regression model"));
+  }
+
+  #[test]
+  fn test_score_csv() {
+    let input = "room_type,state,bathrooms,bedrooms\n\
+                 Entire home/apt,NY,2.0,3.0\n\
+                 Private room,CA,1.0,1.0\n";
+    let options = ScoreOptions {
+      input: Input::Csv,
+      cols: Some(vec![String::from("room_type_index"), String::from("price_prediction")]),
+      .. ScoreOptions::default()
+    };
+    let mut out = Vec::new();
+
+    score(bundle("airbnb"), input.as_bytes(), &mut out, &options).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(out, "room_type_index,price_prediction\n0,172.5\n1,97.5\n");
+  }
+
+  #[test]
+  fn test_score_csv_nulls() {
+    let input = "room_type,state,bathrooms,bedrooms\n\
+                 Entire home/apt,NY,,3.0\n";
+    let options = ScoreOptions {
+      input: Input::Csv,
+      cols: Some(vec![String::from("bathrooms"), String::from("price_prediction")]),
+      .. ScoreOptions::default()
+    };
+    let mut out = Vec::new();
+
+    // the assembler reads the null as NaN
+    score(bundle("airbnb"), input.as_bytes(), &mut out, &options).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "bathrooms,price_prediction\n,NaN\n");
+  }
+
+  #[test]
+  fn test_convert() {
+    let dir = env::temp_dir().join(format!("mleap-convert-{}", process::id()));
+    let out = dir.to_str().unwrap().to_string();
+    let zip = format!("{}.zip", out);
+
+    convert(bundle("airbnb"), &zip, None).unwrap();
+    convert(open_builder(&zip).unwrap(), &out, parse_format("proto").ok()).unwrap();
+
+    assert!(dir.join("root/state_indexer.node/model.pb").is_file());
+    assert!(!dir.join("root/model.json").exists());
+    assert!(validate(open_builder(&out).unwrap()).is_empty());
+
+    let mut listing = Vec::new();
+    inspect(open_builder(&out).unwrap(), &mut listing).unwrap();
+    assert!(String::from_utf8(listing).unwrap().contains("format: proto\n"));
+
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_file(&zip).unwrap();
+  }
+}
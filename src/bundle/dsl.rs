@@ -2,13 +2,22 @@ use std::collections::HashMap;
 use uuid::Uuid;
 use semver::Version;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DenseTensor<T> {
   dimensions: Vec<usize>,
   values: Vec<T>
 }
 
-#[derive(Debug)]
+/// Only the non-zero values of a tensor. Indices are offsets into the
+/// row-major dense values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseTensor<T> {
+  dimensions: Vec<usize>,
+  indices: Vec<usize>,
+  values: Vec<T>
+}
+
+#[derive(Debug, PartialEq)]
 pub enum VectorValue {
   Bool(Vec<bool>),
   String(Vec<String>),
@@ -21,7 +30,7 @@ pub enum VectorValue {
   ByteString(Vec<Vec<u8>>)
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum TensorValue {
   Bool(DenseTensor<bool>),
   String(DenseTensor<String>),
@@ -34,7 +43,7 @@ pub enum TensorValue {
   ByteString(DenseTensor<Vec<u8>>)
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum BasicValue {
   Bool(bool),
   String(String),
@@ -47,44 +56,66 @@ pub enum BasicValue {
   ByteString(Vec<u8>)
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Attribute {
   Basic(BasicValue),
   Array(VectorValue),
   Tensor(TensorValue)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Socket {
   name: String,
   port: String
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Shape {
   inputs: Vec<Socket>,
   outputs: Vec<Socket>
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Model {
   op: String,
   attributes: HashMap<String, Attribute>
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Node {
   name: String,
   shape: Shape
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Split {
+  Continuous {
+    feature_index: usize,
+    threshold: f64
+  },
+  Categorical {
+    feature_index: usize,
+    num_categories: usize,
+    categories: Vec<f64>,
+    is_left: bool
+  }
+}
+
+/// Decision tree nodes are stored in pre-order, so the left child of an
+/// internal node always directly follows it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeNode {
+  Internal(Split),
+  Leaf(Vec<f64>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ConcreteFormat {
   Json,
   Proto
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Format {
   Concrete(ConcreteFormat),
   Mixed
@@ -110,6 +141,66 @@ impl<T> DenseTensor<T> {
   pub fn values(&self) -> &[T] { &self.values }
 }
 
+impl<T> SparseTensor<T> {
+  pub fn new(dimensions: Vec<usize>, indices: Vec<usize>, values: Vec<T>) -> SparseTensor<T> {
+    SparseTensor {
+      dimensions: dimensions,
+      indices: indices,
+      values: values
+    }
+  }
+
+  /// Like `new`, but `None` unless there is one index per value and every
+  /// index is within the dense tensor.
+  pub fn try_new(dimensions: Vec<usize>, indices: Vec<usize>, values: Vec<T>) -> Option<SparseTensor<T>> {
+    let tensor = SparseTensor::new(dimensions, indices, values);
+
+    if tensor.is_valid() { Some(tensor) } else { None }
+  }
+
+  pub fn is_valid(&self) -> bool {
+    match self.dimensions.iter().fold(Some(1usize), |acc, d| acc.and_then(|n| n.checked_mul(*d))) {
+      Some(len) => self.indices.len() == self.values.len() && self.indices.iter().all(|i| *i < len),
+      None => false
+    }
+  }
+
+  pub fn dimensions(&self) -> &[usize] { &self.dimensions }
+  pub fn indices(&self) -> &[usize] { &self.indices }
+  pub fn values(&self) -> &[T] { &self.values }
+
+  /// Number of values in the dense tensor.
+  pub fn dense_len(&self) -> usize { self.dimensions.iter().product() }
+}
+
+impl<T: Clone + Default> SparseTensor<T> {
+  pub fn to_dense(&self) -> DenseTensor<T> {
+    let mut values = vec![T::default(); self.dense_len()];
+
+    for (i, v) in self.indices.iter().zip(self.values.iter()) {
+      values[*i] = v.clone();
+    }
+
+    DenseTensor::new(self.dimensions.clone(), values)
+  }
+}
+
+impl TensorValue {
+  pub fn len(&self) -> usize {
+    match self {
+      &TensorValue::Bool(ref t) => t.values().len(),
+      &TensorValue::String(ref t) => t.values().len(),
+      &TensorValue::Byte(ref t) => t.values().len(),
+      &TensorValue::Short(ref t) => t.values().len(),
+      &TensorValue::Int(ref t) => t.values().len(),
+      &TensorValue::Long(ref t) => t.values().len(),
+      &TensorValue::Float(ref t) => t.values().len(),
+      &TensorValue::Double(ref t) => t.values().len(),
+      &TensorValue::ByteString(ref t) => t.values().len()
+    }
+  }
+}
+
 impl Socket {
   pub fn new(name: String, port: String) -> Socket {
     Socket {
@@ -147,6 +238,24 @@ impl Shape {
   pub fn inputs(&self) -> &[Socket] { &self.inputs }
   pub fn outputs(&self) -> &[Socket] { &self.outputs }
 
+  /// Sockets `input0..n` and `output0..n` of multi-column ops.
+  pub fn with_multi_io(inputs: Vec<String>, outputs: Vec<String>) -> Shape {
+    Shape {
+      inputs: inputs.into_iter().enumerate().map(|(i, name)| Socket::new(name, format!("input{}", i))).collect(),
+      outputs: outputs.into_iter().enumerate().map(|(i, name)| Socket::new(name, format!("output{}", i))).collect()
+    }
+  }
+
+  pub fn get_multi_io(&self) -> Option<Vec<(&Socket, &Socket)>> {
+    let mut io = Vec::new();
+
+    while let Some(pair) = self.get_io(&format!("input{}", io.len()), &format!("output{}", io.len())) {
+      io.push(pair);
+    }
+
+    if io.is_empty() { None } else { Some(io) }
+  }
+
   pub fn get_standard_io(&self) -> Option<(&Socket, &Socket)> {
     self.get_io("input", "output")
   }
@@ -192,6 +301,24 @@ impl Model {
     })
   }
 
+  pub fn get_bool(&self, name: &str) -> Option<bool> {
+    self.attributes.get(name).and_then(|x| {
+      match x {
+        &Attribute::Basic(BasicValue::Bool(b)) => Some(b),
+        _ => None
+      }
+    })
+  }
+
+  pub fn get_int(&self, name: &str) -> Option<i32> {
+    self.attributes.get(name).and_then(|x| {
+      match x {
+        &Attribute::Basic(BasicValue::Int(i)) => Some(i),
+        _ => None
+      }
+    })
+  }
+
   pub fn get_long(&self, name: &str) -> Option<i64> {
     self.attributes.get(name).and_then(|x| {
       match x {
@@ -210,6 +337,33 @@ impl Model {
     })
   }
 
+  pub fn get_double_vector(&self, name: &str) -> Option<&[f64]> {
+    self.attributes.get(name).and_then(|x| {
+      match x {
+        &Attribute::Array(VectorValue::Double(ref v)) => Some(v.as_slice()),
+        _ => None
+      }
+    })
+  }
+
+  pub fn get_int_vector(&self, name: &str) -> Option<&[i32]> {
+    self.attributes.get(name).and_then(|x| {
+      match x {
+        &Attribute::Array(VectorValue::Int(ref v)) => Some(v.as_slice()),
+        _ => None
+      }
+    })
+  }
+
+  pub fn get_string(&self, name: &str) -> Option<&str> {
+    self.attributes.get(name).and_then(|x| {
+      match x {
+        &Attribute::Basic(BasicValue::String(ref s)) => Some(s.as_str()),
+        _ => None
+      }
+    })
+  }
+
   pub fn get_string_vector(&self, name: &str) -> Option<&[String]> {
     self.attributes.get(name).and_then(|x| {
       match x {
@@ -232,6 +386,24 @@ impl Node {
   pub fn shape(&self) -> &Shape { &self.shape }
 }
 
+impl Split {
+  pub fn feature_index(&self) -> usize {
+    match self {
+      &Split::Continuous { feature_index, .. } => feature_index,
+      &Split::Categorical { feature_index, .. } => feature_index
+    }
+  }
+
+  pub fn goes_left(&self, value: f64) -> bool {
+    match self {
+      &Split::Continuous { threshold, .. } => value <= threshold,
+      &Split::Categorical { ref categories, is_left, .. } => {
+        categories.contains(&value) == is_left
+      }
+    }
+  }
+}
+
 impl Bundle {
   pub fn new(uid: Uuid,
              name: String,
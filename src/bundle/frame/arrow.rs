@@ -0,0 +1,421 @@
+//! Conversion between Arrow record batches and LeapFrames, enabled with the
+//! `arrow` feature.
+//!
+//! Primitive, string and binary arrays map onto scalar columns, lists onto
+//! vector columns and fixed size lists onto tensor columns. Tensors with more
+//! than one dimension are flattened, with their dimensions kept in the
+//! `mleap.tensor.dimensions` field metadata. Sparse tensors are written dense.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::{Array, ArrayRef, BinaryArray, BooleanArray, FixedSizeListArray, ListArray, PrimitiveArray,
+                  RecordBatch, RecordBatchOptions, StringArray};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{ArrowPrimitiveType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type};
+use arrow_buffer::{NullBuffer, OffsetBuffer, ScalarBuffer};
+use arrow_schema::{DataType as ArrowType, Field, FieldRef, Schema as ArrowSchema};
+
+use bundle::dsl::{DenseTensor, SparseTensor};
+use super::{BasicType, Col, ColData, DataShape, Error, LeapFrame, Result};
+
+const DIMENSIONS_KEY: &'static str = "mleap.tensor.dimensions";
+
+fn arrow_type(base: BasicType) -> ArrowType {
+  match base {
+    BasicType::Bool => ArrowType::Boolean,
+    BasicType::String => ArrowType::Utf8,
+    BasicType::Byte => ArrowType::Int8,
+    BasicType::Short => ArrowType::Int16,
+    BasicType::Int => ArrowType::Int32,
+    BasicType::Long => ArrowType::Int64,
+    BasicType::Float => ArrowType::Float32,
+    BasicType::Double => ArrowType::Float64,
+    BasicType::ByteString => ArrowType::Binary
+  }
+}
+
+fn primitive<T: ArrowPrimitiveType>(v: Vec<T::Native>, nulls: Option<NullBuffer>) -> ArrayRef {
+  Arc::new(PrimitiveArray::<T>::new(ScalarBuffer::from(v), nulls))
+}
+
+/// Moves the values of a scalar column into an Arrow array.
+fn scalar_array(data: ColData, nulls: Option<NullBuffer>) -> Result<ArrayRef> {
+  let array: ArrayRef = match data {
+    ColData::Bool(v) => Arc::new(BooleanArray::new(v.into_iter().collect(), nulls)),
+    ColData::String(v) => Arc::new(StringArray::new(OffsetBuffer::from_lengths(v.iter().map(|s| s.len())), v.concat().into_bytes().into(), nulls)),
+    ColData::Byte(v) => primitive::<Int8Type>(v, nulls),
+    ColData::Short(v) => primitive::<Int16Type>(v, nulls),
+    ColData::Int(v) => primitive::<Int32Type>(v, nulls),
+    ColData::Long(v) => primitive::<Int64Type>(v, nulls),
+    ColData::Float(v) => primitive::<Float32Type>(v, nulls),
+    ColData::Double(v) => primitive::<Float64Type>(v, nulls),
+    ColData::ByteString(v) => Arc::new(BinaryArray::new(OffsetBuffer::from_lengths(v.iter().map(|s| s.len())), v.concat().into(), nulls)),
+    data => return Err(Error::InvalidType(format!("Expected a scalar column, got {}", data.data_type())))
+  };
+
+  Ok(array)
+}
+
+fn list_array<T>(base: BasicType, v: Vec<Vec<T>>, nulls: Option<NullBuffer>, scalars: fn(Vec<T>) -> ColData) -> Result<(ArrowType, ArrayRef)> {
+  let field: FieldRef = Arc::new(Field::new_list_field(arrow_type(base), false));
+  let offsets = OffsetBuffer::from_lengths(v.iter().map(|x| x.len()));
+  let values = v.into_iter().flat_map(|x| x.into_iter()).collect();
+
+  scalar_array(scalars(values), None).map(|values| {
+    (ArrowType::List(field.clone()), Arc::new(ListArray::new(field, offsets, values, nulls)) as ArrayRef)
+  })
+}
+
+fn is_null(nulls: &Option<NullBuffer>, row: usize) -> bool {
+  nulls.as_ref().map(|n| n.is_null(row)).unwrap_or(false)
+}
+
+/// Densifies the valid rows of a sparse column, null rows stay empty.
+fn dense_rows<T: Clone + Default>(v: Vec<SparseTensor<T>>, nulls: &Option<NullBuffer>) -> Vec<DenseTensor<T>> {
+  v.iter().enumerate().map(|(row, t)| {
+    if is_null(nulls, row) { DenseTensor::new(t.dimensions().to_vec(), vec![]) } else { t.to_dense() }
+  }).collect()
+}
+
+/// Null rows have no values in the column, they are filled in with
+/// defaults here.
+fn tensor_array<T>(base: BasicType, v: Vec<DenseTensor<T>>, dims: &[usize], nulls: Option<NullBuffer>, scalars: fn(Vec<T>) -> ColData) -> Result<(ArrowType, ArrayRef)>
+  where T: Clone + Default {
+  let too_large = || Error::InvalidType(format!("Tensors with dimensions {:?} are too large", dims));
+  let size = match dims.iter().fold(Some(1usize), |acc, d| acc.and_then(|n| n.checked_mul(*d))) {
+    Some(size) if size <= i32::MAX as usize => size,
+    _ => return Err(too_large())
+  };
+
+  if let Some((_, t)) = v.iter().enumerate().find(|&(row, t)| !is_null(&nulls, row) && t.values().len() != size) {
+    return Err(Error::InvalidType(format!("Tensors with dimensions {:?} and {:?} in one column", dims, t.dimensions())));
+  }
+
+  let mut values = Vec::new();
+  match v.len().checked_mul(size) {
+    Some(len) if values.try_reserve_exact(len).is_ok() => (),
+    _ => return Err(too_large())
+  }
+
+  for (row, t) in v.iter().enumerate() {
+    if is_null(&nulls, row) {
+      values.extend((0..size).map(|_| T::default()));
+    } else {
+      values.extend(t.values().iter().cloned());
+    }
+  }
+
+  let field: FieldRef = Arc::new(Field::new_list_field(arrow_type(base), false));
+
+  scalar_array(scalars(values), None).map(|values| {
+    (ArrowType::FixedSizeList(field.clone(), size as i32), Arc::new(FixedSizeListArray::new(field, size as i32, values, nulls)) as ArrayRef)
+  })
+}
+
+/// The Arrow field and array of a column, consuming its values.
+fn col_array(col: Col) -> Result<(Field, ArrayRef)> {
+  let Col { name, data, validity } = col;
+  let nullable = validity.is_some();
+  let nulls = validity.map(NullBuffer::from);
+  let data_type = data.data_type();
+  let base = data_type.base();
+  // without dimensions in the schema only empty tensors fit
+  let dims = match *data_type.shape() {
+    DataShape::Tensor(Some(ref dims)) => dims.clone(),
+    _ => vec![0]
+  };
+
+  let r = match data {
+    ColData::BoolVector(v) => list_array(base, v, nulls, ColData::Bool),
+    ColData::StringVector(v) => list_array(base, v, nulls, ColData::String),
+    ColData::ByteVector(v) => list_array(base, v, nulls, ColData::Byte),
+    ColData::ShortVector(v) => list_array(base, v, nulls, ColData::Short),
+    ColData::IntVector(v) => list_array(base, v, nulls, ColData::Int),
+    ColData::LongVector(v) => list_array(base, v, nulls, ColData::Long),
+    ColData::FloatVector(v) => list_array(base, v, nulls, ColData::Float),
+    ColData::DoubleVector(v) => list_array(base, v, nulls, ColData::Double),
+    ColData::ByteStringVector(v) => list_array(base, v, nulls, ColData::ByteString),
+    ColData::BoolTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Bool),
+    ColData::StringTensor(v) => tensor_array(base, v, &dims, nulls, ColData::String),
+    ColData::ByteTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Byte),
+    ColData::ShortTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Short),
+    ColData::IntTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Int),
+    ColData::LongTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Long),
+    ColData::FloatTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Float),
+    ColData::DoubleTensor(v) => tensor_array(base, v, &dims, nulls, ColData::Double),
+    ColData::ByteStringTensor(v) => tensor_array(base, v, &dims, nulls, ColData::ByteString),
+    ColData::SparseBoolTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Bool),
+    ColData::SparseStringTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::String),
+    ColData::SparseByteTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Byte),
+    ColData::SparseShortTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Short),
+    ColData::SparseIntTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Int),
+    ColData::SparseLongTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Long),
+    ColData::SparseFloatTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Float),
+    ColData::SparseDoubleTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::Double),
+    ColData::SparseByteStringTensor(v) => tensor_array(base, dense_rows(v, &nulls), &dims, nulls, ColData::ByteString),
+    data => scalar_array(data, nulls).map(|array| (arrow_type(base), array))
+  };
+
+  r.map(|(arrow_type, array)| {
+    let field = Field::new(name, arrow_type, nullable);
+
+    match *data_type.shape() {
+      DataShape::Tensor(Some(ref dims)) if dims.len() > 1 => {
+        let dims: Vec<String> = dims.iter().map(|d| d.to_string()).collect();
+        let mut metadata = HashMap::with_capacity(1);
+        metadata.insert(String::from(DIMENSIONS_KEY), dims.join(","));
+
+        (field.with_metadata(metadata), array)
+      },
+      _ => (field, array)
+    }
+  })
+}
+
+/// Reads the values of a primitive, string or binary array, null slots hold
+/// placeholders.
+fn scalar_data(array: &ArrayRef) -> Result<ColData> {
+  let data = match *array.data_type() {
+    ArrowType::Boolean => ColData::Bool(array.as_boolean().values().iter().collect()),
+    ArrowType::Utf8 => ColData::String(array.as_string::<i32>().iter().map(|s| s.unwrap_or("").to_string()).collect()),
+    ArrowType::LargeUtf8 => ColData::String(array.as_string::<i64>().iter().map(|s| s.unwrap_or("").to_string()).collect()),
+    ArrowType::Int8 => ColData::Byte(array.as_primitive::<Int8Type>().values().to_vec()),
+    ArrowType::Int16 => ColData::Short(array.as_primitive::<Int16Type>().values().to_vec()),
+    ArrowType::Int32 => ColData::Int(array.as_primitive::<Int32Type>().values().to_vec()),
+    ArrowType::Int64 => ColData::Long(array.as_primitive::<Int64Type>().values().to_vec()),
+    ArrowType::Float32 => ColData::Float(array.as_primitive::<Float32Type>().values().to_vec()),
+    ArrowType::Float64 => ColData::Double(array.as_primitive::<Float64Type>().values().to_vec()),
+    ArrowType::Binary => ColData::ByteString(array.as_binary::<i32>().iter().map(|b| b.unwrap_or(&[]).to_vec()).collect()),
+    ArrowType::LargeBinary => ColData::ByteString(array.as_binary::<i64>().iter().map(|b| b.unwrap_or(&[]).to_vec()).collect()),
+    ref dt => return Err(Error::InvalidType(format!("Unsupported Arrow type {}", dt)))
+  };
+
+  Ok(data)
+}
+
+fn split<T: Clone>(values: &[T], ranges: &[(usize, usize)]) -> Vec<Vec<T>> {
+  ranges.iter().map(|&(start, end)| values[start..end].to_vec()).collect()
+}
+
+fn tensors<T: Clone>(values: &[T], ranges: &[(usize, usize)], dims: &[usize]) -> Vec<DenseTensor<T>> {
+  ranges.iter().map(|&(start, end)| DenseTensor::new(dims.to_vec(), values[start..end].to_vec())).collect()
+}
+
+/// Reads list arrays into vector columns, or into tensor columns with `dims`.
+fn nested_data(values: &ArrayRef, ranges: &[(usize, usize)], dims: Option<&[usize]>) -> Result<ColData> {
+  scalar_data(values).map(|values| {
+    match (values, dims) {
+      (ColData::Bool(v), None) => ColData::BoolVector(split(&v, ranges)),
+      (ColData::String(v), None) => ColData::StringVector(split(&v, ranges)),
+      (ColData::Byte(v), None) => ColData::ByteVector(split(&v, ranges)),
+      (ColData::Short(v), None) => ColData::ShortVector(split(&v, ranges)),
+      (ColData::Int(v), None) => ColData::IntVector(split(&v, ranges)),
+      (ColData::Long(v), None) => ColData::LongVector(split(&v, ranges)),
+      (ColData::Float(v), None) => ColData::FloatVector(split(&v, ranges)),
+      (ColData::Double(v), None) => ColData::DoubleVector(split(&v, ranges)),
+      (ColData::ByteString(v), None) => ColData::ByteStringVector(split(&v, ranges)),
+      (ColData::Bool(v), Some(dims)) => ColData::BoolTensor(tensors(&v, ranges, dims)),
+      (ColData::String(v), Some(dims)) => ColData::StringTensor(tensors(&v, ranges, dims)),
+      (ColData::Byte(v), Some(dims)) => ColData::ByteTensor(tensors(&v, ranges, dims)),
+      (ColData::Short(v), Some(dims)) => ColData::ShortTensor(tensors(&v, ranges, dims)),
+      (ColData::Int(v), Some(dims)) => ColData::IntTensor(tensors(&v, ranges, dims)),
+      (ColData::Long(v), Some(dims)) => ColData::LongTensor(tensors(&v, ranges, dims)),
+      (ColData::Float(v), Some(dims)) => ColData::FloatTensor(tensors(&v, ranges, dims)),
+      (ColData::Double(v), Some(dims)) => ColData::DoubleTensor(tensors(&v, ranges, dims)),
+      (ColData::ByteString(v), Some(dims)) => ColData::ByteStringTensor(tensors(&v, ranges, dims)),
+      _ => unreachable!()
+    }
+  })
+}
+
+fn tensor_dimensions(field: &Field, size: usize) -> Result<Vec<usize>> {
+  match field.metadata().get(DIMENSIONS_KEY) {
+    Some(dims) => {
+      let dims: Vec<usize> = match dims.split(',').map(|d| d.trim().parse()).collect() {
+        Ok(dims) => dims,
+        Err(_) => return Err(Error::InvalidType(format!("Invalid tensor dimensions {} for {}", dims, field.name())))
+      };
+
+      if dims.iter().product::<usize>() == size {
+        Ok(dims)
+      } else {
+        Err(Error::InvalidType(format!("Tensor dimensions {:?} do not match size {} for {}", dims, size, field.name())))
+      }
+    },
+    None => Ok(vec![size])
+  }
+}
+
+fn array_col(field: &Field, array: &ArrayRef) -> Result<Col> {
+  let data = match *array.data_type() {
+    ArrowType::List(_) => {
+      let list = array.as_list::<i32>();
+      let ranges: Vec<(usize, usize)> = list.value_offsets().windows(2).map(|w| (w[0] as usize, w[1] as usize)).collect();
+      nested_data(list.values(), &ranges, None)
+    },
+    ArrowType::LargeList(_) => {
+      let list = array.as_list::<i64>();
+      let ranges: Vec<(usize, usize)> = list.value_offsets().windows(2).map(|w| (w[0] as usize, w[1] as usize)).collect();
+      nested_data(list.values(), &ranges, None)
+    },
+    ArrowType::FixedSizeList(_, size) => {
+      let list = array.as_fixed_size_list();
+      let size = size as usize;
+      let ranges: Vec<(usize, usize)> = (0..list.len()).map(|i| (list.value_offset(i) as usize, list.value_offset(i) as usize + size)).collect();
+
+      tensor_dimensions(field, size).and_then(|dims| nested_data(list.values(), &ranges, Some(&dims)))
+    },
+    _ => scalar_data(array)
+  };
+
+  data.map(|data| {
+    let col = Col::new(field.name().to_string(), data);
+
+    if field.is_nullable() || array.null_count() > 0 {
+      let validity = (0..array.len()).map(|i| array.is_valid(i)).collect();
+      col.with_validity(validity)
+    } else {
+      col
+    }
+  })
+}
+
+impl LeapFrame {
+  /// Reads every column of `batch` in one pass over its buffers. Nullable
+  /// Arrow fields become nullable columns.
+  pub fn try_from_record_batch(batch: &RecordBatch) -> Result<LeapFrame> {
+    let mut frame = LeapFrame::with_size(batch.num_rows());
+
+    for (field, array) in batch.schema().fields().iter().zip(batch.columns().iter()) {
+      match array_col(field, array).and_then(|col| frame.try_with_col(col).map(|_| ())) {
+        Ok(_) => (),
+        Err(err) => return Err(err)
+      }
+    }
+
+    Ok(frame)
+  }
+
+  /// Moves the columns into Arrow arrays, scalar columns without copying
+  /// their values.
+  pub fn try_into_record_batch(self) -> Result<RecordBatch> {
+    let size = self.size;
+    let mut fields = Vec::with_capacity(self.cols.len());
+    let mut arrays = Vec::with_capacity(self.cols.len());
+
+    for col in self.cols.into_iter() {
+      match col_array(col) {
+        Ok((field, array)) => {
+          fields.push(field);
+          arrays.push(array);
+        },
+        Err(err) => return Err(err)
+      }
+    }
+
+    let options = RecordBatchOptions::new().with_row_count(Some(size));
+    RecordBatch::try_new_with_options(Arc::new(ArrowSchema::new(fields)), arrays, &options).
+      map_err(|err| Error::TransformError(format!("{}", err)))
+  }
+
+  pub fn try_to_record_batch(&self) -> Result<RecordBatch> {
+    self.clone().try_into_record_batch()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use arrow_array::{Float64Array, Int32Array};
+  use arrow_array::builder::{ListBuilder, Int64Builder};
+
+  #[test]
+  fn test_from_record_batch() {
+    let mut tags = ListBuilder::new(Int64Builder::new());
+    tags.values().append_slice(&[1, 2]);
+    tags.append(true);
+    tags.append(true);
+    let tags = tags.finish();
+
+    let features = FixedSizeListArray::new(Arc::new(Field::new_list_field(ArrowType::Float64, false)), 2,
+                                           Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0, 4.0])), None);
+    let schema = ArrowSchema::new(vec![Field::new("state", ArrowType::Utf8, true),
+                                       Field::new("bathrooms", ArrowType::Float64, false),
+                                       Field::new("tags", tags.data_type().clone(), false),
+                                       Field::new("features", features.data_type().clone(), false)]);
+
+    let batch = RecordBatch::try_new(Arc::new(schema),
+                                     vec![Arc::new(StringArray::from(vec![Some("NY"), None])),
+                                          Arc::new(Float64Array::from(vec![2.0, 1.0])),
+                                          Arc::new(tags),
+                                          Arc::new(features)]).unwrap();
+    let frame = LeapFrame::try_from_record_batch(&batch).unwrap();
+
+    assert_eq!(frame.size(), 2);
+    assert_eq!(frame.get_strings("state").unwrap(), &[String::from("NY"), String::new()]);
+    assert_eq!(frame.try_col("state").unwrap().validity(), Some(&[true, false][..]));
+    assert_eq!(frame.get_doubles("bathrooms").unwrap(), &[2.0, 1.0]);
+    match *frame.try_col("tags").unwrap().data() {
+      ColData::LongVector(ref v) => assert_eq!(v, &vec![vec![1, 2], vec![]]),
+      ref data => panic!("Expected a long vector, got {:?}", data)
+    }
+    assert_eq!(frame.get_double_tensors("features").unwrap(), &[DenseTensor::new(vec![2], vec![1.0, 2.0]),
+                                                                DenseTensor::new(vec![2], vec![3.0, 4.0])]);
+
+    let batch = RecordBatch::try_new(Arc::new(ArrowSchema::new(vec![Field::new("a", ArrowType::Int32, false)])),
+                                     vec![Arc::new(Int32Array::from(vec![1, 2, 3]).slice(1, 2))]).unwrap();
+    assert_eq!(LeapFrame::try_from_record_batch(&batch).unwrap().get_ints("a").unwrap(), &[2, 3]);
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut frame = LeapFrame::with_size(2);
+    frame.try_with_col(Col::from_doubles(String::from("a"), vec![1.5, 0.0]).with_validity(vec![true, false])).unwrap().
+      try_with_strings(String::from("b"), vec![String::from("x"), String::from("yz")]).unwrap().
+      try_with_double_tensors(String::from("c"), vec![DenseTensor::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]),
+                                                      DenseTensor::new(vec![2, 2], vec![5.0, 6.0, 7.0, 8.0])]).unwrap().
+      try_with_sparse_double_tensors(String::from("d"), vec![SparseTensor::new(vec![3], vec![1], vec![1.0]),
+                                                             SparseTensor::new(vec![3], vec![], vec![])]).unwrap();
+
+    let batch = frame.try_to_record_batch().unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    assert!(batch.schema().field(0).is_nullable());
+    assert!(!batch.schema().field(1).is_nullable());
+
+    let read = LeapFrame::try_from_record_batch(&batch).unwrap();
+    assert_eq!(read.schema(), frame.schema());
+    assert_eq!(read.try_col("a").unwrap().validity(), Some(&[true, false][..]));
+    assert_eq!(read.get_strings("b").unwrap(), frame.get_strings("b").unwrap());
+    assert_eq!(read.get_double_tensors("c").unwrap(), frame.get_double_tensors("c").unwrap());
+    assert_eq!(read.get_double_tensors("d").unwrap(), &[DenseTensor::new(vec![3], vec![0.0, 1.0, 0.0]),
+                                                        DenseTensor::new(vec![3], vec![0.0, 0.0, 0.0])]);
+  }
+
+  #[test]
+  fn test_null_tensors() {
+    let mut frame = LeapFrame::with_size(2);
+    frame.try_with_col(Col::new(String::from("a"), ColData::DoubleTensor(vec![DenseTensor::new(vec![2], vec![]),
+                                                                              DenseTensor::new(vec![2], vec![1.0, 2.0])])).
+                       with_validity(vec![false, true])).unwrap().
+      try_with_col(Col::new(String::from("b"), ColData::SparseDoubleTensor(vec![SparseTensor::new(vec![2], vec![1], vec![3.0]),
+                                                                                SparseTensor::new(vec![2], vec![], vec![])])).
+                   with_validity(vec![true, false])).unwrap();
+
+    let read = LeapFrame::try_from_record_batch(&frame.try_to_record_batch().unwrap()).unwrap();
+    assert_eq!(read.try_col("a").unwrap().validity(), Some(&[false, true][..]));
+    assert_eq!(read.get_double_tensors("a").unwrap()[1], DenseTensor::new(vec![2], vec![1.0, 2.0]));
+    assert_eq!(read.get_double_tensors("b").unwrap()[0], DenseTensor::new(vec![2], vec![0.0, 3.0]));
+
+    let mut frame = LeapFrame::with_size(1);
+    frame.try_with_col(Col::new(String::from("a"), ColData::DoubleTensor(vec![DenseTensor::new(vec![1 << 40, 1 << 40], vec![])])).
+                       with_validity(vec![false])).unwrap();
+    assert!(frame.try_to_record_batch().is_err());
+  }
+
+  #[test]
+  fn test_unsupported_type() {
+    let schema = ArrowSchema::new(vec![Field::new("a", ArrowType::UInt32, false)]);
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arrow_array::UInt32Array::from(vec![1]))]).unwrap();
+    assert!(LeapFrame::try_from_record_batch(&batch).is_err());
+  }
+}
@@ -0,0 +1,559 @@
+//! Chunked CSV input and output for batch scoring.
+//!
+//! Cells are read according to a schema: scalars are parsed from their text,
+//! with integers also accepted as whole floats such as `3.0`, and lists and
+//! tensors are JSON arrays, with tensor dimensions taken from the schema.
+//! Fields that take numbers or tensors of numbers are read as double
+//! tensors when their first cell is a JSON array, and as doubles otherwise.
+//! Empty cells are null in nullable fields.
+use std::io;
+use std::str::FromStr;
+use serde::de::DeserializeOwned;
+use serde_json;
+use base64;
+use csv;
+
+use bundle::dsl::DenseTensor;
+use super::{BasicType, Col, DataShape, DataType, Error, LeapFrame, Result, Row, Schema, StructField, Value};
+
+pub struct CsvReader<R: io::Read> {
+  records: csv::Reader<R>,
+  schema: Schema,
+  positions: Vec<usize>,
+  chunk_size: usize,
+  resolved: bool,
+  done: bool
+}
+
+/// How tensor columns are written: as one JSON array per cell, or flattened
+/// into a CSV column per element, named `<column>_<i>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TensorFormat {
+  Json,
+  Flatten
+}
+
+pub struct CsvWriter<W: io::Write> {
+  records: csv::Writer<W>,
+  cols: Vec<String>,
+  tensor_format: TensorFormat,
+  widths: Option<Vec<Option<usize>>>
+}
+
+fn read_error(err: csv::Error) -> Error { Error::ReadError(format!("{}", err)) }
+fn write_error(err: csv::Error) -> Error { Error::WriteError(format!("{}", err)) }
+
+fn invalid_cell(field: &StructField, cell: &str) -> Error {
+  Error::ReadError(format!("{}: cannot read {:?} as {}", field.name(), cell, field.data_type()))
+}
+
+fn parse<T: FromStr>(field: &StructField, cell: &str) -> Result<T> {
+  cell.trim().parse().map_err(|_| invalid_cell(field, cell))
+}
+
+/// Integers exported through floating point columns come out as `3.0`.
+fn parse_integer<T: FromStr>(field: &StructField, cell: &str) -> Result<T> {
+  parse(field, cell).or_else(|err| {
+    match cell.trim().parse::<f64>() {
+      Ok(f) if f.fract() == 0.0 => parse(field, &format!("{}", f)),
+      _ => Err(err)
+    }
+  })
+}
+
+fn parse_bool(field: &StructField, cell: &str) -> Result<bool> {
+  match cell.trim() {
+    "true" | "1" => Ok(true),
+    "false" | "0" => Ok(false),
+    _ => Err(invalid_cell(field, cell))
+  }
+}
+
+fn parse_array<T: DeserializeOwned>(field: &StructField, cell: &str) -> Result<Vec<T>> {
+  serde_json::from_str(cell).map_err(|_| invalid_cell(field, cell))
+}
+
+/// Reading and writing cells and JSON array elements of each basic type.
+trait Cell: DeserializeOwned {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self>;
+  fn parse_elements(field: &StructField, cell: &str) -> Result<Vec<Self>> { parse_array(field, cell) }
+  fn to_element(&self) -> serde_json::Value;
+  fn to_cell(&self) -> String;
+}
+
+impl Cell for bool {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse_bool(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+impl Cell for String {
+  fn parse_cell(_field: &StructField, cell: &str) -> Result<Self> { Ok(cell.to_string()) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(self.as_str()) }
+  fn to_cell(&self) -> String { self.clone() }
+}
+impl Cell for i8 {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse_integer(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+impl Cell for i16 {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse_integer(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+impl Cell for i32 {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse_integer(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+impl Cell for i64 {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse_integer(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+impl Cell for f32 {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+impl Cell for f64 {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> { parse(field, cell) }
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(*self) }
+  fn to_cell(&self) -> String { self.to_string() }
+}
+
+/// Byte strings are base64, in cells and in JSON arrays of strings.
+impl Cell for Vec<u8> {
+  fn parse_cell(field: &StructField, cell: &str) -> Result<Self> {
+    base64::decode(cell.trim()).map_err(|_| invalid_cell(field, cell))
+  }
+
+  fn parse_elements(field: &StructField, cell: &str) -> Result<Vec<Self>> {
+    parse_array::<String>(field, cell).and_then(|vs| {
+      let mut acc = Vec::with_capacity(vs.len());
+
+      for v in vs.iter() {
+        match Self::parse_cell(field, v) {
+          Ok(b) => acc.push(b),
+          Err(err) => return Err(err)
+        }
+      }
+
+      Ok(acc)
+    })
+  }
+
+  fn to_element(&self) -> serde_json::Value { serde_json::Value::from(base64::encode(self)) }
+  fn to_cell(&self) -> String { base64::encode(self) }
+}
+
+/// A tensor from its flattened values, with the field dimensions if known.
+fn tensor<T>(field: &StructField, values: Vec<T>) -> Result<DenseTensor<T>> {
+  match *field.data_type().shape() {
+    DataShape::Tensor(Some(ref dims)) if dims.iter().product::<usize>() != values.len() => {
+      Err(Error::ReadError(format!("{}: {} values do not fit dimensions {:?}", field.name(), values.len(), dims)))
+    },
+    DataShape::Tensor(Some(ref dims)) => Ok(DenseTensor::new(dims.clone(), values)),
+    _ => Ok(DenseTensor::new(vec![values.len()], values))
+  }
+}
+
+/// The type of a field that takes numbers or tensors of numbers, as read
+/// from its first cell.
+fn resolve(field: &StructField, cell: &str) -> StructField {
+  match *field.data_type().shape() {
+    DataShape::Tensor(None) if field.data_type().is_numeric() => {
+      let data_type = if cell.trim_start().starts_with('[') {
+        DataType::tensor(BasicType::Double, None)
+      } else {
+        DataType::scalar(BasicType::Double)
+      };
+
+      StructField::new(field.name().to_string(), data_type, field.nullable())
+    },
+    _ => field.clone()
+  }
+}
+
+fn parse_cell(field: &StructField, cell: &str) -> Result<Value> {
+  let base = field.data_type().base();
+
+  if cell.is_empty() && (field.nullable() || base != BasicType::String || *field.data_type().shape() != DataShape::Scalar) {
+    return Ok(Value::Null);
+  }
+
+  match *field.data_type().shape() {
+    DataShape::Scalar => {
+      match base {
+        BasicType::Bool => Cell::parse_cell(field, cell).map(Value::Bool),
+        BasicType::String => Cell::parse_cell(field, cell).map(Value::String),
+        BasicType::Byte => Cell::parse_cell(field, cell).map(Value::Byte),
+        BasicType::Short => Cell::parse_cell(field, cell).map(Value::Short),
+        BasicType::Int => Cell::parse_cell(field, cell).map(Value::Int),
+        BasicType::Long => Cell::parse_cell(field, cell).map(Value::Long),
+        BasicType::Float => Cell::parse_cell(field, cell).map(Value::Float),
+        BasicType::Double => Cell::parse_cell(field, cell).map(Value::Double),
+        BasicType::ByteString => Cell::parse_cell(field, cell).map(Value::ByteString)
+      }
+    },
+    DataShape::List => {
+      match base {
+        BasicType::Bool => Cell::parse_elements(field, cell).map(Value::BoolVector),
+        BasicType::String => Cell::parse_elements(field, cell).map(Value::StringVector),
+        BasicType::Byte => Cell::parse_elements(field, cell).map(Value::ByteVector),
+        BasicType::Short => Cell::parse_elements(field, cell).map(Value::ShortVector),
+        BasicType::Int => Cell::parse_elements(field, cell).map(Value::IntVector),
+        BasicType::Long => Cell::parse_elements(field, cell).map(Value::LongVector),
+        BasicType::Float => Cell::parse_elements(field, cell).map(Value::FloatVector),
+        BasicType::Double => Cell::parse_elements(field, cell).map(Value::DoubleVector),
+        BasicType::ByteString => Cell::parse_elements(field, cell).map(Value::ByteStringVector)
+      }
+    },
+    DataShape::Tensor(_) => {
+      match base {
+        BasicType::Bool => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::BoolTensor),
+        BasicType::String => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::StringTensor),
+        BasicType::Byte => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::ByteTensor),
+        BasicType::Short => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::ShortTensor),
+        BasicType::Int => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::IntTensor),
+        BasicType::Long => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::LongTensor),
+        BasicType::Float => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::FloatTensor),
+        BasicType::Double => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::DoubleTensor),
+        BasicType::ByteString => Cell::parse_elements(field, cell).and_then(|v| tensor(field, v)).map(Value::ByteStringTensor)
+      }
+    }
+  }
+}
+
+impl<R: io::Read> CsvReader<R> {
+  /// Reads the header row and finds the CSV column of every field in
+  /// `schema`, other columns are ignored.
+  pub fn new(reader: R, schema: Schema, chunk_size: usize) -> Result<CsvReader<R>> {
+    let mut records = csv::Reader::from_reader(reader);
+    let headers = match records.headers() {
+      Ok(headers) => headers.clone(),
+      Err(err) => return Err(read_error(err))
+    };
+
+    let mut positions = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields().iter() {
+      match headers.iter().position(|h| h == field.name()) {
+        Some(i) => positions.push(i),
+        None => return Err(Error::NoSuchColumn(field.name().to_string()))
+      }
+    }
+
+    Ok(CsvReader {
+      records: records,
+      schema: schema,
+      positions: positions,
+      chunk_size: if chunk_size == 0 { 1 } else { chunk_size },
+      resolved: false,
+      done: false
+    })
+  }
+
+  pub fn schema(&self) -> &Schema { &self.schema }
+
+  /// The next frame of at most `chunk_size` rows, `None` once the input is
+  /// exhausted.
+  pub fn read_chunk(&mut self) -> Result<Option<LeapFrame>> {
+    let mut record = csv::StringRecord::new();
+    let mut rows = Vec::with_capacity(self.chunk_size);
+
+    while rows.len() < self.chunk_size {
+      match self.records.read_record(&mut record) {
+        Ok(true) => (),
+        Ok(false) => break,
+        Err(err) => return Err(read_error(err))
+      }
+
+      if !self.resolved {
+        self.schema = Schema::new(self.schema.fields().iter().zip(self.positions.iter()).map(|(field, &i)| {
+          resolve(field, record.get(i).unwrap_or(""))
+        }).collect());
+        self.resolved = true;
+      }
+
+      let mut values = Vec::with_capacity(self.positions.len());
+      for (field, &i) in self.schema.fields().iter().zip(self.positions.iter()) {
+        let cell = record.get(i).unwrap_or("");
+
+        match parse_cell(field, cell) {
+          Ok(v) => values.push(v),
+          Err(Error::ReadError(msg)) => {
+            let line = record.position().map(|p| p.line()).unwrap_or(0);
+            return Err(Error::ReadError(format!("line {}: {}", line, msg)));
+          },
+          Err(err) => return Err(err)
+        }
+      }
+
+      rows.push(Row::new(values));
+    }
+
+    if rows.is_empty() {
+      Ok(None)
+    } else {
+      LeapFrame::from_rows(&self.schema, rows).map(Some)
+    }
+  }
+}
+
+impl<R: io::Read> Iterator for CsvReader<R> {
+  type Item = Result<LeapFrame>;
+
+  fn next(&mut self) -> Option<Result<LeapFrame>> {
+    if self.done {
+      return None;
+    }
+
+    match self.read_chunk() {
+      Ok(Some(frame)) => Some(Ok(frame)),
+      Ok(None) => {
+        self.done = true;
+        None
+      },
+      Err(err) => {
+        self.done = true;
+        Some(Err(err))
+      }
+    }
+  }
+}
+
+fn json_values<T: Cell>(v: &[T]) -> Vec<serde_json::Value> {
+  v.iter().map(T::to_element).collect()
+}
+
+/// The elements of a list or tensor value, sparse tensors densified.
+fn elements(value: &Value) -> Option<Vec<serde_json::Value>> {
+  let elements = match *value {
+    Value::BoolVector(ref v) => json_values(v),
+    Value::StringVector(ref v) => json_values(v),
+    Value::ByteVector(ref v) => json_values(v),
+    Value::ShortVector(ref v) => json_values(v),
+    Value::IntVector(ref v) => json_values(v),
+    Value::LongVector(ref v) => json_values(v),
+    Value::FloatVector(ref v) => json_values(v),
+    Value::DoubleVector(ref v) => json_values(v),
+    Value::ByteStringVector(ref v) => json_values(v),
+    Value::BoolTensor(ref t) => json_values(t.values()),
+    Value::StringTensor(ref t) => json_values(t.values()),
+    Value::ByteTensor(ref t) => json_values(t.values()),
+    Value::ShortTensor(ref t) => json_values(t.values()),
+    Value::IntTensor(ref t) => json_values(t.values()),
+    Value::LongTensor(ref t) => json_values(t.values()),
+    Value::FloatTensor(ref t) => json_values(t.values()),
+    Value::DoubleTensor(ref t) => json_values(t.values()),
+    Value::ByteStringTensor(ref t) => json_values(t.values()),
+    Value::SparseBoolTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseStringTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseByteTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseShortTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseIntTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseLongTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseFloatTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseDoubleTensor(ref t) => json_values(t.to_dense().values()),
+    Value::SparseByteStringTensor(ref t) => json_values(t.to_dense().values()),
+    _ => return None
+  };
+
+  Some(elements)
+}
+
+fn scalar_cell(value: &Value) -> String {
+  match *value {
+    Value::Bool(ref v) => v.to_cell(),
+    Value::String(ref v) => v.to_cell(),
+    Value::Byte(ref v) => v.to_cell(),
+    Value::Short(ref v) => v.to_cell(),
+    Value::Int(ref v) => v.to_cell(),
+    Value::Long(ref v) => v.to_cell(),
+    Value::Float(ref v) => v.to_cell(),
+    Value::Double(ref v) => v.to_cell(),
+    Value::ByteString(ref v) => v.to_cell(),
+    _ => String::new()
+  }
+}
+
+fn element_cell(value: serde_json::Value) -> String {
+  match value {
+    serde_json::Value::Null => String::new(),
+    serde_json::Value::String(s) => s,
+    v => v.to_string()
+  }
+}
+
+impl<W: io::Write> CsvWriter<W> {
+  /// Writes the columns `cols` of every frame, the header comes from the
+  /// first one.
+  pub fn new(writer: W, cols: Vec<String>, tensor_format: TensorFormat) -> CsvWriter<W> {
+    CsvWriter {
+      records: csv::Writer::from_writer(writer),
+      cols: cols,
+      tensor_format: tensor_format,
+      widths: None
+    }
+  }
+
+  /// Flattened tensor columns take the size of their first row.
+  fn write_header(&mut self, cols: &[&Col]) -> Result<()> {
+    let widths: Vec<Option<usize>> = cols.iter().map(|col| {
+      match (self.tensor_format, col.data().data_type().shape()) {
+        (TensorFormat::Flatten, &DataShape::Tensor(ref dims)) => Some(dims.as_ref().map(|d| d.iter().product()).unwrap_or(0)),
+        _ => None
+      }
+    }).collect();
+
+    let mut header = Vec::new();
+    for (col, width) in cols.iter().zip(widths.iter()) {
+      match *width {
+        Some(width) => header.extend((0..width).map(|i| format!("{}_{}", col.name(), i))),
+        None => header.push(col.name().to_string())
+      }
+    }
+
+    self.widths = Some(widths);
+    self.records.write_record(&header).map_err(write_error)
+  }
+
+  pub fn write_frame(&mut self, frame: &LeapFrame) -> Result<()> {
+    let cols = match frame.try_cols(&self.cols) {
+      Ok(cols) => cols,
+      Err(err) => return Err(err)
+    };
+
+    if self.widths.is_none() {
+      if let Err(err) = self.write_header(&cols) {
+        return Err(err);
+      }
+    }
+
+    let widths = self.widths.clone().unwrap_or_default();
+    let mut record = Vec::new();
+
+    for row in 0..frame.size() {
+      record.clear();
+
+      for (col, width) in cols.iter().zip(widths.iter()) {
+        let value = col.value(row);
+
+        match (*width, elements(&value)) {
+          (Some(width), Some(elements)) => {
+            if elements.len() != width {
+              return Err(Error::WriteError(format!("{}: expected {} values, got {}", col.name(), width, elements.len())));
+            }
+
+            record.extend(elements.into_iter().map(element_cell));
+          },
+          (Some(width), None) => record.extend((0..width).map(|_| String::new())),
+          (None, Some(elements)) => record.push(serde_json::Value::Array(elements).to_string()),
+          (None, None) => record.push(scalar_cell(&value))
+        }
+      }
+
+      if let Err(err) = self.records.write_record(&record) {
+        return Err(write_error(err));
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn flush(&mut self) -> Result<()> {
+    self.records.flush().map_err(|err| Error::WriteError(format!("{}", err)))
+  }
+
+  pub fn into_inner(self) -> Result<W> {
+    self.records.into_inner().map_err(|err| Error::WriteError(format!("{}", err)))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use bundle::dsl::SparseTensor;
+
+  fn schema() -> Schema {
+    Schema::new(vec![StructField::required("state", DataType::scalar(BasicType::String)),
+                     StructField::new(String::from("bathrooms"), DataType::scalar(BasicType::Double), true),
+                     StructField::required("bedrooms", DataType::scalar(BasicType::Int)),
+                     StructField::required("features", DataType::tensor(BasicType::Double, Some(vec![2])))])
+  }
+
+  #[test]
+  fn test_read_chunks() {
+    let csv = "id,state,bathrooms,bedrooms,features\n\
+               1,NY,2.0,3,\"[1.0, 2.0]\"\n\
+               2,CA,,1.0,\"[3.0, 4.0]\"\n\
+               3,,1.5,2,\"[5.0, 6.0]\"\n";
+    let chunks: Vec<LeapFrame> = CsvReader::new(csv.as_bytes(), schema(), 2).unwrap().map(|f| f.unwrap()).collect();
+
+    assert_eq!(chunks.iter().map(|f| f.size()).collect::<Vec<usize>>(), vec![2, 1]);
+    assert_eq!(chunks[0].get_doubles("bathrooms").unwrap(), &[2.0, 0.0]);
+    assert_eq!(chunks[0].try_col("bathrooms").unwrap().validity(), Some(&[true, false][..]));
+    assert_eq!(chunks[0].get_ints("bedrooms").unwrap(), &[3, 1]);
+    assert_eq!(chunks[1].get_strings("state").unwrap(), &[String::new()]);
+    assert_eq!(chunks[1].get_double_tensors("features").unwrap(), &[DenseTensor::new(vec![2], vec![5.0, 6.0])]);
+  }
+
+  #[test]
+  fn test_read_numeric() {
+    let schema = Schema::new(vec![StructField::required("a", DataType::numeric(DataShape::Tensor(None))),
+                                  StructField::required("b", DataType::numeric(DataShape::Tensor(None))),
+                                  StructField::required("c", DataType::numeric(DataShape::Scalar))]);
+    let csv = "a,b,c\n1,\"[1.0, 4.0]\",2\n2,\"[2.0, 3.0]\",3\n";
+    let frame = CsvReader::new(csv.as_bytes(), schema.clone(), 10).unwrap().next().unwrap().unwrap();
+
+    assert_eq!(frame.get_doubles("a").unwrap(), &[1.0, 2.0]);
+    assert_eq!(frame.get_double_tensors("b").unwrap(), &[DenseTensor::new(vec![2], vec![1.0, 4.0]), DenseTensor::new(vec![2], vec![2.0, 3.0])]);
+    assert_eq!(frame.get_doubles("c").unwrap(), &[2.0, 3.0]);
+
+    // the tensors of a column have the dimensions of the first one
+    let csv = "a,b,c\n1,[1.0],2\n2,\"[2.0, 3.0]\",3\n";
+    assert!(CsvReader::new(csv.as_bytes(), schema, 10).unwrap().next().unwrap().is_err());
+  }
+
+  #[test]
+  fn test_read_errors() {
+    assert!(CsvReader::new("state,bathrooms\nNY,1.0\n".as_bytes(), schema(), 10).is_err());
+
+    let csv = "state,bathrooms,bedrooms,features\nNY,2.0,three,\"[1.0, 2.0]\"\n";
+    let mut reader = CsvReader::new(csv.as_bytes(), schema(), 10).unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+
+    let csv = "state,bathrooms,bedrooms,features\nNY,2.0,3,\"[1.0]\"\n";
+    assert!(CsvReader::new(csv.as_bytes(), schema(), 10).unwrap().next().unwrap().is_err());
+  }
+
+  #[test]
+  fn test_write() {
+    let mut frame = LeapFrame::with_size(2);
+    frame.try_with_col(Col::from_doubles(String::from("price"), vec![172.5, 0.0]).with_validity(vec![true, false])).unwrap().
+      try_with_strings(String::from("state"), vec![String::from("NY"), String::from("C,A")]).unwrap().
+      try_with_sparse_double_tensors(String::from("features"), vec![SparseTensor::new(vec![3], vec![1], vec![1.0]),
+                                                                    SparseTensor::new(vec![3], vec![], vec![])]).unwrap();
+    let cols = vec![String::from("state"), String::from("features"), String::from("price")];
+
+    let mut writer = CsvWriter::new(Vec::new(), cols.clone(), TensorFormat::Json);
+    writer.write_frame(&frame).unwrap();
+    writer.write_frame(&frame.slice(0..1).unwrap()).unwrap();
+    assert_eq!(String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+               "state,features,price\n\
+                NY,\"[0.0,1.0,0.0]\",172.5\n\
+                \"C,A\",\"[0.0,0.0,0.0]\",\n\
+                NY,\"[0.0,1.0,0.0]\",172.5\n");
+
+    let mut writer = CsvWriter::new(Vec::new(), cols, TensorFormat::Flatten);
+    writer.write_frame(&frame).unwrap();
+    assert_eq!(String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+               "state,features_0,features_1,features_2,price\n\
+                NY,0.0,1.0,0.0,172.5\n\
+                \"C,A\",0.0,0.0,0.0,\n");
+
+    let mut writer = CsvWriter::new(Vec::new(), vec![String::from("missing")], TensorFormat::Json);
+    assert!(writer.write_frame(&frame).is_err());
+  }
+}
@@ -0,0 +1,556 @@
+//! LeapFrames in the JSON format of the MLeap runtime and mleap-serving:
+//! `{"schema": {"fields": [...]}, "rows": [[...], ...]}`.
+use serde_json::Value;
+use serde_json::map::Map;
+use std::result::Result;
+use base64;
+
+use bundle::dsl::{DenseTensor, SparseTensor};
+use bundle::json::{self, Error, TryFrom};
+use super::{BasicType, Col, ColData, DataShape, DataType, LeapFrame, Schema, StructField};
+
+/// Nullable scalars are written as just their base type, like MLeap does.
+fn type_value(data_type: &DataType, nullable: bool) -> Value {
+  let tpe = match *data_type.shape() {
+    DataShape::Scalar if nullable => return Value::from(data_type.base().as_str()),
+    DataShape::Scalar => "basic",
+    DataShape::List => "list",
+    DataShape::Tensor(_) => "tensor"
+  };
+
+  let mut map = Map::with_capacity(4);
+  map.insert(String::from("type"), Value::from(tpe));
+  map.insert(String::from("base"), Value::from(data_type.base().as_str()));
+  map.insert(String::from("isNullable"), Value::from(nullable));
+
+  if let DataShape::Tensor(Some(ref dims)) = *data_type.shape() {
+    map.insert(String::from("dimensions"), Value::from(dims.as_slice()));
+  }
+
+  Value::Object(map)
+}
+
+impl<'a> From<&'a StructField> for Value {
+  fn from(value: &'a StructField) -> Self {
+    let mut map = Map::with_capacity(2);
+    map.insert(String::from("name"), Value::from(value.name()));
+    map.insert(String::from("type"), type_value(value.data_type(), value.nullable()));
+
+    Value::Object(map)
+  }
+}
+
+impl<'a> TryFrom<&'a Value> for StructField {
+  type Err = Error;
+
+  fn try_from(value: &'a Value) -> Result<Self, Self::Err> {
+    let name = match value.get("name").and_then(|x| x.as_str()) {
+      Some(name) => name.to_string(),
+      None => return Err(Error::ReadError(String::from("Missing field name")))
+    };
+
+    let field = match value.get("type") {
+      Some(&Value::String(ref base)) => {
+        BasicType::from_str(base).map(|base| StructField::new(name.clone(), DataType::scalar(base), true))
+      },
+      Some(&Value::Object(ref map)) => {
+        let base = map.get("base").and_then(|x| x.as_str()).and_then(BasicType::from_str);
+        let nullable = map.get("isNullable").and_then(|x| x.as_bool()).unwrap_or(true);
+        let shape = match map.get("type").and_then(|x| x.as_str()) {
+          Some("basic") => Some(DataShape::Scalar),
+          Some("list") => Some(DataShape::List),
+          Some("tensor") => {
+            match map.get("dimensions").map(|x| Vec::<usize>::try_from(x)) {
+              Some(Ok(dims)) => Some(DataShape::Tensor(Some(dims))),
+              Some(Err(err)) => return Err(err),
+              None => Some(DataShape::Tensor(None))
+            }
+          },
+          _ => None
+        };
+
+        base.and_then(|base| shape.map(|shape| StructField::new(name.clone(), DataType::new(base, shape), nullable)))
+      },
+      _ => None
+    };
+
+    field.map(Ok).unwrap_or_else(|| Err(Error::ReadError(format!("Invalid type for field {}", name))))
+  }
+}
+
+impl<'a> From<&'a Schema> for Value {
+  fn from(value: &'a Schema) -> Self {
+    let fields: Vec<Value> = value.fields().iter().map(Value::from).collect();
+
+    let mut map = Map::with_capacity(1);
+    map.insert(String::from("fields"), Value::Array(fields));
+
+    Value::Object(map)
+  }
+}
+
+impl<'a> TryFrom<&'a Value> for Schema {
+  type Err = Error;
+
+  fn try_from(value: &'a Value) -> Result<Self, Self::Err> {
+    value.get("fields").
+      map(|fields| Vec::<StructField>::try_from(fields).map(Schema::new)).
+      unwrap_or_else(|| Err(Error::ReadError(String::from("Missing schema fields"))))
+  }
+}
+
+/// A cell of one of the basic types.
+trait Cell: Sized {
+  fn to_json(&self) -> Value;
+  fn from_json(value: &Value) -> Result<Self, Error>;
+}
+
+impl Cell for bool {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { bool::try_from(value) }
+}
+impl Cell for String {
+  fn to_json(&self) -> Value { Value::from(self.as_str()) }
+  fn from_json(value: &Value) -> Result<Self, Error> { String::try_from(value) }
+}
+impl Cell for i8 {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { i8::try_from(value) }
+}
+impl Cell for i16 {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { i16::try_from(value) }
+}
+impl Cell for i32 {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { i32::try_from(value) }
+}
+impl Cell for i64 {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { i64::try_from(value) }
+}
+impl Cell for f32 {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { f32::try_from(value) }
+}
+impl Cell for f64 {
+  fn to_json(&self) -> Value { Value::from(*self) }
+  fn from_json(value: &Value) -> Result<Self, Error> { f64::try_from(value) }
+}
+/// Byte strings are base64 encoded.
+impl Cell for Vec<u8> {
+  fn to_json(&self) -> Value { Value::from(base64::encode(self)) }
+  fn from_json(value: &Value) -> Result<Self, Error> {
+    value.as_str().
+      map(|v64| base64::decode(v64).map_err(|_| Error::ReadError(String::from("Invalid base64 string")))).
+      unwrap_or_else(|| Err(Error::ReadError(String::from("Expected a base64 string"))))
+  }
+}
+
+fn scalars<T: Cell>(v: &[T]) -> Vec<Value> {
+  v.iter().map(T::to_json).collect()
+}
+
+fn lists<T: Cell>(v: &[Vec<T>]) -> Vec<Value> {
+  v.iter().map(|x| Value::Array(x.iter().map(T::to_json).collect())).collect()
+}
+
+fn dense_tensors<T: Cell>(v: &[DenseTensor<T>]) -> Vec<Value> {
+  v.iter().map(|t| {
+    let mut map = Map::with_capacity(2);
+    map.insert(String::from("dimensions"), Value::from(t.dimensions()));
+    map.insert(String::from("values"), Value::Array(t.values().iter().map(T::to_json).collect()));
+
+    Value::Object(map)
+  }).collect()
+}
+
+fn sparse_tensors<T: Cell>(v: &[SparseTensor<T>]) -> Vec<Value> {
+  v.iter().map(|t| {
+    let indices: Vec<Value> = json::coordinates(t.dimensions(), t.indices()).iter().
+      map(|index| Value::from(index.as_slice())).
+      collect();
+
+    let mut map = Map::with_capacity(3);
+    map.insert(String::from("dimensions"), Value::from(t.dimensions()));
+    map.insert(String::from("indices"), Value::Array(indices));
+    map.insert(String::from("values"), Value::Array(t.values().iter().map(T::to_json).collect()));
+
+    Value::Object(map)
+  }).collect()
+}
+
+/// One value per row, null rows are written as `null`.
+fn col_values(col: &Col) -> Vec<Value> {
+  let mut values = match *col.data() {
+    ColData::Bool(ref v) => scalars(v),
+    ColData::String(ref v) => scalars(v),
+    ColData::Byte(ref v) => scalars(v),
+    ColData::Short(ref v) => scalars(v),
+    ColData::Int(ref v) => scalars(v),
+    ColData::Long(ref v) => scalars(v),
+    ColData::Float(ref v) => scalars(v),
+    ColData::Double(ref v) => scalars(v),
+    ColData::ByteString(ref v) => scalars(v),
+    ColData::BoolVector(ref v) => lists(v),
+    ColData::StringVector(ref v) => lists(v),
+    ColData::ByteVector(ref v) => lists(v),
+    ColData::ShortVector(ref v) => lists(v),
+    ColData::IntVector(ref v) => lists(v),
+    ColData::LongVector(ref v) => lists(v),
+    ColData::FloatVector(ref v) => lists(v),
+    ColData::DoubleVector(ref v) => lists(v),
+    ColData::ByteStringVector(ref v) => lists(v),
+    ColData::BoolTensor(ref v) => dense_tensors(v),
+    ColData::StringTensor(ref v) => dense_tensors(v),
+    ColData::ByteTensor(ref v) => dense_tensors(v),
+    ColData::ShortTensor(ref v) => dense_tensors(v),
+    ColData::IntTensor(ref v) => dense_tensors(v),
+    ColData::LongTensor(ref v) => dense_tensors(v),
+    ColData::FloatTensor(ref v) => dense_tensors(v),
+    ColData::DoubleTensor(ref v) => dense_tensors(v),
+    ColData::ByteStringTensor(ref v) => dense_tensors(v),
+    ColData::SparseBoolTensor(ref v) => sparse_tensors(v),
+    ColData::SparseStringTensor(ref v) => sparse_tensors(v),
+    ColData::SparseByteTensor(ref v) => sparse_tensors(v),
+    ColData::SparseShortTensor(ref v) => sparse_tensors(v),
+    ColData::SparseIntTensor(ref v) => sparse_tensors(v),
+    ColData::SparseLongTensor(ref v) => sparse_tensors(v),
+    ColData::SparseFloatTensor(ref v) => sparse_tensors(v),
+    ColData::SparseDoubleTensor(ref v) => sparse_tensors(v),
+    ColData::SparseByteStringTensor(ref v) => sparse_tensors(v)
+  };
+
+  if let Some(validity) = col.validity() {
+    for (v, &valid) in values.iter_mut().zip(validity.iter()) {
+      if !valid {
+        *v = Value::Null;
+      }
+    }
+  }
+
+  values
+}
+
+impl<'a> From<&'a LeapFrame> for Value {
+  fn from(value: &'a LeapFrame) -> Self {
+    let mut rows: Vec<Vec<Value>> = (0..value.size()).map(|_| Vec::with_capacity(value.cols().len())).collect();
+
+    for col in value.cols().iter() {
+      for (row, v) in rows.iter_mut().zip(col_values(col).into_iter()) {
+        row.push(v);
+      }
+    }
+
+    let mut map = Map::with_capacity(2);
+    map.insert(String::from("schema"), Value::from(&value.schema()));
+    map.insert(String::from("rows"), Value::Array(rows.into_iter().map(Value::Array).collect()));
+
+    Value::Object(map)
+  }
+}
+
+fn array<'a, T, F: Fn(&'a Value) -> Result<T, Error>>(value: &'a Value, f: F) -> Result<Vec<T>, Error> {
+  value.as_array().map(|arr| {
+    let mut acc = Vec::with_capacity(arr.len());
+
+    for v in arr.iter() {
+      match f(v) {
+        Ok(x) => acc.push(x),
+        Err(err) => return Err(err)
+      }
+    }
+
+    Ok(acc)
+  }).unwrap_or_else(|| Err(Error::ReadError(format!("Expected an array: {}", value))))
+}
+
+/// Reads the dimensions, flat sparse indices if any and values of a tensor.
+fn tensor<'a, T, F: Fn(&'a Value) -> Result<T, Error>>(value: &'a Value, f: F) -> Result<(Vec<usize>, Option<Vec<usize>>, Vec<T>), Error> {
+  match (value.get("dimensions"), value.get("values")) {
+    (Some(jdims), Some(jvalues)) => {
+      Vec::<usize>::try_from(jdims).and_then(|dims| {
+        match value.get("indices") {
+          Some(jindices) => Vec::<Vec<usize>>::try_from(jindices).and_then(|indices| json::flat_indices(&dims, &indices)).map(Some),
+          None => Ok(None)
+        }.map(|indices| (dims, indices))
+      }).and_then(|(dims, indices)| {
+        let len = match dims.iter().fold(Some(1usize), |acc, d| acc.and_then(|n| n.checked_mul(*d))) {
+          Some(len) => len,
+          None => return Err(Error::ReadError(format!("Tensor dimensions {:?} are too large", dims)))
+        };
+
+        array(jvalues, f).and_then(|values| {
+          let expected = indices.as_ref().map(|i| i.len()).unwrap_or(len);
+
+          if values.len() == expected {
+            Ok((dims, indices, values))
+          } else {
+            Err(Error::ReadError(format!("Tensor with dimensions {:?} has {} values", dims, values.len())))
+          }
+        })
+      })
+    },
+    _ => Err(Error::ReadError(String::from("Missing dimensions or values from tensor")))
+  }
+}
+
+/// Reads one value per row, `default` fills in null rows.
+fn read<'a, T, D, F>(values: &[&'a Value], default: D, f: F) -> Result<Vec<T>, Error>
+  where D: Fn() -> T, F: Fn(&'a Value) -> Result<T, Error> {
+  let mut acc = Vec::with_capacity(values.len());
+
+  for v in values.iter() {
+    if v.is_null() {
+      acc.push(default());
+    } else {
+      match f(v) {
+        Ok(x) => acc.push(x),
+        Err(err) => return Err(err)
+      }
+    }
+  }
+
+  Ok(acc)
+}
+
+fn read_scalars<T: Cell + Default>(values: &[&Value]) -> Result<Vec<T>, Error> {
+  read(values, T::default, T::from_json)
+}
+
+fn read_lists<T: Cell>(values: &[&Value]) -> Result<Vec<Vec<T>>, Error> {
+  read(values, Vec::new, |v| array(v, T::from_json))
+}
+
+/// Null rows hold a tensor with the field dimensions and no values, which
+/// is never densified.
+fn read_dense_tensors<T: Cell + Clone + Default>(values: &[&Value], dims: &Option<Vec<usize>>) -> Result<Vec<DenseTensor<T>>, Error> {
+  read(values, || DenseTensor::new(dims.clone().unwrap_or_else(|| vec![0]), vec![]), |v| {
+    tensor(v, T::from_json).and_then(|(dims, indices, values)| {
+      match indices {
+        Some(indices) => sparse_tensor(dims, indices, values).map(|t| t.to_dense()),
+        None => Ok(DenseTensor::new(dims, values))
+      }
+    })
+  })
+}
+
+fn read_sparse_tensors<T: Cell>(values: &[&Value], dims: &Option<Vec<usize>>) -> Result<Vec<SparseTensor<T>>, Error> {
+  read(values, || SparseTensor::new(dims.clone().unwrap_or_else(|| vec![0]), vec![], vec![]), |v| {
+    tensor(v, T::from_json).and_then(|(dims, indices, values)| {
+      let indices = indices.unwrap_or_else(|| (0..values.len()).collect());
+      sparse_tensor(dims, indices, values)
+    })
+  })
+}
+
+fn sparse_tensor<T>(dims: Vec<usize>, indices: Vec<usize>, values: Vec<T>) -> Result<SparseTensor<T>, Error> {
+  let tensor = SparseTensor::new(dims, indices, values);
+
+  if tensor.is_valid() {
+    Ok(tensor)
+  } else {
+    Err(Error::ReadError(format!("Sparse tensor with dimensions {:?} has invalid indices {:?}", tensor.dimensions(), tensor.indices())))
+  }
+}
+
+/// Tensor columns are sparse as soon as one row has `indices`.
+fn read_col(field: &StructField, values: &[&Value]) -> Result<Col, Error> {
+  let validity: Vec<bool> = values.iter().map(|v| !v.is_null()).collect();
+
+  if !field.nullable() && validity.iter().any(|v| !v) {
+    return Err(Error::ReadError(format!("Null value in non-nullable field {}", field.name())));
+  }
+
+  let base = field.data_type().base();
+  let data = match *field.data_type().shape() {
+    DataShape::Scalar => {
+      match base {
+        BasicType::Bool => read_scalars(values).map(ColData::Bool),
+        BasicType::String => read_scalars(values).map(ColData::String),
+        BasicType::Byte => read_scalars(values).map(ColData::Byte),
+        BasicType::Short => read_scalars(values).map(ColData::Short),
+        BasicType::Int => read_scalars(values).map(ColData::Int),
+        BasicType::Long => read_scalars(values).map(ColData::Long),
+        BasicType::Float => read_scalars(values).map(ColData::Float),
+        BasicType::Double => read_scalars(values).map(ColData::Double),
+        BasicType::ByteString => read_scalars(values).map(ColData::ByteString)
+      }
+    },
+    DataShape::List => {
+      match base {
+        BasicType::Bool => read_lists(values).map(ColData::BoolVector),
+        BasicType::String => read_lists(values).map(ColData::StringVector),
+        BasicType::Byte => read_lists(values).map(ColData::ByteVector),
+        BasicType::Short => read_lists(values).map(ColData::ShortVector),
+        BasicType::Int => read_lists(values).map(ColData::IntVector),
+        BasicType::Long => read_lists(values).map(ColData::LongVector),
+        BasicType::Float => read_lists(values).map(ColData::FloatVector),
+        BasicType::Double => read_lists(values).map(ColData::DoubleVector),
+        BasicType::ByteString => read_lists(values).map(ColData::ByteStringVector)
+      }
+    },
+    DataShape::Tensor(ref dims) if values.iter().any(|v| v.get("indices").is_some()) => {
+      match base {
+        BasicType::Bool => read_sparse_tensors(values, dims).map(ColData::SparseBoolTensor),
+        BasicType::String => read_sparse_tensors(values, dims).map(ColData::SparseStringTensor),
+        BasicType::Byte => read_sparse_tensors(values, dims).map(ColData::SparseByteTensor),
+        BasicType::Short => read_sparse_tensors(values, dims).map(ColData::SparseShortTensor),
+        BasicType::Int => read_sparse_tensors(values, dims).map(ColData::SparseIntTensor),
+        BasicType::Long => read_sparse_tensors(values, dims).map(ColData::SparseLongTensor),
+        BasicType::Float => read_sparse_tensors(values, dims).map(ColData::SparseFloatTensor),
+        BasicType::Double => read_sparse_tensors(values, dims).map(ColData::SparseDoubleTensor),
+        BasicType::ByteString => read_sparse_tensors(values, dims).map(ColData::SparseByteStringTensor)
+      }
+    },
+    DataShape::Tensor(ref dims) => {
+      match base {
+        BasicType::Bool => read_dense_tensors(values, dims).map(ColData::BoolTensor),
+        BasicType::String => read_dense_tensors(values, dims).map(ColData::StringTensor),
+        BasicType::Byte => read_dense_tensors(values, dims).map(ColData::ByteTensor),
+        BasicType::Short => read_dense_tensors(values, dims).map(ColData::ShortTensor),
+        BasicType::Int => read_dense_tensors(values, dims).map(ColData::IntTensor),
+        BasicType::Long => read_dense_tensors(values, dims).map(ColData::LongTensor),
+        BasicType::Float => read_dense_tensors(values, dims).map(ColData::FloatTensor),
+        BasicType::Double => read_dense_tensors(values, dims).map(ColData::DoubleTensor),
+        BasicType::ByteString => read_dense_tensors(values, dims).map(ColData::ByteStringTensor)
+      }
+    }
+  };
+
+  data.map(|data| {
+    let col = Col::new(field.name().to_string(), data);
+
+    if field.nullable() {
+      col.with_validity(validity)
+    } else {
+      col
+    }
+  })
+}
+
+impl<'a> TryFrom<&'a Value> for LeapFrame {
+  type Err = Error;
+
+  fn try_from(value: &'a Value) -> Result<Self, Self::Err> {
+    let schema = match value.get("schema").map(Schema::try_from) {
+      Some(Ok(schema)) => schema,
+      Some(Err(err)) => return Err(err),
+      None => return Err(Error::ReadError(String::from("Missing frame schema")))
+    };
+    let rows = match value.get("rows").map(|rows| array(rows, |row| array(row, Ok))) {
+      Some(Ok(rows)) => rows,
+      Some(Err(err)) => return Err(err),
+      None => return Err(Error::ReadError(String::from("Missing frame rows")))
+    };
+
+    if rows.iter().any(|row| row.len() != schema.fields().len()) {
+      return Err(Error::ReadError(format!("Expected {} values per row", schema.fields().len())));
+    }
+
+    let mut frame = LeapFrame::with_size(rows.len());
+
+    for (i, field) in schema.fields().iter().enumerate() {
+      let values: Vec<&Value> = rows.iter().map(|row| row[i]).collect();
+
+      match read_col(field, &values).and_then(|col| frame.try_with_col(col).map(|_| ()).map_err(|err| Error::ReadError(format!("{:?}", err)))) {
+        Ok(_) => (),
+        Err(err) => return Err(err)
+      }
+    }
+
+    Ok(frame)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json;
+
+  #[test]
+  fn test_read_mleap_serving() {
+    let json = r#"{
+      "schema": {
+        "fields": [
+          {"name": "state", "type": "string"},
+          {"name": "bathrooms", "type": {"type": "basic", "base": "double", "isNullable": false}},
+          {"name": "features", "type": {"type": "tensor", "base": "double", "dimensions": [3]}},
+          {"name": "tags", "type": {"type": "list", "base": "string"}}
+        ]
+      },
+      "rows": [
+        ["NY", 2.0, {"dimensions": [3], "values": [1.0, 2.0, 3.0]}, ["a", "b"]],
+        [null, 1.0, null, []]
+      ]
+    }"#;
+    let frame = LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()).unwrap();
+
+    assert_eq!(frame.size(), 2);
+    assert_eq!(frame.get_strings("state").unwrap(), &[String::from("NY"), String::new()]);
+    assert_eq!(frame.try_col("state").unwrap().validity(), Some(&[true, false][..]));
+    assert_eq!(frame.get_doubles("bathrooms").unwrap(), &[2.0, 1.0]);
+    assert_eq!(frame.try_col("bathrooms").unwrap().validity(), None);
+    assert_eq!(frame.get_double_tensors("features").unwrap()[0], DenseTensor::new(vec![3], vec![1.0, 2.0, 3.0]));
+    assert_eq!(frame.get_double_tensors("features").unwrap()[1], DenseTensor::new(vec![3], vec![]));
+    assert_eq!(frame.schema().get_field("tags").unwrap().data_type(), &DataType::new(BasicType::String, DataShape::List));
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut frame = LeapFrame::with_size(2);
+    frame.try_with_col(Col::from_doubles(String::from("a"), vec![1.5, 0.0]).with_validity(vec![true, false])).unwrap().
+      try_with_ints(String::from("b"), vec![1, 2]).unwrap().
+      try_with_sparse_double_tensors(String::from("c"), vec![SparseTensor::new(vec![2, 2], vec![3], vec![1.0]),
+                                                             SparseTensor::new(vec![2, 2], vec![], vec![])]).unwrap().
+      try_with_col(Col::new(String::from("d"), ColData::ByteString(vec![vec![1, 2], vec![]]))).unwrap();
+
+    let json = serde_json::to_string(&Value::from(&frame)).unwrap();
+    assert!(json.contains(r#"{"dimensions":[2,2],"indices":[[1,1]],"values":[1.0]}"#));
+
+    let read = LeapFrame::try_from(&serde_json::from_str::<Value>(&json).unwrap()).unwrap();
+    assert_eq!(read.schema(), frame.schema());
+    assert_eq!(read.try_col("a").unwrap().validity(), Some(&[true, false][..]));
+    assert_eq!(read.get_ints("b").unwrap(), &[1, 2]);
+    assert_eq!(read.get_sparse_double_tensors("c").unwrap(), frame.get_sparse_double_tensors("c").unwrap());
+    assert_eq!(serde_json::to_string(&Value::from(&read)).unwrap(), json);
+  }
+
+  #[test]
+  fn test_invalid_rows() {
+    let json = r#"{"schema": {"fields": [{"name": "a", "type": {"type": "basic", "base": "int", "isNullable": false}}]}, "rows": [[null]]}"#;
+    assert!(LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()).is_err());
+
+    let json = r#"{"schema": {"fields": [{"name": "a", "type": "int"}]}, "rows": [[1, 2]]}"#;
+    assert!(LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()).is_err());
+
+    let json = r#"{"schema": {"fields": [{"name": "a", "type": {"type": "tensor", "base": "double"}}]},
+                   "rows": [[{"dimensions": [3], "indices": [3], "values": [1.0]}]]}"#;
+    assert!(LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()).is_err());
+
+    let json = r#"{"schema": {"fields": [{"name": "a", "type": {"type": "tensor", "base": "double"}}]},
+                   "rows": [[{"dimensions": [3], "indices": [0, 1], "values": [1.0]}]]}"#;
+    assert!(LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()).is_err());
+
+    let json = r#"{"schema": {"fields": [{"name": "a", "type": {"type": "tensor", "base": "double"}}]},
+                   "rows": [[{"dimensions": [4294967296, 4294967296], "values": []}]]}"#;
+    match LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()) {
+      Err(Error::ReadError(_)) => (),
+      r => panic!("expected a read error, got {:?}", r.map(|f| f.size()))
+    }
+  }
+
+  #[test]
+  fn test_null_tensors_with_large_dimensions() {
+    let json = r#"{"schema": {"fields": [{"name": "a", "type": {"type": "tensor", "base": "double", "dimensions": [100000, 100000]}},
+                                         {"name": "b", "type": {"type": "tensor", "base": "double", "dimensions": [100000, 100000]}}]},
+                   "rows": [[null, {"dimensions": [100000, 100000], "indices": [[1, 2]], "values": [1.0]}],
+                            [null, null]]}"#;
+    let frame = LeapFrame::try_from(&serde_json::from_str::<Value>(json).unwrap()).unwrap();
+
+    assert_eq!(frame.try_col("a").unwrap().validity(), Some(&[false, false][..]));
+    assert_eq!(frame.get_double_tensors("a").unwrap()[0], DenseTensor::new(vec![100000, 100000], vec![]));
+    assert_eq!(frame.get_sparse_double_tensors("b").unwrap()[1].values().len(), 0);
+    assert_eq!(Value::from(&frame)["rows"][1], Value::Array(vec![Value::Null, Value::Null]));
+  }
+}
@@ -1,14 +1,29 @@
 use std::result;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
-use bundle::dsl::DenseTensor;
+use bundle::dsl::{DenseTensor, SparseTensor};
+
+pub mod schema;
+pub mod json;
+pub mod row;
+pub mod csv;
+pub mod proto;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+
+pub use self::schema::{BasicType, DataShape, DataType, StructField, Schema};
+pub use self::row::{Row, Rows, Value};
 
 #[derive(Debug)]
 pub enum Error {
   TransformError(String),
   InvalidType(String),
   ColumnAlreadyExists(String),
-  NoSuchColumn(String)
+  NoSuchColumn(String),
+  ReadError(String),
+  WriteError(String)
 }
 pub type Result<T> = result::Result<T, Error>;
 
@@ -16,7 +31,7 @@ pub trait Transformer {
   fn transform(&self, frame: &mut LeapFrame) -> Result<()>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ColData {
   Bool(Vec<bool>),
   String(Vec<String>),
@@ -46,67 +61,362 @@ pub enum ColData {
   LongTensor(Vec<DenseTensor<i64>>),
   FloatTensor(Vec<DenseTensor<f32>>),
   DoubleTensor(Vec<DenseTensor<f64>>),
-  ByteStringTensor(Vec<DenseTensor<Vec<u8>>>)
+  ByteStringTensor(Vec<DenseTensor<Vec<u8>>>),
+
+  SparseBoolTensor(Vec<SparseTensor<bool>>),
+  SparseStringTensor(Vec<SparseTensor<String>>),
+  SparseByteTensor(Vec<SparseTensor<i8>>),
+  SparseShortTensor(Vec<SparseTensor<i16>>),
+  SparseIntTensor(Vec<SparseTensor<i32>>),
+  SparseLongTensor(Vec<SparseTensor<i64>>),
+  SparseFloatTensor(Vec<SparseTensor<f32>>),
+  SparseDoubleTensor(Vec<SparseTensor<f64>>),
+  SparseByteStringTensor(Vec<SparseTensor<Vec<u8>>>)
 }
 
-#[derive(Debug)]
+/// A column with an optional validity bitmap, `false` marks a null row.
+/// Null rows still hold a placeholder value in `data`.
+#[derive(Debug, Clone)]
 pub struct Col {
   name: String,
-  data: ColData
+  data: ColData,
+  validity: Option<Vec<bool>>
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LeapFrame {
   size: usize,
+  skipped_rows: Vec<usize>,
   cols: Vec<Col>,
   col_indices_by_name: HashMap<String, usize>
 }
 
+impl ColData {
+  pub fn data_type(&self) -> DataType {
+    match *self {
+      ColData::Bool(_) => DataType::new(BasicType::Bool, DataShape::Scalar),
+      ColData::String(_) => DataType::new(BasicType::String, DataShape::Scalar),
+      ColData::Byte(_) => DataType::new(BasicType::Byte, DataShape::Scalar),
+      ColData::Short(_) => DataType::new(BasicType::Short, DataShape::Scalar),
+      ColData::Int(_) => DataType::new(BasicType::Int, DataShape::Scalar),
+      ColData::Long(_) => DataType::new(BasicType::Long, DataShape::Scalar),
+      ColData::Float(_) => DataType::new(BasicType::Float, DataShape::Scalar),
+      ColData::Double(_) => DataType::new(BasicType::Double, DataShape::Scalar),
+      ColData::ByteString(_) => DataType::new(BasicType::ByteString, DataShape::Scalar),
+      ColData::BoolVector(_) => DataType::new(BasicType::Bool, DataShape::List),
+      ColData::StringVector(_) => DataType::new(BasicType::String, DataShape::List),
+      ColData::ByteVector(_) => DataType::new(BasicType::Byte, DataShape::List),
+      ColData::ShortVector(_) => DataType::new(BasicType::Short, DataShape::List),
+      ColData::IntVector(_) => DataType::new(BasicType::Int, DataShape::List),
+      ColData::LongVector(_) => DataType::new(BasicType::Long, DataShape::List),
+      ColData::FloatVector(_) => DataType::new(BasicType::Float, DataShape::List),
+      ColData::DoubleVector(_) => DataType::new(BasicType::Double, DataShape::List),
+      ColData::ByteStringVector(_) => DataType::new(BasicType::ByteString, DataShape::List),
+      ColData::BoolTensor(ref t) => DataType::new(BasicType::Bool, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::StringTensor(ref t) => DataType::new(BasicType::String, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::ByteTensor(ref t) => DataType::new(BasicType::Byte, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::ShortTensor(ref t) => DataType::new(BasicType::Short, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::IntTensor(ref t) => DataType::new(BasicType::Int, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::LongTensor(ref t) => DataType::new(BasicType::Long, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::FloatTensor(ref t) => DataType::new(BasicType::Float, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::DoubleTensor(ref t) => DataType::new(BasicType::Double, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::ByteStringTensor(ref t) => DataType::new(BasicType::ByteString, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseBoolTensor(ref t) => DataType::new(BasicType::Bool, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseStringTensor(ref t) => DataType::new(BasicType::String, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseByteTensor(ref t) => DataType::new(BasicType::Byte, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseShortTensor(ref t) => DataType::new(BasicType::Short, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseIntTensor(ref t) => DataType::new(BasicType::Int, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseLongTensor(ref t) => DataType::new(BasicType::Long, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseFloatTensor(ref t) => DataType::new(BasicType::Float, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseDoubleTensor(ref t) => DataType::new(BasicType::Double, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec()))),
+      ColData::SparseByteStringTensor(ref t) => DataType::new(BasicType::ByteString, DataShape::Tensor(t.first().map(|x| x.dimensions().to_vec())))
+    }
+  }
+
+  /// Drops every row whose flag in `keep` is false.
+  pub fn retain(&mut self, keep: &[bool]) {
+    fn retain<T>(v: &mut Vec<T>, keep: &[bool]) {
+      let mut i = 0;
+      v.retain(|_| { i += 1; keep[i - 1] });
+    }
+
+    match *self {
+      ColData::Bool(ref mut v) => retain(v, keep),
+      ColData::String(ref mut v) => retain(v, keep),
+      ColData::Byte(ref mut v) => retain(v, keep),
+      ColData::Short(ref mut v) => retain(v, keep),
+      ColData::Int(ref mut v) => retain(v, keep),
+      ColData::Long(ref mut v) => retain(v, keep),
+      ColData::Float(ref mut v) => retain(v, keep),
+      ColData::Double(ref mut v) => retain(v, keep),
+      ColData::ByteString(ref mut v) => retain(v, keep),
+      ColData::BoolVector(ref mut v) => retain(v, keep),
+      ColData::StringVector(ref mut v) => retain(v, keep),
+      ColData::ByteVector(ref mut v) => retain(v, keep),
+      ColData::ShortVector(ref mut v) => retain(v, keep),
+      ColData::IntVector(ref mut v) => retain(v, keep),
+      ColData::LongVector(ref mut v) => retain(v, keep),
+      ColData::FloatVector(ref mut v) => retain(v, keep),
+      ColData::DoubleVector(ref mut v) => retain(v, keep),
+      ColData::ByteStringVector(ref mut v) => retain(v, keep),
+      ColData::BoolTensor(ref mut v) => retain(v, keep),
+      ColData::StringTensor(ref mut v) => retain(v, keep),
+      ColData::ByteTensor(ref mut v) => retain(v, keep),
+      ColData::ShortTensor(ref mut v) => retain(v, keep),
+      ColData::IntTensor(ref mut v) => retain(v, keep),
+      ColData::LongTensor(ref mut v) => retain(v, keep),
+      ColData::FloatTensor(ref mut v) => retain(v, keep),
+      ColData::DoubleTensor(ref mut v) => retain(v, keep),
+      ColData::ByteStringTensor(ref mut v) => retain(v, keep),
+      ColData::SparseBoolTensor(ref mut v) => retain(v, keep),
+      ColData::SparseStringTensor(ref mut v) => retain(v, keep),
+      ColData::SparseByteTensor(ref mut v) => retain(v, keep),
+      ColData::SparseShortTensor(ref mut v) => retain(v, keep),
+      ColData::SparseIntTensor(ref mut v) => retain(v, keep),
+      ColData::SparseLongTensor(ref mut v) => retain(v, keep),
+      ColData::SparseFloatTensor(ref mut v) => retain(v, keep),
+      ColData::SparseDoubleTensor(ref mut v) => retain(v, keep),
+      ColData::SparseByteStringTensor(ref mut v) => retain(v, keep)
+    }
+  }
+
+  /// The number of rows.
+  pub fn len(&self) -> usize {
+    match *self {
+      ColData::Bool(ref v) => v.len(),
+      ColData::String(ref v) => v.len(),
+      ColData::Byte(ref v) => v.len(),
+      ColData::Short(ref v) => v.len(),
+      ColData::Int(ref v) => v.len(),
+      ColData::Long(ref v) => v.len(),
+      ColData::Float(ref v) => v.len(),
+      ColData::Double(ref v) => v.len(),
+      ColData::ByteString(ref v) => v.len(),
+      ColData::BoolVector(ref v) => v.len(),
+      ColData::StringVector(ref v) => v.len(),
+      ColData::ByteVector(ref v) => v.len(),
+      ColData::ShortVector(ref v) => v.len(),
+      ColData::IntVector(ref v) => v.len(),
+      ColData::LongVector(ref v) => v.len(),
+      ColData::FloatVector(ref v) => v.len(),
+      ColData::DoubleVector(ref v) => v.len(),
+      ColData::ByteStringVector(ref v) => v.len(),
+      ColData::BoolTensor(ref v) => v.len(),
+      ColData::StringTensor(ref v) => v.len(),
+      ColData::ByteTensor(ref v) => v.len(),
+      ColData::ShortTensor(ref v) => v.len(),
+      ColData::IntTensor(ref v) => v.len(),
+      ColData::LongTensor(ref v) => v.len(),
+      ColData::FloatTensor(ref v) => v.len(),
+      ColData::DoubleTensor(ref v) => v.len(),
+      ColData::ByteStringTensor(ref v) => v.len(),
+      ColData::SparseBoolTensor(ref v) => v.len(),
+      ColData::SparseStringTensor(ref v) => v.len(),
+      ColData::SparseByteTensor(ref v) => v.len(),
+      ColData::SparseShortTensor(ref v) => v.len(),
+      ColData::SparseIntTensor(ref v) => v.len(),
+      ColData::SparseLongTensor(ref v) => v.len(),
+      ColData::SparseFloatTensor(ref v) => v.len(),
+      ColData::SparseDoubleTensor(ref v) => v.len(),
+      ColData::SparseByteStringTensor(ref v) => v.len()
+    }
+  }
+
+  pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+  /// Whether every sparse tensor has one index per value, each within its
+  /// dense tensor. Always true for other columns.
+  pub fn has_valid_sparse_tensors(&self) -> bool {
+    fn valid<T>(v: &[SparseTensor<T>]) -> bool { v.iter().all(|t| t.is_valid()) }
+
+    match *self {
+      ColData::SparseBoolTensor(ref v) => valid(v),
+      ColData::SparseStringTensor(ref v) => valid(v),
+      ColData::SparseByteTensor(ref v) => valid(v),
+      ColData::SparseShortTensor(ref v) => valid(v),
+      ColData::SparseIntTensor(ref v) => valid(v),
+      ColData::SparseLongTensor(ref v) => valid(v),
+      ColData::SparseFloatTensor(ref v) => valid(v),
+      ColData::SparseDoubleTensor(ref v) => valid(v),
+      ColData::SparseByteStringTensor(ref v) => valid(v),
+      _ => true
+    }
+  }
+
+  pub fn slice(&self, range: Range<usize>) -> ColData {
+    match *self {
+      ColData::Bool(ref v) => ColData::Bool(v[range.clone()].to_vec()),
+      ColData::String(ref v) => ColData::String(v[range.clone()].to_vec()),
+      ColData::Byte(ref v) => ColData::Byte(v[range.clone()].to_vec()),
+      ColData::Short(ref v) => ColData::Short(v[range.clone()].to_vec()),
+      ColData::Int(ref v) => ColData::Int(v[range.clone()].to_vec()),
+      ColData::Long(ref v) => ColData::Long(v[range.clone()].to_vec()),
+      ColData::Float(ref v) => ColData::Float(v[range.clone()].to_vec()),
+      ColData::Double(ref v) => ColData::Double(v[range.clone()].to_vec()),
+      ColData::ByteString(ref v) => ColData::ByteString(v[range.clone()].to_vec()),
+      ColData::BoolVector(ref v) => ColData::BoolVector(v[range.clone()].to_vec()),
+      ColData::StringVector(ref v) => ColData::StringVector(v[range.clone()].to_vec()),
+      ColData::ByteVector(ref v) => ColData::ByteVector(v[range.clone()].to_vec()),
+      ColData::ShortVector(ref v) => ColData::ShortVector(v[range.clone()].to_vec()),
+      ColData::IntVector(ref v) => ColData::IntVector(v[range.clone()].to_vec()),
+      ColData::LongVector(ref v) => ColData::LongVector(v[range.clone()].to_vec()),
+      ColData::FloatVector(ref v) => ColData::FloatVector(v[range.clone()].to_vec()),
+      ColData::DoubleVector(ref v) => ColData::DoubleVector(v[range.clone()].to_vec()),
+      ColData::ByteStringVector(ref v) => ColData::ByteStringVector(v[range.clone()].to_vec()),
+      ColData::BoolTensor(ref v) => ColData::BoolTensor(v[range.clone()].to_vec()),
+      ColData::StringTensor(ref v) => ColData::StringTensor(v[range.clone()].to_vec()),
+      ColData::ByteTensor(ref v) => ColData::ByteTensor(v[range.clone()].to_vec()),
+      ColData::ShortTensor(ref v) => ColData::ShortTensor(v[range.clone()].to_vec()),
+      ColData::IntTensor(ref v) => ColData::IntTensor(v[range.clone()].to_vec()),
+      ColData::LongTensor(ref v) => ColData::LongTensor(v[range.clone()].to_vec()),
+      ColData::FloatTensor(ref v) => ColData::FloatTensor(v[range.clone()].to_vec()),
+      ColData::DoubleTensor(ref v) => ColData::DoubleTensor(v[range.clone()].to_vec()),
+      ColData::ByteStringTensor(ref v) => ColData::ByteStringTensor(v[range.clone()].to_vec()),
+      ColData::SparseBoolTensor(ref v) => ColData::SparseBoolTensor(v[range.clone()].to_vec()),
+      ColData::SparseStringTensor(ref v) => ColData::SparseStringTensor(v[range.clone()].to_vec()),
+      ColData::SparseByteTensor(ref v) => ColData::SparseByteTensor(v[range.clone()].to_vec()),
+      ColData::SparseShortTensor(ref v) => ColData::SparseShortTensor(v[range.clone()].to_vec()),
+      ColData::SparseIntTensor(ref v) => ColData::SparseIntTensor(v[range.clone()].to_vec()),
+      ColData::SparseLongTensor(ref v) => ColData::SparseLongTensor(v[range.clone()].to_vec()),
+      ColData::SparseFloatTensor(ref v) => ColData::SparseFloatTensor(v[range.clone()].to_vec()),
+      ColData::SparseDoubleTensor(ref v) => ColData::SparseDoubleTensor(v[range.clone()].to_vec()),
+      ColData::SparseByteStringTensor(ref v) => ColData::SparseByteStringTensor(v[range.clone()].to_vec())
+    }
+  }
+
+  /// Appends the rows of `other`, which must hold the same type.
+  pub fn try_extend(&mut self, other: &ColData) -> Result<()> {
+    match (self, other) {
+      (&mut ColData::Bool(ref mut a), &ColData::Bool(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::String(ref mut a), &ColData::String(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::Byte(ref mut a), &ColData::Byte(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::Short(ref mut a), &ColData::Short(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::Int(ref mut a), &ColData::Int(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::Long(ref mut a), &ColData::Long(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::Float(ref mut a), &ColData::Float(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::Double(ref mut a), &ColData::Double(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ByteString(ref mut a), &ColData::ByteString(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::BoolVector(ref mut a), &ColData::BoolVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::StringVector(ref mut a), &ColData::StringVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ByteVector(ref mut a), &ColData::ByteVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ShortVector(ref mut a), &ColData::ShortVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::IntVector(ref mut a), &ColData::IntVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::LongVector(ref mut a), &ColData::LongVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::FloatVector(ref mut a), &ColData::FloatVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::DoubleVector(ref mut a), &ColData::DoubleVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ByteStringVector(ref mut a), &ColData::ByteStringVector(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::BoolTensor(ref mut a), &ColData::BoolTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::StringTensor(ref mut a), &ColData::StringTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ByteTensor(ref mut a), &ColData::ByteTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ShortTensor(ref mut a), &ColData::ShortTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::IntTensor(ref mut a), &ColData::IntTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::LongTensor(ref mut a), &ColData::LongTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::FloatTensor(ref mut a), &ColData::FloatTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::DoubleTensor(ref mut a), &ColData::DoubleTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::ByteStringTensor(ref mut a), &ColData::ByteStringTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseBoolTensor(ref mut a), &ColData::SparseBoolTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseStringTensor(ref mut a), &ColData::SparseStringTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseByteTensor(ref mut a), &ColData::SparseByteTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseShortTensor(ref mut a), &ColData::SparseShortTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseIntTensor(ref mut a), &ColData::SparseIntTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseLongTensor(ref mut a), &ColData::SparseLongTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseFloatTensor(ref mut a), &ColData::SparseFloatTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseDoubleTensor(ref mut a), &ColData::SparseDoubleTensor(ref b)) => a.extend_from_slice(b),
+      (&mut ColData::SparseByteStringTensor(ref mut a), &ColData::SparseByteStringTensor(ref b)) => a.extend_from_slice(b),
+      (a, b) => return Err(Error::InvalidType(format!("Cannot append {} to {}", b.data_type(), a.data_type())))
+    }
+
+    Ok(())
+  }
+}
+
 impl Col {
   pub fn new(name: String, data: ColData) -> Col {
     Col {
       name: name,
-      data: data
+      data: data,
+      validity: None
     }
   }
 
   pub fn from_doubles(name: String, v: Vec<f64>) -> Col {
     Col {
       name: name,
-      data: ColData::Double(v)
+      data: ColData::Double(v),
+      validity: None
     }
   }
 
   pub fn from_double_tensors(name: String, v: Vec<DenseTensor<f64>>) -> Col {
     Col {
       name: name,
-      data: ColData::DoubleTensor(v)
+      data: ColData::DoubleTensor(v),
+      validity: None
+    }
+  }
+
+  pub fn from_sparse_double_tensors(name: String, v: Vec<SparseTensor<f64>>) -> Col {
+    Col {
+      name: name,
+      data: ColData::SparseDoubleTensor(v),
+      validity: None
     }
   }
 
   pub fn from_strings(name: String, v: Vec<String>) -> Col {
     Col {
       name: name,
-      data: ColData::String(v)
+      data: ColData::String(v),
+      validity: None
     }
   }
 
   pub fn from_ints(name: String, v: Vec<i32>) -> Col {
     Col {
       name: name,
-      data: ColData::Int(v)
+      data: ColData::Int(v),
+      validity: None
     }
   }
 
   pub fn from_long_tensors(name: String, v: Vec<DenseTensor<i64>>) -> Col {
     Col {
       name: name,
-      data: ColData::LongTensor(v)
+      data: ColData::LongTensor(v),
+      validity: None
     }
   }
 
+  pub fn with_validity(mut self, validity: Vec<bool>) -> Col {
+    self.validity = Some(validity);
+    self
+  }
+
   pub fn name(&self) -> &str { &self.name }
   pub fn data(&self) -> &ColData { &self.data }
+  pub fn validity(&self) -> Option<&[bool]> { self.validity.as_ref().map(|v| v.as_slice()) }
+
+  pub fn is_valid(&self, row: usize) -> bool {
+    self.validity.as_ref().map(|v| v[row]).unwrap_or(true)
+  }
+
+  pub fn null_count(&self) -> usize {
+    self.validity.as_ref().map(|v| v.iter().filter(|x| !**x).count()).unwrap_or(0)
+  }
+
+  /// Columns with a validity bitmap are nullable, even without nulls.
+  pub fn field(&self) -> StructField {
+    StructField::new(self.name.clone(), self.data.data_type(), self.validity.is_some())
+  }
+
+  /// Models without null semantics of their own reject null features.
+  pub fn try_non_null(&self) -> Result<&Self> {
+    if self.null_count() == 0 {
+      Ok(self)
+    } else {
+      Err(Error::TransformError(format!("Column {} has null values", self.name)))
+    }
+  }
 
   pub fn get_doubles(&self) -> Option<&[f64]> {
     match self.data {
@@ -132,6 +442,31 @@ impl Col {
   }
   pub fn try_double_tensors(&self) -> Result<&[DenseTensor<f64>]> { Self::option_to_result(self.get_double_tensors()) }
 
+  pub fn get_sparse_double_tensors(&self) -> Option<&[SparseTensor<f64>]> {
+    match self.data {
+      ColData::SparseDoubleTensor(ref v) => Some(v),
+      _ => None
+    }
+  }
+  pub fn try_sparse_double_tensors(&self) -> Result<&[SparseTensor<f64>]> { Self::option_to_result(self.get_sparse_double_tensors()) }
+
+  /// Double tensors, densifying a sparse column. Null rows are left
+  /// without values.
+  pub fn get_dense_double_tensors(&self) -> Option<Cow<[DenseTensor<f64>]>> {
+    match self.data {
+      ColData::DoubleTensor(ref v) => Some(Cow::Borrowed(v)),
+      ColData::SparseDoubleTensor(ref v) => {
+        Some(Cow::Owned(v.iter().enumerate().map(|(row, t)| {
+          if self.is_valid(row) { t.to_dense() } else { DenseTensor::new(t.dimensions().to_vec(), vec![]) }
+        }).collect()))
+      },
+      _ => None
+    }
+  }
+  pub fn try_dense_double_tensors(&self) -> Result<Cow<[DenseTensor<f64>]>> {
+    self.try_non_null().and_then(|c| Self::option_to_result(c.get_dense_double_tensors()))
+  }
+
   pub fn get_strings(&self) -> Option<&[String]> {
     match self.data {
       ColData::String(ref v) => Some(v),
@@ -149,6 +484,7 @@ impl LeapFrame {
   pub fn with_size(size: usize) -> LeapFrame {
     LeapFrame {
       size: size,
+      skipped_rows: Vec::new(),
       cols: Vec::new(),
       col_indices_by_name: HashMap::new()
     }
@@ -157,14 +493,64 @@ impl LeapFrame {
   pub fn size(&self) -> usize { self.size }
   pub fn cols(&self) -> &[Col] { &self.cols }
 
+  pub fn schema(&self) -> Schema {
+    Schema::new(self.cols.iter().map(|c| c.field()).collect())
+  }
+
+  /// Indices, in the frame as it was created, of the rows dropped by
+  /// `retain_rows`.
+  pub fn skipped_rows(&self) -> &[usize] { &self.skipped_rows }
+
+  pub fn retain_rows(&mut self, keep: &[bool]) -> Result<&mut Self> {
+    if keep.len() != self.size {
+      return Err(Error::TransformError(format!("Expected {} row flags, got {}", self.size, keep.len())));
+    }
+
+    let mut skipped = self.skipped_rows.iter().peekable();
+    let mut original = 0;
+    let mut dropped = Vec::new();
+
+    for k in keep.iter() {
+      while skipped.peek() == Some(&&original) {
+        skipped.next();
+        original += 1;
+      }
+
+      if !k { dropped.push(original); }
+      original += 1;
+    }
+
+    for col in self.cols.iter_mut() {
+      col.data.retain(keep);
+
+      if let Some(ref mut validity) = col.validity {
+        let mut i = 0;
+        validity.retain(|_| { i += 1; keep[i - 1] });
+      }
+    }
+
+    self.skipped_rows.extend(dropped);
+    self.skipped_rows.sort();
+    self.size = keep.iter().filter(|k| **k).count();
+
+    Ok(self)
+  }
+
   pub fn try_with_doubles(&mut self, name: String, v: Vec<f64>) -> Result<&mut Self> { self.try_with_col(Col::from_doubles(name, v)) }
   pub fn try_with_double_tensors(&mut self, name: String, v: Vec<DenseTensor<f64>>) -> Result<&mut Self> { self.try_with_col(Col::from_double_tensors(name, v)) }
+  pub fn try_with_sparse_double_tensors(&mut self, name: String, v: Vec<SparseTensor<f64>>) -> Result<&mut Self> { self.try_with_col(Col::from_sparse_double_tensors(name, v)) }
   pub fn try_with_strings(&mut self, name: String, v: Vec<String>) -> Result<&mut Self> { self.try_with_col(Col::from_strings(name, v)) }
   pub fn try_with_ints(&mut self, name: String, v: Vec<i32>) -> Result<&mut Self> { self.try_with_col(Col::from_ints(name, v)) }
 
   pub fn try_with_col(&mut self, col: Col) -> Result<&mut Self> {
     if self.col_indices_by_name.contains_key(col.name()) {
       Err(Error::ColumnAlreadyExists(String::from(col.name())))
+    } else if col.data().len() != self.size {
+      Err(Error::TransformError(format!("Column {} has {} rows, expected {}", col.name(), col.data().len(), self.size)))
+    } else if col.validity().map(|v| v.len() != self.size).unwrap_or(false) {
+      Err(Error::TransformError(format!("Validity of column {} does not match the frame size", col.name())))
+    } else if !col.data().has_valid_sparse_tensors() {
+      Err(Error::InvalidType(format!("Column {} has sparse tensor indices that do not match its values or dimensions", col.name())))
     } else {
       self.col_indices_by_name.insert(col.name().to_string(), self.cols.len());
       self.cols.push(col);
@@ -173,6 +559,147 @@ impl LeapFrame {
     }
   }
 
+  /// Keeps only the columns in `names`, in that order.
+  pub fn select(&mut self, names: &[&str]) -> Result<&mut Self> {
+    let mut seen = HashSet::with_capacity(names.len());
+
+    for name in names.iter() {
+      if !self.col_indices_by_name.contains_key(*name) {
+        return Err(Error::NoSuchColumn(name.to_string()));
+      } else if !seen.insert(*name) {
+        return Err(Error::ColumnAlreadyExists(name.to_string()));
+      }
+    }
+
+    let mut cols: Vec<Option<Col>> = self.cols.drain(..).map(Some).collect();
+    self.cols = names.iter().map(|name| cols[self.col_indices_by_name[*name]].take().unwrap()).collect();
+    self.reindex();
+
+    Ok(self)
+  }
+
+  pub fn drop(&mut self, names: &[&str]) -> Result<&mut Self> {
+    if let Some(name) = names.iter().find(|name| !self.col_indices_by_name.contains_key(**name)) {
+      return Err(Error::NoSuchColumn(name.to_string()));
+    }
+
+    self.cols.retain(|c| !names.contains(&c.name()));
+    self.reindex();
+
+    Ok(self)
+  }
+
+  pub fn rename(&mut self, name: &str, new_name: &str) -> Result<&mut Self> {
+    if name != new_name && self.col_indices_by_name.contains_key(new_name) {
+      return Err(Error::ColumnAlreadyExists(new_name.to_string()));
+    }
+
+    match self.col_indices_by_name.remove(name) {
+      Some(i) => {
+        self.cols[i].name = new_name.to_string();
+        self.col_indices_by_name.insert(new_name.to_string(), i);
+
+        Ok(self)
+      },
+      None => Err(Error::NoSuchColumn(name.to_string()))
+    }
+  }
+
+  /// A new frame with the rows in `range`. Like `filter` and `concat`, the
+  /// result is a fresh batch without skipped rows.
+  pub fn slice(&self, range: Range<usize>) -> Result<LeapFrame> {
+    if range.start > range.end || range.end > self.size {
+      return Err(Error::TransformError(format!("Invalid range {:?} for {} rows", range, self.size)));
+    }
+
+    let mut frame = LeapFrame::with_size(range.end - range.start);
+
+    for col in self.cols.iter() {
+      frame.cols.push(Col {
+        name: col.name.clone(),
+        data: col.data.slice(range.clone()),
+        validity: col.validity.as_ref().map(|v| v[range.clone()].to_vec())
+      });
+    }
+
+    frame.reindex();
+    Ok(frame)
+  }
+
+  /// A new frame with the rows whose flag in `mask` is true.
+  pub fn filter(&self, mask: &[bool]) -> Result<LeapFrame> {
+    if mask.len() != self.size {
+      return Err(Error::TransformError(format!("Expected {} row flags, got {}", self.size, mask.len())));
+    }
+
+    let mut frame = LeapFrame::with_size(mask.iter().filter(|m| **m).count());
+
+    for col in self.cols.iter() {
+      let mut col = col.clone();
+      col.data.retain(mask);
+
+      if let Some(ref mut validity) = col.validity {
+        let mut i = 0;
+        validity.retain(|_| { i += 1; mask[i - 1] });
+      }
+
+      frame.cols.push(col);
+    }
+
+    frame.reindex();
+    Ok(frame)
+  }
+
+  /// Stacks frames with the same columns, in the column order of the first
+  /// one. Columns with nulls in any frame stay nullable.
+  pub fn concat(frames: &[LeapFrame]) -> Result<LeapFrame> {
+    let first = match frames.first() {
+      Some(first) => first,
+      None => return Ok(LeapFrame::with_size(0))
+    };
+
+    if let Some(other) = frames.iter().find(|f| f.cols.len() != first.cols.len()) {
+      return Err(Error::InvalidType(format!("Cannot concat frames with {} and {} columns", first.cols.len(), other.cols.len())));
+    }
+
+    let mut frame = LeapFrame::with_size(frames.iter().map(|f| f.size).sum());
+
+    for col in first.cols.iter() {
+      let mut data = col.data.slice(0..0);
+      let mut validity = Vec::with_capacity(frame.size);
+
+      for other in frames.iter() {
+        let other_col = match other.try_col(&col.name) {
+          Ok(c) => c,
+          Err(err) => return Err(err)
+        };
+
+        if let Err(err) = data.try_extend(&other_col.data) {
+          return Err(err);
+        }
+
+        validity.extend((0..other.size).map(|row| other_col.is_valid(row)));
+      }
+
+      let nullable = frames.iter().any(|f| f.cols[f.col_indices_by_name[&col.name]].validity.is_some());
+      frame.cols.push(Col {
+        name: col.name.clone(),
+        data: data,
+        validity: if nullable { Some(validity) } else { None }
+      });
+    }
+
+    frame.reindex();
+    Ok(frame)
+  }
+
+  fn reindex(&mut self) {
+    self.col_indices_by_name = self.cols.iter().
+      enumerate().
+      map(|(i, c)| (c.name.clone(), i)).
+      collect();
+  }
+
   pub fn get_col(&self, name: &str) -> Option<&Col> {
     self.col_indices_by_name.get(name).map(|i| &self.cols[*i])
   }
@@ -203,6 +730,128 @@ impl LeapFrame {
   pub fn get_double_tensors(&self, name: &str) -> Option<&[DenseTensor<f64>]> { self.get_col(name).and_then(|c| c.get_double_tensors()) }
   pub fn try_double_tensors(&self, name: &str) -> Result<&[DenseTensor<f64>]> { self.try_col(name).and_then(|c| c.try_double_tensors()) }
 
+  pub fn get_sparse_double_tensors(&self, name: &str) -> Option<&[SparseTensor<f64>]> { self.get_col(name).and_then(|c| c.get_sparse_double_tensors()) }
+  pub fn try_sparse_double_tensors(&self, name: &str) -> Result<&[SparseTensor<f64>]> { self.try_col(name).and_then(|c| c.try_sparse_double_tensors()) }
+
+  pub fn get_dense_double_tensors(&self, name: &str) -> Option<Cow<[DenseTensor<f64>]>> { self.get_col(name).and_then(|c| c.get_dense_double_tensors()) }
+  pub fn try_dense_double_tensors(&self, name: &str) -> Result<Cow<[DenseTensor<f64>]>> { self.try_col(name).and_then(|c| c.try_dense_double_tensors()) }
+
   pub fn get_strings(&self, name: &str) -> Option<&[String]> { self.get_col(name).and_then(|c| c.get_strings()) }
   pub fn try_strings(&self, name: &str) -> Result<&[String]> { self.try_col(name).and_then(|c| c.try_strings()) }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_retain_rows() {
+    let mut frame = LeapFrame::with_size(5);
+    frame.try_with_ints(String::from("a"), vec![0, 1, 2, 3, 4]).unwrap();
+
+    frame.retain_rows(&[true, false, true, true, false]).unwrap();
+    assert_eq!(frame.get_ints("a").unwrap(), &[0, 2, 3]);
+
+    frame.retain_rows(&[false, true, true]).unwrap();
+    assert_eq!(frame.size(), 2);
+    assert_eq!(frame.get_ints("a").unwrap(), &[2, 3]);
+    assert_eq!(frame.skipped_rows(), &[0, 1, 4]);
+
+    assert!(frame.retain_rows(&[true]).is_err());
+  }
+
+  #[test]
+  fn test_select_drop_rename() {
+    let mut frame = LeapFrame::with_size(1);
+    frame.try_with_ints(String::from("a"), vec![0]).unwrap().
+      try_with_ints(String::from("b"), vec![1]).unwrap().
+      try_with_ints(String::from("c"), vec![2]).unwrap();
+
+    frame.select(&["c", "a"]).unwrap();
+    assert_eq!(frame.cols().iter().map(|c| c.name()).collect::<Vec<&str>>(), vec!["c", "a"]);
+    assert_eq!(frame.get_ints("a").unwrap(), &[0]);
+    assert!(frame.get_col("b").is_none());
+    assert!(frame.select(&["a", "b"]).is_err());
+    assert!(frame.select(&["a", "a"]).is_err());
+
+    frame.rename("c", "d").unwrap();
+    assert_eq!(frame.get_ints("d").unwrap(), &[2]);
+    assert!(frame.get_col("c").is_none());
+    assert!(frame.rename("d", "a").is_err());
+
+    frame.drop(&["a"]).unwrap();
+    assert_eq!(frame.get_ints("d").unwrap(), &[2]);
+    assert!(frame.drop(&["a"]).is_err());
+  }
+
+  #[test]
+  fn test_slice_filter_concat() {
+    let mut frame = LeapFrame::with_size(4);
+    frame.try_with_ints(String::from("a"), vec![0, 1, 2, 3]).unwrap().
+      try_with_col(Col::from_doubles(String::from("b"), vec![0.0, 1.0, 2.0, 3.0]).with_validity(vec![true, false, true, true])).unwrap();
+
+    let head = frame.slice(0..1).unwrap();
+    let tail = frame.slice(1..4).unwrap();
+    assert_eq!(tail.get_ints("a").unwrap(), &[1, 2, 3]);
+    assert_eq!(tail.try_col("b").unwrap().validity(), Some(&[false, true, true][..]));
+    assert!(frame.slice(3..5).is_err());
+
+    let odd = frame.filter(&[false, true, false, true]).unwrap();
+    assert_eq!(odd.size(), 2);
+    assert_eq!(odd.get_doubles("b").unwrap(), &[1.0, 3.0]);
+
+    let all = LeapFrame::concat(&[head, tail]).unwrap();
+    assert_eq!(all.size(), 4);
+    assert_eq!(all.get_ints("a").unwrap(), frame.get_ints("a").unwrap());
+    assert_eq!(all.try_col("b").unwrap().validity(), frame.try_col("b").unwrap().validity());
+
+    let mut other = LeapFrame::with_size(1);
+    other.try_with_doubles(String::from("a"), vec![0.0]).unwrap().
+      try_with_doubles(String::from("b"), vec![0.0]).unwrap();
+    assert!(LeapFrame::concat(&[all, other]).is_err());
+  }
+
+  #[test]
+  fn test_validity() {
+    let mut frame = LeapFrame::with_size(3);
+    frame.try_with_col(Col::from_doubles(String::from("a"), vec![1.0, 0.0, 3.0]).with_validity(vec![true, false, true])).unwrap();
+    assert!(frame.try_with_col(Col::from_doubles(String::from("b"), vec![1.0, 2.0, 3.0]).with_validity(vec![true])).is_err());
+    match frame.try_with_doubles(String::from("c"), vec![1.0, 2.0]) {
+      Err(Error::TransformError(msg)) => assert_eq!(msg, "Column c has 2 rows, expected 3"),
+      r => panic!("Expected a column of the wrong size to be rejected, got {:?}", r.map(|_| ()))
+    }
+
+    assert_eq!(frame.try_col("a").unwrap().null_count(), 1);
+    assert!(frame.schema().get_field("a").unwrap().nullable());
+
+    frame.retain_rows(&[false, true, true]).unwrap();
+    let col = frame.try_col("a").unwrap();
+    assert_eq!(col.validity(), Some(&[false, true][..]));
+    assert!(col.try_non_null().is_err());
+  }
+
+  #[test]
+  fn test_invalid_sparse_tensors() {
+    let mut frame = LeapFrame::with_size(1);
+    assert!(frame.try_with_sparse_double_tensors(String::from("a"), vec![SparseTensor::new(vec![3], vec![3], vec![1.0])]).is_err());
+    assert!(frame.try_with_sparse_double_tensors(String::from("b"), vec![SparseTensor::new(vec![3], vec![0, 1], vec![1.0])]).is_err());
+    assert!(frame.try_with_sparse_double_tensors(String::from("c"), vec![SparseTensor::new(vec![3], vec![2], vec![1.0])]).is_ok());
+    assert!(SparseTensor::try_new(vec![usize::max_value(), 2], vec![0], vec![1.0]).is_none());
+  }
+
+  #[test]
+  fn test_schema() {
+    let mut frame = LeapFrame::with_size(1);
+    frame.try_with_strings(String::from("s"), vec![String::from("a")]).unwrap().
+      try_with_sparse_double_tensors(String::from("t"), vec![SparseTensor::new(vec![4], vec![1], vec![1.0])]).unwrap().
+      try_with_col(Col::new(String::from("l"), ColData::LongVector(vec![vec![1, 2]]))).unwrap();
+
+    let schema = frame.schema();
+    assert_eq!(schema.fields(),
+               &[StructField::new(String::from("s"), DataType::new(BasicType::String, DataShape::Scalar), false),
+                 StructField::new(String::from("t"), DataType::new(BasicType::Double, DataShape::Tensor(Some(vec![4]))), false),
+                 StructField::new(String::from("l"), DataType::new(BasicType::Long, DataShape::List), false)]);
+    assert_eq!(schema.get_field("t").unwrap().data_type().to_string(), "tensor<double>[4]");
+    assert!(schema.get_field("x").is_none());
+  }
+}
@@ -0,0 +1,339 @@
+//! LeapFrames and schemas as `ml.combust.mleap.pb` protobuf messages.
+//!
+//! Cells are encoded like model attributes, as `ml_bundle::Value`s read
+//! with the type of their field. Null cells are values with neither a
+//! scalar nor a list, and sparse tensors are sent dense.
+use std::result::Result;
+
+use bundle::dsl;
+use bundle::json::TryFrom;
+use bundle::proto::{self, ml_bundle, runtime, Error};
+use super::{BasicType, DataShape, DataType, LeapFrame, Row, Schema, StructField, Value};
+
+fn base_to_proto(base: BasicType) -> ml_bundle::BasicType {
+  match base {
+    BasicType::Bool => ml_bundle::BasicType::Boolean,
+    BasicType::String => ml_bundle::BasicType::String,
+    BasicType::Byte => ml_bundle::BasicType::Byte,
+    BasicType::Short => ml_bundle::BasicType::Short,
+    BasicType::Int => ml_bundle::BasicType::Int,
+    BasicType::Long => ml_bundle::BasicType::Long,
+    BasicType::Float => ml_bundle::BasicType::Float,
+    BasicType::Double => ml_bundle::BasicType::Double,
+    BasicType::ByteString => ml_bundle::BasicType::ByteString
+  }
+}
+
+fn base_from_proto(base: i32) -> Option<BasicType> {
+  match ::std::convert::TryFrom::try_from(base) {
+    Ok(ml_bundle::BasicType::Boolean) => Some(BasicType::Bool),
+    Ok(ml_bundle::BasicType::String) => Some(BasicType::String),
+    Ok(ml_bundle::BasicType::Byte) => Some(BasicType::Byte),
+    Ok(ml_bundle::BasicType::Short) => Some(BasicType::Short),
+    Ok(ml_bundle::BasicType::Int) => Some(BasicType::Int),
+    Ok(ml_bundle::BasicType::Long) => Some(BasicType::Long),
+    Ok(ml_bundle::BasicType::Float) => Some(BasicType::Float),
+    Ok(ml_bundle::BasicType::Double) => Some(BasicType::Double),
+    Ok(ml_bundle::BasicType::ByteString) => Some(BasicType::ByteString),
+    _ => None
+  }
+}
+
+/// The type of the cells of `field`, as model attributes carry it.
+fn cell_type(field: &StructField) -> ml_bundle::DataType {
+  let shape = match *field.data_type().shape() {
+    DataShape::Scalar => ml_bundle::DataShapeType::Scalar,
+    DataShape::List => ml_bundle::DataShapeType::List,
+    DataShape::Tensor(_) => ml_bundle::DataShapeType::Tensor
+  };
+
+  ml_bundle::DataType {
+    base: base_to_proto(field.data_type().base()) as i32,
+    shape: shape as i32
+  }
+}
+
+/// `None` for null values.
+fn value_to_attribute(value: Value) -> Option<dsl::Attribute> {
+  let attr = match value {
+    Value::Null => return None,
+
+    Value::Bool(v) => dsl::Attribute::Basic(dsl::BasicValue::Bool(v)),
+    Value::String(v) => dsl::Attribute::Basic(dsl::BasicValue::String(v)),
+    Value::Byte(v) => dsl::Attribute::Basic(dsl::BasicValue::Byte(v)),
+    Value::Short(v) => dsl::Attribute::Basic(dsl::BasicValue::Short(v)),
+    Value::Int(v) => dsl::Attribute::Basic(dsl::BasicValue::Int(v)),
+    Value::Long(v) => dsl::Attribute::Basic(dsl::BasicValue::Long(v)),
+    Value::Float(v) => dsl::Attribute::Basic(dsl::BasicValue::Float(v)),
+    Value::Double(v) => dsl::Attribute::Basic(dsl::BasicValue::Double(v)),
+    Value::ByteString(v) => dsl::Attribute::Basic(dsl::BasicValue::ByteString(v)),
+
+    Value::BoolVector(v) => dsl::Attribute::Array(dsl::VectorValue::Bool(v)),
+    Value::StringVector(v) => dsl::Attribute::Array(dsl::VectorValue::String(v)),
+    Value::ByteVector(v) => dsl::Attribute::Array(dsl::VectorValue::Byte(v)),
+    Value::ShortVector(v) => dsl::Attribute::Array(dsl::VectorValue::Short(v)),
+    Value::IntVector(v) => dsl::Attribute::Array(dsl::VectorValue::Int(v)),
+    Value::LongVector(v) => dsl::Attribute::Array(dsl::VectorValue::Long(v)),
+    Value::FloatVector(v) => dsl::Attribute::Array(dsl::VectorValue::Float(v)),
+    Value::DoubleVector(v) => dsl::Attribute::Array(dsl::VectorValue::Double(v)),
+    Value::ByteStringVector(v) => dsl::Attribute::Array(dsl::VectorValue::ByteString(v)),
+
+    Value::BoolTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Bool(t)),
+    Value::StringTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::String(t)),
+    Value::ByteTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Byte(t)),
+    Value::ShortTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Short(t)),
+    Value::IntTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Int(t)),
+    Value::LongTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Long(t)),
+    Value::FloatTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Float(t)),
+    Value::DoubleTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Double(t)),
+    Value::ByteStringTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::ByteString(t)),
+
+    Value::SparseBoolTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Bool(t.to_dense())),
+    Value::SparseStringTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::String(t.to_dense())),
+    Value::SparseByteTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Byte(t.to_dense())),
+    Value::SparseShortTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Short(t.to_dense())),
+    Value::SparseIntTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Int(t.to_dense())),
+    Value::SparseLongTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Long(t.to_dense())),
+    Value::SparseFloatTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Float(t.to_dense())),
+    Value::SparseDoubleTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::Double(t.to_dense())),
+    Value::SparseByteStringTensor(t) => dsl::Attribute::Tensor(dsl::TensorValue::ByteString(t.to_dense()))
+  };
+
+  Some(attr)
+}
+
+fn attribute_to_value(attr: dsl::Attribute) -> Value {
+  match attr {
+    dsl::Attribute::Basic(dsl::BasicValue::Bool(v)) => Value::Bool(v),
+    dsl::Attribute::Basic(dsl::BasicValue::String(v)) => Value::String(v),
+    dsl::Attribute::Basic(dsl::BasicValue::Byte(v)) => Value::Byte(v),
+    dsl::Attribute::Basic(dsl::BasicValue::Short(v)) => Value::Short(v),
+    dsl::Attribute::Basic(dsl::BasicValue::Int(v)) => Value::Int(v),
+    dsl::Attribute::Basic(dsl::BasicValue::Long(v)) => Value::Long(v),
+    dsl::Attribute::Basic(dsl::BasicValue::Float(v)) => Value::Float(v),
+    dsl::Attribute::Basic(dsl::BasicValue::Double(v)) => Value::Double(v),
+    dsl::Attribute::Basic(dsl::BasicValue::ByteString(v)) => Value::ByteString(v),
+
+    dsl::Attribute::Array(dsl::VectorValue::Bool(v)) => Value::BoolVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::String(v)) => Value::StringVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::Byte(v)) => Value::ByteVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::Short(v)) => Value::ShortVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::Int(v)) => Value::IntVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::Long(v)) => Value::LongVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::Float(v)) => Value::FloatVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::Double(v)) => Value::DoubleVector(v),
+    dsl::Attribute::Array(dsl::VectorValue::ByteString(v)) => Value::ByteStringVector(v),
+
+    dsl::Attribute::Tensor(dsl::TensorValue::Bool(t)) => Value::BoolTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::String(t)) => Value::StringTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::Byte(t)) => Value::ByteTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::Short(t)) => Value::ShortTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::Int(t)) => Value::IntTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::Long(t)) => Value::LongTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::Float(t)) => Value::FloatTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::Double(t)) => Value::DoubleTensor(t),
+    dsl::Attribute::Tensor(dsl::TensorValue::ByteString(t)) => Value::ByteStringTensor(t)
+  }
+}
+
+impl<'a> From<&'a StructField> for runtime::StructField {
+  fn from(value: &'a StructField) -> Self {
+    let dt = cell_type(value);
+    let tensor_shape = match *value.data_type().shape() {
+      DataShape::Tensor(Some(ref dims)) => Some(runtime::TensorShape { dimensions: dims.iter().map(|d| *d as i32).collect() }),
+      _ => None
+    };
+
+    runtime::StructField {
+      name: value.name().to_string(),
+      data_type: Some(runtime::DataType {
+        base: dt.base,
+        shape: dt.shape,
+        is_nullable: value.nullable(),
+        tensor_shape: tensor_shape
+      })
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a runtime::StructField> for StructField {
+  type Err = Error;
+
+  fn try_from(value: &'a runtime::StructField) -> Result<Self, Self::Err> {
+    let dt = match value.data_type {
+      Some(ref dt) => dt,
+      None => return Err(Error::ReadError(format!("Missing type for field {}", value.name)))
+    };
+    if let Some(d) = dt.tensor_shape.iter().flat_map(|s| s.dimensions.iter()).find(|d| **d < 0) {
+      return Err(Error::ReadError(format!("Invalid tensor dimension {} for field {}", d, value.name)));
+    }
+
+    let shape = match ::std::convert::TryFrom::try_from(dt.shape) {
+      Ok(ml_bundle::DataShapeType::Scalar) => Some(DataShape::Scalar),
+      Ok(ml_bundle::DataShapeType::List) => Some(DataShape::List),
+      Ok(ml_bundle::DataShapeType::Tensor) => {
+        Some(DataShape::Tensor(dt.tensor_shape.as_ref().map(|s| s.dimensions.iter().map(|d| *d as usize).collect())))
+      },
+      _ => None
+    };
+
+    base_from_proto(dt.base).and_then(|base| shape.map(|shape| {
+      StructField::new(value.name.clone(), DataType::new(base, shape), dt.is_nullable)
+    })).map(Ok).unwrap_or_else(|| Err(Error::ReadError(format!("Invalid type for field {}", value.name))))
+  }
+}
+
+impl<'a> From<&'a Schema> for runtime::Schema {
+  fn from(value: &'a Schema) -> Self {
+    runtime::Schema {
+      fields: value.fields().iter().map(runtime::StructField::from).collect()
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a runtime::Schema> for Schema {
+  type Err = Error;
+
+  fn try_from(value: &'a runtime::Schema) -> Result<Self, Self::Err> {
+    let mut fields = Vec::with_capacity(value.fields.len());
+
+    for field in value.fields.iter() {
+      match StructField::try_from(field) {
+        Ok(field) => fields.push(field),
+        Err(err) => return Err(err)
+      }
+    }
+
+    Ok(Schema::new(fields))
+  }
+}
+
+impl<'a> From<&'a LeapFrame> for runtime::LeapFrame {
+  fn from(value: &'a LeapFrame) -> Self {
+    let rows = value.rows().map(|row| {
+      let values = row.into_values().into_iter().map(|v| {
+        match value_to_attribute(v) {
+          Some(attr) => proto::attribute_value(&attr).1,
+          None => ml_bundle::Value { v: None }
+        }
+      }).collect();
+
+      runtime::Row { values: values }
+    }).collect();
+
+    runtime::LeapFrame {
+      schema: Some(runtime::Schema::from(&value.schema())),
+      rows: rows
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a runtime::LeapFrame> for LeapFrame {
+  type Err = Error;
+
+  fn try_from(value: &'a runtime::LeapFrame) -> Result<Self, Self::Err> {
+    let schema = match value.schema.as_ref().map(Schema::try_from) {
+      Some(Ok(schema)) => schema,
+      Some(Err(err)) => return Err(err),
+      None => return Err(Error::ReadError(String::from("Missing frame schema")))
+    };
+    let types: Vec<ml_bundle::DataType> = schema.fields().iter().map(cell_type).collect();
+    let mut rows = Vec::with_capacity(value.rows.len());
+
+    for row in value.rows.iter() {
+      if row.values.len() != types.len() {
+        return Err(Error::ReadError(format!("Expected {} values per row", types.len())));
+      }
+
+      let mut values = Vec::with_capacity(types.len());
+      for (dt, cell) in types.iter().zip(row.values.iter()) {
+        if cell.v.is_none() {
+          values.push(Value::Null);
+          continue;
+        }
+
+        match proto::try_attribute_from_value(dt, Some(cell)) {
+          Ok(attr) => values.push(attribute_to_value(attr)),
+          Err(err) => return Err(err)
+        }
+      }
+
+      rows.push(Row::new(values));
+    }
+
+    LeapFrame::from_rows(&schema, rows).map_err(|err| Error::ReadError(format!("{:?}", err)))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use prost::Message;
+  use bundle::dsl::{DenseTensor, SparseTensor};
+  use super::super::{Col, ColData};
+
+  fn round_trip(frame: &LeapFrame) -> LeapFrame {
+    let buf = runtime::LeapFrame::from(frame).encode_to_vec();
+    LeapFrame::try_from(&runtime::LeapFrame::decode(buf.as_slice()).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut frame = LeapFrame::with_size(2);
+    frame.try_with_col(Col::from_doubles(String::from("a"), vec![1.5, 0.0]).with_validity(vec![true, false])).unwrap().
+      try_with_ints(String::from("b"), vec![1, -2]).unwrap().
+      try_with_strings(String::from("c"), vec![String::from("x"), String::new()]).unwrap().
+      try_with_double_tensors(String::from("d"), vec![DenseTensor::new(vec![2], vec![1.0, 2.0]),
+                                                      DenseTensor::new(vec![2], vec![0.0, -1.0])]).unwrap().
+      try_with_col(Col::new(String::from("e"), ColData::ByteString(vec![vec![1, 2], vec![]]))).unwrap();
+
+    let read = round_trip(&frame);
+    assert_eq!(read.schema(), frame.schema());
+    assert_eq!(read.rows().collect::<Vec<Row>>(), frame.rows().collect::<Vec<Row>>());
+    assert_eq!(read.try_col("a").unwrap().validity(), Some(&[true, false][..]));
+  }
+
+  #[test]
+  fn test_sparse_tensors_are_sent_dense() {
+    let mut frame = LeapFrame::with_size(1);
+    frame.try_with_sparse_double_tensors(String::from("a"), vec![SparseTensor::new(vec![3], vec![1], vec![2.0])]).unwrap();
+
+    let read = round_trip(&frame);
+    assert_eq!(read.get_double_tensors("a").unwrap(), &[DenseTensor::new(vec![3], vec![0.0, 2.0, 0.0])]);
+  }
+
+  #[test]
+  fn test_schema_round_trip() {
+    let schema = Schema::new(vec![StructField::new(String::from("a"), DataType::tensor(BasicType::Float, None), true),
+                                  StructField::new(String::from("b"), DataType::tensor(BasicType::Double, Some(vec![2, 3])), false),
+                                  StructField::new(String::from("c"), DataType::new(BasicType::Long, DataShape::List), false)]);
+    let buf = runtime::Schema::from(&schema).encode_to_vec();
+
+    assert_eq!(Schema::try_from(&runtime::Schema::decode(buf.as_slice()).unwrap()).unwrap(), schema);
+  }
+
+  #[test]
+  fn test_negative_tensor_dimensions() {
+    let mut field = runtime::StructField::from(&StructField::new(String::from("a"), DataType::tensor(BasicType::Double, Some(vec![2])), true));
+    field.data_type.as_mut().unwrap().tensor_shape = Some(runtime::TensorShape { dimensions: vec![2, -1] });
+    let pb = runtime::LeapFrame {
+      schema: Some(runtime::Schema { fields: vec![field] }),
+      rows: vec![runtime::Row { values: vec![ml_bundle::Value { v: None }] }]
+    };
+
+    match LeapFrame::try_from(&pb) {
+      Err(Error::ReadError(msg)) => assert!(msg.contains("-1")),
+      r => panic!("expected a read error, got {:?}", r.map(|f| f.size()))
+    }
+  }
+
+  #[test]
+  fn test_null_in_required_field() {
+    let pb = runtime::LeapFrame {
+      schema: Some(runtime::Schema::from(&Schema::new(vec![StructField::required("a", DataType::scalar(BasicType::Double))]))),
+      rows: vec![runtime::Row { values: vec![ml_bundle::Value { v: None }] }]
+    };
+
+    assert!(LeapFrame::try_from(&pb).is_err());
+  }
+}
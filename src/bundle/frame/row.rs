@@ -0,0 +1,596 @@
+use bundle::dsl::{DenseTensor, SparseTensor};
+use super::{BasicType, Col, ColData, DataShape, DataType, Error, LeapFrame, Result, Schema};
+
+/// A single cell of a LeapFrame, with one variant per column type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Null,
+
+  Bool(bool),
+  String(String),
+  Byte(i8),
+  Short(i16),
+  Int(i32),
+  Long(i64),
+  Float(f32),
+  Double(f64),
+  ByteString(Vec<u8>),
+
+  BoolVector(Vec<bool>),
+  StringVector(Vec<String>),
+  ByteVector(Vec<i8>),
+  ShortVector(Vec<i16>),
+  IntVector(Vec<i32>),
+  LongVector(Vec<i64>),
+  FloatVector(Vec<f32>),
+  DoubleVector(Vec<f64>),
+  ByteStringVector(Vec<Vec<u8>>),
+
+  BoolTensor(DenseTensor<bool>),
+  StringTensor(DenseTensor<String>),
+  ByteTensor(DenseTensor<i8>),
+  ShortTensor(DenseTensor<i16>),
+  IntTensor(DenseTensor<i32>),
+  LongTensor(DenseTensor<i64>),
+  FloatTensor(DenseTensor<f32>),
+  DoubleTensor(DenseTensor<f64>),
+  ByteStringTensor(DenseTensor<Vec<u8>>),
+
+  SparseBoolTensor(SparseTensor<bool>),
+  SparseStringTensor(SparseTensor<String>),
+  SparseByteTensor(SparseTensor<i8>),
+  SparseShortTensor(SparseTensor<i16>),
+  SparseIntTensor(SparseTensor<i32>),
+  SparseLongTensor(SparseTensor<i64>),
+  SparseFloatTensor(SparseTensor<f32>),
+  SparseDoubleTensor(SparseTensor<f64>),
+  SparseByteStringTensor(SparseTensor<Vec<u8>>)
+}
+
+/// The values of one row, in the order of the frame's columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+  values: Vec<Value>
+}
+
+pub struct Rows<'a> {
+  frame: &'a LeapFrame,
+  row: usize
+}
+
+impl Value {
+  pub fn is_null(&self) -> bool {
+    match *self {
+      Value::Null => true,
+      _ => false
+    }
+  }
+
+  pub fn is_sparse(&self) -> bool {
+    match *self {
+      Value::SparseBoolTensor(_) | Value::SparseStringTensor(_) | Value::SparseByteTensor(_) |
+      Value::SparseShortTensor(_) | Value::SparseIntTensor(_) | Value::SparseLongTensor(_) |
+      Value::SparseFloatTensor(_) | Value::SparseDoubleTensor(_) | Value::SparseByteStringTensor(_) => true,
+      _ => false
+    }
+  }
+
+  /// `None` for null values.
+  pub fn data_type(&self) -> Option<DataType> {
+    let dt = match *self {
+      Value::Null => return None,
+      Value::Bool(_) => DataType::new(BasicType::Bool, DataShape::Scalar),
+      Value::String(_) => DataType::new(BasicType::String, DataShape::Scalar),
+      Value::Byte(_) => DataType::new(BasicType::Byte, DataShape::Scalar),
+      Value::Short(_) => DataType::new(BasicType::Short, DataShape::Scalar),
+      Value::Int(_) => DataType::new(BasicType::Int, DataShape::Scalar),
+      Value::Long(_) => DataType::new(BasicType::Long, DataShape::Scalar),
+      Value::Float(_) => DataType::new(BasicType::Float, DataShape::Scalar),
+      Value::Double(_) => DataType::new(BasicType::Double, DataShape::Scalar),
+      Value::ByteString(_) => DataType::new(BasicType::ByteString, DataShape::Scalar),
+      Value::BoolVector(_) => DataType::new(BasicType::Bool, DataShape::List),
+      Value::StringVector(_) => DataType::new(BasicType::String, DataShape::List),
+      Value::ByteVector(_) => DataType::new(BasicType::Byte, DataShape::List),
+      Value::ShortVector(_) => DataType::new(BasicType::Short, DataShape::List),
+      Value::IntVector(_) => DataType::new(BasicType::Int, DataShape::List),
+      Value::LongVector(_) => DataType::new(BasicType::Long, DataShape::List),
+      Value::FloatVector(_) => DataType::new(BasicType::Float, DataShape::List),
+      Value::DoubleVector(_) => DataType::new(BasicType::Double, DataShape::List),
+      Value::ByteStringVector(_) => DataType::new(BasicType::ByteString, DataShape::List),
+      Value::BoolTensor(ref t) => DataType::tensor(BasicType::Bool, Some(t.dimensions().to_vec())),
+      Value::StringTensor(ref t) => DataType::tensor(BasicType::String, Some(t.dimensions().to_vec())),
+      Value::ByteTensor(ref t) => DataType::tensor(BasicType::Byte, Some(t.dimensions().to_vec())),
+      Value::ShortTensor(ref t) => DataType::tensor(BasicType::Short, Some(t.dimensions().to_vec())),
+      Value::IntTensor(ref t) => DataType::tensor(BasicType::Int, Some(t.dimensions().to_vec())),
+      Value::LongTensor(ref t) => DataType::tensor(BasicType::Long, Some(t.dimensions().to_vec())),
+      Value::FloatTensor(ref t) => DataType::tensor(BasicType::Float, Some(t.dimensions().to_vec())),
+      Value::DoubleTensor(ref t) => DataType::tensor(BasicType::Double, Some(t.dimensions().to_vec())),
+      Value::ByteStringTensor(ref t) => DataType::tensor(BasicType::ByteString, Some(t.dimensions().to_vec())),
+      Value::SparseBoolTensor(ref t) => DataType::tensor(BasicType::Bool, Some(t.dimensions().to_vec())),
+      Value::SparseStringTensor(ref t) => DataType::tensor(BasicType::String, Some(t.dimensions().to_vec())),
+      Value::SparseByteTensor(ref t) => DataType::tensor(BasicType::Byte, Some(t.dimensions().to_vec())),
+      Value::SparseShortTensor(ref t) => DataType::tensor(BasicType::Short, Some(t.dimensions().to_vec())),
+      Value::SparseIntTensor(ref t) => DataType::tensor(BasicType::Int, Some(t.dimensions().to_vec())),
+      Value::SparseLongTensor(ref t) => DataType::tensor(BasicType::Long, Some(t.dimensions().to_vec())),
+      Value::SparseFloatTensor(ref t) => DataType::tensor(BasicType::Float, Some(t.dimensions().to_vec())),
+      Value::SparseDoubleTensor(ref t) => DataType::tensor(BasicType::Double, Some(t.dimensions().to_vec())),
+      Value::SparseByteStringTensor(ref t) => DataType::tensor(BasicType::ByteString, Some(t.dimensions().to_vec()))
+    };
+
+    Some(dt)
+  }
+
+  /// Numbers as doubles of the same shape, other values are unchanged.
+  pub fn to_double(self) -> Value {
+    match self {
+      Value::Byte(v) => Value::Double(f64::from(v)),
+      Value::Short(v) => Value::Double(f64::from(v)),
+      Value::Int(v) => Value::Double(f64::from(v)),
+      Value::Long(v) => Value::Double(v as f64),
+      Value::Float(v) => Value::Double(f64::from(v)),
+      Value::ByteVector(v) => Value::DoubleVector(doubles(&v, f64::from)),
+      Value::ShortVector(v) => Value::DoubleVector(doubles(&v, f64::from)),
+      Value::IntVector(v) => Value::DoubleVector(doubles(&v, f64::from)),
+      Value::LongVector(v) => Value::DoubleVector(doubles(&v, |x| x as f64)),
+      Value::FloatVector(v) => Value::DoubleVector(doubles(&v, f64::from)),
+      Value::ByteTensor(t) => Value::DoubleTensor(DenseTensor::new(t.dimensions().to_vec(), doubles(t.values(), f64::from))),
+      Value::ShortTensor(t) => Value::DoubleTensor(DenseTensor::new(t.dimensions().to_vec(), doubles(t.values(), f64::from))),
+      Value::IntTensor(t) => Value::DoubleTensor(DenseTensor::new(t.dimensions().to_vec(), doubles(t.values(), f64::from))),
+      Value::LongTensor(t) => Value::DoubleTensor(DenseTensor::new(t.dimensions().to_vec(), doubles(t.values(), |x| x as f64))),
+      Value::FloatTensor(t) => Value::DoubleTensor(DenseTensor::new(t.dimensions().to_vec(), doubles(t.values(), f64::from))),
+      Value::SparseByteTensor(t) => Value::SparseDoubleTensor(SparseTensor::new(t.dimensions().to_vec(), t.indices().to_vec(), doubles(t.values(), f64::from))),
+      Value::SparseShortTensor(t) => Value::SparseDoubleTensor(SparseTensor::new(t.dimensions().to_vec(), t.indices().to_vec(), doubles(t.values(), f64::from))),
+      Value::SparseIntTensor(t) => Value::SparseDoubleTensor(SparseTensor::new(t.dimensions().to_vec(), t.indices().to_vec(), doubles(t.values(), f64::from))),
+      Value::SparseLongTensor(t) => Value::SparseDoubleTensor(SparseTensor::new(t.dimensions().to_vec(), t.indices().to_vec(), doubles(t.values(), |x| x as f64))),
+      Value::SparseFloatTensor(t) => Value::SparseDoubleTensor(SparseTensor::new(t.dimensions().to_vec(), t.indices().to_vec(), doubles(t.values(), f64::from))),
+      v => v
+    }
+  }
+
+  pub fn get_double(&self) -> Option<f64> {
+    match *self {
+      Value::Double(v) => Some(v),
+      _ => None
+    }
+  }
+
+  pub fn get_int(&self) -> Option<i32> {
+    match *self {
+      Value::Int(v) => Some(v),
+      _ => None
+    }
+  }
+
+  pub fn get_string(&self) -> Option<&str> {
+    match *self {
+      Value::String(ref v) => Some(v),
+      _ => None
+    }
+  }
+
+  pub fn get_double_tensor(&self) -> Option<&DenseTensor<f64>> {
+    match *self {
+      Value::DoubleTensor(ref v) => Some(v),
+      _ => None
+    }
+  }
+
+  pub fn get_sparse_double_tensor(&self) -> Option<&SparseTensor<f64>> {
+    match *self {
+      Value::SparseDoubleTensor(ref v) => Some(v),
+      _ => None
+    }
+  }
+}
+
+impl From<bool> for Value { fn from(v: bool) -> Value { Value::Bool(v) } }
+impl From<String> for Value { fn from(v: String) -> Value { Value::String(v) } }
+impl<'a> From<&'a str> for Value { fn from(v: &'a str) -> Value { Value::String(v.to_string()) } }
+impl From<i32> for Value { fn from(v: i32) -> Value { Value::Int(v) } }
+impl From<i64> for Value { fn from(v: i64) -> Value { Value::Long(v) } }
+impl From<f64> for Value { fn from(v: f64) -> Value { Value::Double(v) } }
+impl From<DenseTensor<f64>> for Value { fn from(v: DenseTensor<f64>) -> Value { Value::DoubleTensor(v) } }
+impl From<SparseTensor<f64>> for Value { fn from(v: SparseTensor<f64>) -> Value { Value::SparseDoubleTensor(v) } }
+impl<T: Into<Value>> From<Option<T>> for Value {
+  fn from(v: Option<T>) -> Value { v.map(|x| x.into()).unwrap_or(Value::Null) }
+}
+
+impl Row {
+  pub fn new(values: Vec<Value>) -> Row {
+    Row {
+      values: values
+    }
+  }
+
+  pub fn len(&self) -> usize { self.values.len() }
+  pub fn is_empty(&self) -> bool { self.values.is_empty() }
+  pub fn values(&self) -> &[Value] { &self.values }
+  pub fn get(&self, i: usize) -> Option<&Value> { self.values.get(i) }
+  pub fn into_values(self) -> Vec<Value> { self.values }
+}
+
+impl<'a> Iterator for Rows<'a> {
+  type Item = Row;
+
+  fn next(&mut self) -> Option<Row> {
+    let row = self.frame.get_row(self.row);
+    self.row += 1;
+    row
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let n = self.frame.size().saturating_sub(self.row);
+    (n, Some(n))
+  }
+}
+
+fn doubles<T: Copy, F>(v: &[T], f: F) -> Vec<f64>
+  where F: Fn(T) -> f64 {
+    v.iter().map(|x| f(*x)).collect()
+  }
+
+// nulls hold a tensor without values, which is never densified, so the
+// dimensions of a field cost nothing until a row has values
+fn null_tensor<T>(dims: &[usize]) -> DenseTensor<T> {
+  DenseTensor::new(dims.to_vec(), vec![])
+}
+
+fn null_sparse_tensor<T>(dims: &[usize]) -> SparseTensor<T> {
+  SparseTensor::new(dims.to_vec(), vec![], vec![])
+}
+
+impl ColData {
+  /// An empty column of `data_type`, tensors are sparse if `sparse` is set.
+  pub fn with_capacity(data_type: &DataType, sparse: bool, capacity: usize) -> ColData {
+    let n = capacity;
+
+    match (data_type.shape(), sparse) {
+      (&DataShape::Scalar, _) => {
+        match data_type.base() {
+          BasicType::Bool => ColData::Bool(Vec::with_capacity(n)),
+          BasicType::String => ColData::String(Vec::with_capacity(n)),
+          BasicType::Byte => ColData::Byte(Vec::with_capacity(n)),
+          BasicType::Short => ColData::Short(Vec::with_capacity(n)),
+          BasicType::Int => ColData::Int(Vec::with_capacity(n)),
+          BasicType::Long => ColData::Long(Vec::with_capacity(n)),
+          BasicType::Float => ColData::Float(Vec::with_capacity(n)),
+          BasicType::Double => ColData::Double(Vec::with_capacity(n)),
+          BasicType::ByteString => ColData::ByteString(Vec::with_capacity(n))
+        }
+      },
+      (&DataShape::List, _) => {
+        match data_type.base() {
+          BasicType::Bool => ColData::BoolVector(Vec::with_capacity(n)),
+          BasicType::String => ColData::StringVector(Vec::with_capacity(n)),
+          BasicType::Byte => ColData::ByteVector(Vec::with_capacity(n)),
+          BasicType::Short => ColData::ShortVector(Vec::with_capacity(n)),
+          BasicType::Int => ColData::IntVector(Vec::with_capacity(n)),
+          BasicType::Long => ColData::LongVector(Vec::with_capacity(n)),
+          BasicType::Float => ColData::FloatVector(Vec::with_capacity(n)),
+          BasicType::Double => ColData::DoubleVector(Vec::with_capacity(n)),
+          BasicType::ByteString => ColData::ByteStringVector(Vec::with_capacity(n))
+        }
+      },
+      (&DataShape::Tensor(_), false) => {
+        match data_type.base() {
+          BasicType::Bool => ColData::BoolTensor(Vec::with_capacity(n)),
+          BasicType::String => ColData::StringTensor(Vec::with_capacity(n)),
+          BasicType::Byte => ColData::ByteTensor(Vec::with_capacity(n)),
+          BasicType::Short => ColData::ShortTensor(Vec::with_capacity(n)),
+          BasicType::Int => ColData::IntTensor(Vec::with_capacity(n)),
+          BasicType::Long => ColData::LongTensor(Vec::with_capacity(n)),
+          BasicType::Float => ColData::FloatTensor(Vec::with_capacity(n)),
+          BasicType::Double => ColData::DoubleTensor(Vec::with_capacity(n)),
+          BasicType::ByteString => ColData::ByteStringTensor(Vec::with_capacity(n))
+        }
+      },
+      (&DataShape::Tensor(_), true) => {
+        match data_type.base() {
+          BasicType::Bool => ColData::SparseBoolTensor(Vec::with_capacity(n)),
+          BasicType::String => ColData::SparseStringTensor(Vec::with_capacity(n)),
+          BasicType::Byte => ColData::SparseByteTensor(Vec::with_capacity(n)),
+          BasicType::Short => ColData::SparseShortTensor(Vec::with_capacity(n)),
+          BasicType::Int => ColData::SparseIntTensor(Vec::with_capacity(n)),
+          BasicType::Long => ColData::SparseLongTensor(Vec::with_capacity(n)),
+          BasicType::Float => ColData::SparseFloatTensor(Vec::with_capacity(n)),
+          BasicType::Double => ColData::SparseDoubleTensor(Vec::with_capacity(n)),
+          BasicType::ByteString => ColData::SparseByteStringTensor(Vec::with_capacity(n))
+        }
+      }
+    }
+  }
+
+  pub fn value(&self, row: usize) -> Value {
+    match *self {
+      ColData::Bool(ref v) => Value::Bool(v[row]),
+      ColData::String(ref v) => Value::String(v[row].clone()),
+      ColData::Byte(ref v) => Value::Byte(v[row]),
+      ColData::Short(ref v) => Value::Short(v[row]),
+      ColData::Int(ref v) => Value::Int(v[row]),
+      ColData::Long(ref v) => Value::Long(v[row]),
+      ColData::Float(ref v) => Value::Float(v[row]),
+      ColData::Double(ref v) => Value::Double(v[row]),
+      ColData::ByteString(ref v) => Value::ByteString(v[row].clone()),
+      ColData::BoolVector(ref v) => Value::BoolVector(v[row].clone()),
+      ColData::StringVector(ref v) => Value::StringVector(v[row].clone()),
+      ColData::ByteVector(ref v) => Value::ByteVector(v[row].clone()),
+      ColData::ShortVector(ref v) => Value::ShortVector(v[row].clone()),
+      ColData::IntVector(ref v) => Value::IntVector(v[row].clone()),
+      ColData::LongVector(ref v) => Value::LongVector(v[row].clone()),
+      ColData::FloatVector(ref v) => Value::FloatVector(v[row].clone()),
+      ColData::DoubleVector(ref v) => Value::DoubleVector(v[row].clone()),
+      ColData::ByteStringVector(ref v) => Value::ByteStringVector(v[row].clone()),
+      ColData::BoolTensor(ref v) => Value::BoolTensor(v[row].clone()),
+      ColData::StringTensor(ref v) => Value::StringTensor(v[row].clone()),
+      ColData::ByteTensor(ref v) => Value::ByteTensor(v[row].clone()),
+      ColData::ShortTensor(ref v) => Value::ShortTensor(v[row].clone()),
+      ColData::IntTensor(ref v) => Value::IntTensor(v[row].clone()),
+      ColData::LongTensor(ref v) => Value::LongTensor(v[row].clone()),
+      ColData::FloatTensor(ref v) => Value::FloatTensor(v[row].clone()),
+      ColData::DoubleTensor(ref v) => Value::DoubleTensor(v[row].clone()),
+      ColData::ByteStringTensor(ref v) => Value::ByteStringTensor(v[row].clone()),
+      ColData::SparseBoolTensor(ref v) => Value::SparseBoolTensor(v[row].clone()),
+      ColData::SparseStringTensor(ref v) => Value::SparseStringTensor(v[row].clone()),
+      ColData::SparseByteTensor(ref v) => Value::SparseByteTensor(v[row].clone()),
+      ColData::SparseShortTensor(ref v) => Value::SparseShortTensor(v[row].clone()),
+      ColData::SparseIntTensor(ref v) => Value::SparseIntTensor(v[row].clone()),
+      ColData::SparseLongTensor(ref v) => Value::SparseLongTensor(v[row].clone()),
+      ColData::SparseFloatTensor(ref v) => Value::SparseFloatTensor(v[row].clone()),
+      ColData::SparseDoubleTensor(ref v) => Value::SparseDoubleTensor(v[row].clone()),
+      ColData::SparseByteStringTensor(ref v) => Value::SparseByteStringTensor(v[row].clone())
+    }
+  }
+
+  /// Appends `value`, which must have this column's type. Nulls append a
+  /// placeholder, with `dims` for tensors.
+  pub fn try_push(&mut self, value: Value, dims: &[usize]) -> Result<()> {
+    match (self, value) {
+      (&mut ColData::Bool(ref mut v), Value::Null) => v.push(bool::default()),
+      (&mut ColData::String(ref mut v), Value::Null) => v.push(String::new()),
+      (&mut ColData::Byte(ref mut v), Value::Null) => v.push(i8::default()),
+      (&mut ColData::Short(ref mut v), Value::Null) => v.push(i16::default()),
+      (&mut ColData::Int(ref mut v), Value::Null) => v.push(i32::default()),
+      (&mut ColData::Long(ref mut v), Value::Null) => v.push(i64::default()),
+      (&mut ColData::Float(ref mut v), Value::Null) => v.push(f32::default()),
+      (&mut ColData::Double(ref mut v), Value::Null) => v.push(f64::default()),
+      (&mut ColData::ByteString(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::BoolVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::StringVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::ByteVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::ShortVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::IntVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::LongVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::FloatVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::DoubleVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::ByteStringVector(ref mut v), Value::Null) => v.push(Vec::new()),
+      (&mut ColData::BoolTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::StringTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::ByteTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::ShortTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::IntTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::LongTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::FloatTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::DoubleTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::ByteStringTensor(ref mut v), Value::Null) => v.push(null_tensor(dims)),
+      (&mut ColData::SparseBoolTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseStringTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseByteTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseShortTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseIntTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseLongTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseFloatTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseDoubleTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+      (&mut ColData::SparseByteStringTensor(ref mut v), Value::Null) => v.push(null_sparse_tensor(dims)),
+
+      (&mut ColData::Bool(ref mut v), Value::Bool(x)) => v.push(x),
+      (&mut ColData::String(ref mut v), Value::String(x)) => v.push(x),
+      (&mut ColData::Byte(ref mut v), Value::Byte(x)) => v.push(x),
+      (&mut ColData::Short(ref mut v), Value::Short(x)) => v.push(x),
+      (&mut ColData::Int(ref mut v), Value::Int(x)) => v.push(x),
+      (&mut ColData::Long(ref mut v), Value::Long(x)) => v.push(x),
+      (&mut ColData::Float(ref mut v), Value::Float(x)) => v.push(x),
+      (&mut ColData::Double(ref mut v), Value::Double(x)) => v.push(x),
+      (&mut ColData::ByteString(ref mut v), Value::ByteString(x)) => v.push(x),
+      (&mut ColData::BoolVector(ref mut v), Value::BoolVector(x)) => v.push(x),
+      (&mut ColData::StringVector(ref mut v), Value::StringVector(x)) => v.push(x),
+      (&mut ColData::ByteVector(ref mut v), Value::ByteVector(x)) => v.push(x),
+      (&mut ColData::ShortVector(ref mut v), Value::ShortVector(x)) => v.push(x),
+      (&mut ColData::IntVector(ref mut v), Value::IntVector(x)) => v.push(x),
+      (&mut ColData::LongVector(ref mut v), Value::LongVector(x)) => v.push(x),
+      (&mut ColData::FloatVector(ref mut v), Value::FloatVector(x)) => v.push(x),
+      (&mut ColData::DoubleVector(ref mut v), Value::DoubleVector(x)) => v.push(x),
+      (&mut ColData::ByteStringVector(ref mut v), Value::ByteStringVector(x)) => v.push(x),
+      (&mut ColData::BoolTensor(ref mut v), Value::BoolTensor(x)) => v.push(x),
+      (&mut ColData::StringTensor(ref mut v), Value::StringTensor(x)) => v.push(x),
+      (&mut ColData::ByteTensor(ref mut v), Value::ByteTensor(x)) => v.push(x),
+      (&mut ColData::ShortTensor(ref mut v), Value::ShortTensor(x)) => v.push(x),
+      (&mut ColData::IntTensor(ref mut v), Value::IntTensor(x)) => v.push(x),
+      (&mut ColData::LongTensor(ref mut v), Value::LongTensor(x)) => v.push(x),
+      (&mut ColData::FloatTensor(ref mut v), Value::FloatTensor(x)) => v.push(x),
+      (&mut ColData::DoubleTensor(ref mut v), Value::DoubleTensor(x)) => v.push(x),
+      (&mut ColData::ByteStringTensor(ref mut v), Value::ByteStringTensor(x)) => v.push(x),
+      (&mut ColData::SparseBoolTensor(ref mut v), Value::SparseBoolTensor(x)) => v.push(x),
+      (&mut ColData::SparseStringTensor(ref mut v), Value::SparseStringTensor(x)) => v.push(x),
+      (&mut ColData::SparseByteTensor(ref mut v), Value::SparseByteTensor(x)) => v.push(x),
+      (&mut ColData::SparseShortTensor(ref mut v), Value::SparseShortTensor(x)) => v.push(x),
+      (&mut ColData::SparseIntTensor(ref mut v), Value::SparseIntTensor(x)) => v.push(x),
+      (&mut ColData::SparseLongTensor(ref mut v), Value::SparseLongTensor(x)) => v.push(x),
+      (&mut ColData::SparseFloatTensor(ref mut v), Value::SparseFloatTensor(x)) => v.push(x),
+      (&mut ColData::SparseDoubleTensor(ref mut v), Value::SparseDoubleTensor(x)) => v.push(x),
+      (&mut ColData::SparseByteStringTensor(ref mut v), Value::SparseByteStringTensor(x)) => v.push(x),
+
+      (data, value) => return Err(Error::InvalidType(format!("Cannot add {:?} to a column of {}", value, data.data_type())))
+    }
+
+    Ok(())
+  }
+}
+
+impl Col {
+  /// The value at `row`, `Value::Null` if it is not valid.
+  pub fn value(&self, row: usize) -> Value {
+    if self.is_valid(row) {
+      self.data.value(row)
+    } else {
+      Value::Null
+    }
+  }
+}
+
+impl LeapFrame {
+  /// Builds a frame with one column per field of `schema`. Nullable fields
+  /// get a validity bitmap, and tensor columns are sparse when their first
+  /// non-null value is. Numeric fields hold doubles, in the shape of their
+  /// first non-null value. Tensors must all have the dimensions of the field,
+  /// or of the first tensor when the field has none.
+  pub fn from_rows(schema: &Schema, rows: Vec<Row>) -> Result<LeapFrame> {
+    let fields = schema.fields();
+    let size = rows.len();
+    let mut columns: Vec<Vec<Value>> = fields.iter().map(|_| Vec::with_capacity(size)).collect();
+
+    for row in rows.into_iter() {
+      if row.len() != fields.len() {
+        return Err(Error::InvalidType(format!("Expected {} values per row, got {}", fields.len(), row.len())));
+      }
+
+      for (col, value) in columns.iter_mut().zip(row.into_values().into_iter()) {
+        col.push(value);
+      }
+    }
+
+    let mut frame = LeapFrame::with_size(size);
+
+    for (field, values) in fields.iter().zip(columns.into_iter()) {
+      let first = values.iter().find(|v| !v.is_null()).and_then(|v| v.data_type().map(|dt| (dt, v.is_sparse())));
+      let sparse = first.as_ref().map(|&(_, sparse)| sparse).unwrap_or(false);
+      let dims = match (field.data_type().shape(), first.as_ref().map(|&(ref dt, _)| dt.shape())) {
+        (&DataShape::Tensor(Some(ref dims)), _) | (_, Some(&DataShape::Tensor(Some(ref dims)))) => dims.clone(),
+        _ => vec![0]
+      };
+
+      let data_type = match first {
+        Some((ref dt, _)) if field.data_type().is_numeric() => DataType::new(field.data_type().base(), dt.shape().clone()),
+        None if field.data_type().is_numeric() => DataType::scalar(field.data_type().base()),
+        _ => field.data_type().clone()
+      };
+
+      let mut data = ColData::with_capacity(&data_type, sparse, size);
+      let mut validity = Vec::with_capacity(size);
+
+      for value in values.into_iter() {
+        match value.data_type() {
+          None if !field.nullable() => return Err(Error::InvalidType(format!("{}: null value in non-nullable field", field.name()))),
+          Some(ref dt) if !field.data_type().accepts(dt) => return Err(Error::InvalidType(format!("{}: expected {}, got {}", field.name(), field.data_type(), dt))),
+          Some(ref dt) => {
+            match *dt.shape() {
+              DataShape::Tensor(Some(ref d)) if *d != dims => {
+                return Err(Error::InvalidType(format!("{}: expected tensor dimensions {:?}, got {:?}", field.name(), dims, d)));
+              },
+              _ => ()
+            }
+          },
+          _ => ()
+        }
+
+        validity.push(!value.is_null());
+
+        let value = if field.data_type().is_numeric() { value.to_double() } else { value };
+        match data.try_push(value, &dims) {
+          Ok(_) => (),
+          Err(Error::InvalidType(msg)) => return Err(Error::InvalidType(format!("{}: {}", field.name(), msg))),
+          Err(err) => return Err(err)
+        }
+      }
+
+      let col = if field.nullable() {
+        Col::new(field.name().to_string(), data).with_validity(validity)
+      } else {
+        Col::new(field.name().to_string(), data)
+      };
+
+      if let Err(err) = frame.try_with_col(col) {
+        return Err(err);
+      }
+    }
+
+    Ok(frame)
+  }
+
+  pub fn get_row(&self, row: usize) -> Option<Row> {
+    if row < self.size {
+      Some(Row::new(self.cols.iter().map(|c| c.value(row)).collect()))
+    } else {
+      None
+    }
+  }
+
+  pub fn rows(&self) -> Rows<'_> {
+    Rows {
+      frame: self,
+      row: 0
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use super::super::StructField;
+
+  #[test]
+  fn test_rows_round_trip() {
+    let schema = Schema::new(vec![StructField::required("state", DataType::scalar(BasicType::String)),
+                                  StructField::new(String::from("bathrooms"), DataType::scalar(BasicType::Double), true),
+                                  StructField::required("features", DataType::tensor(BasicType::Double, None))]);
+    let rows = vec![Row::new(vec![Value::from("NY"), Value::from(2.0), Value::from(SparseTensor::new(vec![3], vec![1], vec![1.0]))]),
+                    Row::new(vec![Value::from("CA"), Value::Null, Value::from(SparseTensor::new(vec![3], vec![], vec![]))])];
+
+    let frame = LeapFrame::from_rows(&schema, rows.clone()).unwrap();
+    assert_eq!(frame.size(), 2);
+    assert_eq!(frame.get_strings("state").unwrap(), &[String::from("NY"), String::from("CA")]);
+    assert_eq!(frame.try_col("bathrooms").unwrap().validity(), Some(&[true, false][..]));
+    assert!(frame.get_sparse_double_tensors("features").is_some());
+
+    assert_eq!(frame.get_row(1).unwrap().get(1), Some(&Value::Null));
+    assert_eq!(frame.get_row(1).unwrap().get(0).and_then(|v| v.get_string()), Some("CA"));
+    assert!(frame.get_row(2).is_none());
+    assert_eq!(frame.rows().collect::<Vec<Row>>(), rows);
+  }
+
+  #[test]
+  fn test_invalid_rows() {
+    let schema = Schema::new(vec![StructField::required("a", DataType::scalar(BasicType::Double))]);
+
+    assert!(LeapFrame::from_rows(&schema, vec![Row::new(vec![Value::Null])]).is_err());
+    assert!(LeapFrame::from_rows(&schema, vec![Row::new(vec![Value::from(1)])]).is_err());
+    assert!(LeapFrame::from_rows(&schema, vec![Row::new(vec![])]).is_err());
+  }
+  #[test]
+  fn test_numeric_rows() {
+    let schema = Schema::new(vec![StructField::optional("a", DataType::numeric(DataShape::Scalar)),
+                                  StructField::required("b", DataType::numeric(DataShape::Tensor(None)))]);
+    let rows = vec![Row::new(vec![Value::from(1), Value::IntTensor(DenseTensor::new(vec![2], vec![1, 2]))]),
+                    Row::new(vec![Value::from(2.5), Value::from(DenseTensor::new(vec![2], vec![0.5, 1.5]))]),
+                    Row::new(vec![Value::Null, Value::LongTensor(DenseTensor::new(vec![2], vec![3, 4]))]),
+                    Row::new(vec![Value::from(4i64), Value::FloatTensor(DenseTensor::new(vec![2], vec![0.25, 0.75]))])];
+
+    let frame = LeapFrame::from_rows(&schema, rows).unwrap();
+    assert_eq!(frame.get_doubles("a").unwrap(), &[1.0, 2.5, 0.0, 4.0]);
+    assert_eq!(frame.get_double_tensors("b").unwrap(), &[DenseTensor::new(vec![2], vec![1.0, 2.0]),
+                                                         DenseTensor::new(vec![2], vec![0.5, 1.5]),
+                                                         DenseTensor::new(vec![2], vec![3.0, 4.0]),
+                                                         DenseTensor::new(vec![2], vec![0.25, 0.75])]);
+  }
+
+  #[test]
+  fn test_tensor_dimensions() {
+    let schema = Schema::new(vec![StructField::required("t", DataType::tensor(BasicType::Double, None))]);
+    let rows = vec![Row::new(vec![Value::from(DenseTensor::new(vec![2], vec![1.0, 2.0]))]),
+                    Row::new(vec![Value::from(DenseTensor::new(vec![3], vec![1.0, 2.0, 3.0]))])];
+
+    match LeapFrame::from_rows(&schema, rows) {
+      Err(Error::InvalidType(msg)) => assert_eq!(msg, "t: expected tensor dimensions [2], got [3]"),
+      r => panic!("Expected mismatched tensor dimensions, got {:?}", r.map(|f| f.size()))
+    }
+  }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use super::{Error, Result};
+
+/// Element type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasicType {
+  Bool,
+  String,
+  Byte,
+  Short,
+  Int,
+  Long,
+  Float,
+  Double,
+  ByteString
+}
+
+/// Scalars hold one value per row, lists a variable number and tensors a
+/// value per cell, with the dimensions of the first row when known.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataShape {
+  Scalar,
+  List,
+  Tensor(Option<Vec<usize>>)
+}
+
+/// A numeric type takes numbers of any base, which the transformer reads
+/// as `base`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataType {
+  base: BasicType,
+  shape: DataShape,
+  numeric: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+  name: String,
+  data_type: DataType,
+  nullable: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+  fields: Vec<StructField>
+}
+
+impl BasicType {
+  pub fn from_str(s: &str) -> Option<BasicType> {
+    match s {
+      "boolean" => Some(BasicType::Bool),
+      "string" => Some(BasicType::String),
+      "byte" => Some(BasicType::Byte),
+      "short" => Some(BasicType::Short),
+      "int" => Some(BasicType::Int),
+      "long" => Some(BasicType::Long),
+      "float" => Some(BasicType::Float),
+      "double" => Some(BasicType::Double),
+      "byte_string" => Some(BasicType::ByteString),
+      _ => None
+    }
+  }
+
+  pub fn is_numeric(&self) -> bool {
+    match *self {
+      BasicType::Byte | BasicType::Short | BasicType::Int | BasicType::Long | BasicType::Float | BasicType::Double => true,
+      _ => false
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match *self {
+      BasicType::Bool => "boolean",
+      BasicType::String => "string",
+      BasicType::Byte => "byte",
+      BasicType::Short => "short",
+      BasicType::Int => "int",
+      BasicType::Long => "long",
+      BasicType::Float => "float",
+      BasicType::Double => "double",
+      BasicType::ByteString => "byte_string"
+    }
+  }
+}
+
+impl DataType {
+  pub fn new(base: BasicType, shape: DataShape) -> DataType {
+    DataType {
+      base: base,
+      shape: shape,
+      numeric: false
+    }
+  }
+
+  /// Numbers of any base read as doubles. A tensor shape without
+  /// dimensions also takes scalars, as tensors of one value.
+  pub fn numeric(shape: DataShape) -> DataType {
+    DataType {
+      base: BasicType::Double,
+      shape: shape,
+      numeric: true
+    }
+  }
+
+  pub fn scalar(base: BasicType) -> DataType { DataType::new(base, DataShape::Scalar) }
+
+  pub fn tensor(base: BasicType, dimensions: Option<Vec<usize>>) -> DataType {
+    DataType::new(base, DataShape::Tensor(dimensions))
+  }
+
+  pub fn base(&self) -> BasicType { self.base }
+  pub fn shape(&self) -> &DataShape { &self.shape }
+  pub fn is_numeric(&self) -> bool { self.numeric }
+
+  /// Whether a column of type `other` can be read as this type, a tensor
+  /// without dimensions matches any dimensions.
+  pub fn accepts(&self, other: &DataType) -> bool {
+    let base = self.base == other.base || (self.numeric && other.base.is_numeric());
+
+    base && match (&self.shape, &other.shape) {
+      (&DataShape::Tensor(None), &DataShape::Tensor(_)) => true,
+      (&DataShape::Tensor(None), &DataShape::Scalar) => self.numeric,
+      (a, b) => a == b
+    }
+  }
+}
+
+impl fmt::Display for DataType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let base = if self.numeric { "numeric" } else { self.base.as_str() };
+
+    match self.shape {
+      DataShape::Scalar => write!(f, "{}", base),
+      DataShape::List => write!(f, "list<{}>", base),
+      DataShape::Tensor(Some(ref dims)) => write!(f, "tensor<{}>{:?}", base, dims),
+      DataShape::Tensor(None) if self.numeric => write!(f, "{} or tensor<{}>", base, base),
+      DataShape::Tensor(None) => write!(f, "tensor<{}>", base)
+    }
+  }
+}
+
+impl StructField {
+  pub fn new(name: String, data_type: DataType, nullable: bool) -> StructField {
+    StructField {
+      name: name,
+      data_type: data_type,
+      nullable: nullable
+    }
+  }
+
+  /// A non-nullable field.
+  pub fn required(name: &str, data_type: DataType) -> StructField {
+    StructField::new(name.to_string(), data_type, false)
+  }
+
+  /// A nullable field.
+  pub fn optional(name: &str, data_type: DataType) -> StructField {
+    StructField::new(name.to_string(), data_type, true)
+  }
+
+  pub fn name(&self) -> &str { &self.name }
+  pub fn data_type(&self) -> &DataType { &self.data_type }
+  pub fn nullable(&self) -> bool { self.nullable }
+}
+
+impl Schema {
+  pub fn new(fields: Vec<StructField>) -> Schema {
+    Schema {
+      fields: fields
+    }
+  }
+
+  pub fn fields(&self) -> &[StructField] { &self.fields }
+
+  pub fn get_field(&self, name: &str) -> Option<&StructField> {
+    self.fields.iter().find(|f| f.name() == name)
+  }
+
+  /// Checks that `actual` has every field of this schema with an accepted
+  /// type.
+  pub fn try_validate(&self, actual: &Schema) -> Result<()> {
+    for field in self.fields.iter() {
+      match actual.get_field(field.name()) {
+        Some(a) if field.data_type().accepts(a.data_type()) => (),
+        Some(a) => return Err(Error::InvalidType(format!("{}: expected {}, got {}", field.name(), field.data_type(), a.data_type()))),
+        None => return Err(Error::NoSuchColumn(field.name().to_string()))
+      }
+    }
+
+    Ok(())
+  }
+}
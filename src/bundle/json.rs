@@ -61,9 +61,12 @@ impl<'a> From<&'a dsl::Attribute> for Value {
         tmap.insert(String::from("dimensions"), Value::from(d));
         tmap.insert(String::from("values"), v);
 
+        let mut btmap = Map::with_capacity(1);
+        btmap.insert(String::from("base"), Value::from(b));
+
         let mut ttmap = Map::with_capacity(2);
         ttmap.insert(String::from("type"), Value::from("tensor"));
-        ttmap.insert(String::from("base"), Value::from(b));
+        ttmap.insert(String::from("tensor"), Value::Object(btmap));
 
         let mut map = Map::with_capacity(3);
         map.insert(String::from("type"), Value::Object(ttmap));
@@ -87,9 +90,12 @@ impl<'a> From<&'a dsl::Attribute> for Value {
           }
         };
 
-        let mut map = Map::with_capacity(3);
-        map.insert(String::from("type"), Value::from("tensor"));
-        map.insert(String::from("base"), Value::from(b));
+        let mut ltmap = Map::with_capacity(2);
+        ltmap.insert(String::from("type"), Value::from("list"));
+        ltmap.insert(String::from("base"), Value::from(b));
+
+        let mut map = Map::with_capacity(2);
+        map.insert(String::from("type"), Value::Object(ltmap));
         map.insert(String::from("value"), v);
 
         Value::Object(map)
@@ -102,6 +108,67 @@ fn basic_attribute(basic: dsl::BasicValue) -> dsl::Attribute {
   dsl::Attribute::Basic(basic)
 }
 
+fn byte_strings(value: &Value) -> Result<Vec<Vec<u8>>, Error> {
+  Vec::<String>::try_from(value).and_then(|vs| {
+    let mut acc = Vec::with_capacity(vs.len());
+
+    for v64 in vs.iter() {
+      match base64::decode(v64) {
+        Ok(v) => acc.push(v),
+        Err(_) => return Err(Error::ReadError("Invalid base64 string".to_string()))
+      }
+    }
+
+    Ok(acc)
+  })
+}
+
+/// Upstream MLeap writes sparse tensors with an `indices` list holding one
+/// coordinate per value.
+pub fn flat_indices(dims: &[usize], indices: &[Vec<usize>]) -> Result<Vec<usize>, Error> {
+  let mut acc = Vec::with_capacity(indices.len());
+
+  for index in indices.iter() {
+    if index.len() != dims.len() || index.iter().zip(dims.iter()).any(|(i, d)| i >= d) {
+      return Err(Error::ReadError(format!("Invalid sparse tensor index {:?}", index)));
+    }
+
+    let flat = index.iter().zip(dims.iter()).fold(Some(0usize), |flat, (i, d)| {
+      flat.and_then(|f| f.checked_mul(*d)).and_then(|f| f.checked_add(*i))
+    });
+    match flat {
+      Some(flat) => acc.push(flat),
+      None => return Err(Error::ReadError(format!("Sparse tensor index {:?} overflows dimensions {:?}", index, dims)))
+    }
+  }
+
+  Ok(acc)
+}
+
+/// The inverse of `flat_indices`.
+pub fn coordinates(dims: &[usize], indices: &[usize]) -> Vec<Vec<usize>> {
+  indices.iter().map(|&flat| {
+    let mut index = vec![0; dims.len()];
+    let mut rest = flat;
+
+    for (i, d) in index.iter_mut().zip(dims.iter()).rev() {
+      *i = rest % d;
+      rest /= *d;
+    }
+
+    index
+  }).collect()
+}
+
+fn dense_tensor<T: Clone + Default>(dims: Vec<usize>, indices: &Option<Vec<usize>>, values: Vec<T>) -> Result<dsl::DenseTensor<T>, Error> {
+  match indices {
+    &Some(ref indices) => dsl::SparseTensor::try_new(dims, indices.clone(), values).
+      map(|t| Ok(t.to_dense())).
+      unwrap_or_else(|| Err(Error::ReadError("Sparse tensor indices do not match values or dimensions".to_string()))),
+    &None => Ok(dsl::DenseTensor::new(dims, values))
+  }
+}
+
 impl<'a> TryFrom<&'a Value> for usize {
   type Err = Error;
 
@@ -250,15 +317,21 @@ impl<'a> TryFrom<&'a Value> for dsl::Attribute {
                       match (tmap.get("dimensions"), tmap.get("values")) {
                         (Some(jdims), Some(jvalues)) => {
                           let r = Vec::<usize>::try_from(jdims).and_then(|dims| {
+                            match tmap.get("indices") {
+                              Some(jindices) => Vec::<Vec<usize>>::try_from(jindices).and_then(|indices| flat_indices(&dims, &indices)).map(Some),
+                              None => Ok(None)
+                            }.map(|indices| (dims, indices))
+                          }).and_then(|(dims, indices)| {
                             (match base.as_ref() {
-                              "boolean" => Vec::<bool>::try_from(jvalues).map(|v| dsl::TensorValue::Bool(dsl::DenseTensor::new(dims, v))),
-                              "string" => Vec::<String>::try_from(jvalues).map(|v| dsl::TensorValue::String(dsl::DenseTensor::new(dims, v))),
-                              "byte" => Vec::<i8>::try_from(jvalues).map(|v| dsl::TensorValue::Byte(dsl::DenseTensor::new(dims, v))),
-                              "short" => Vec::<i16>::try_from(jvalues).map(|v| dsl::TensorValue::Short(dsl::DenseTensor::new(dims, v))),
-                              "int" => Vec::<i32>::try_from(jvalues).map(|v| dsl::TensorValue::Int(dsl::DenseTensor::new(dims, v))),
-                              "long" => Vec::<i64>::try_from(jvalues).map(|v| dsl::TensorValue::Long(dsl::DenseTensor::new(dims, v))),
-                              "float" => Vec::<f32>::try_from(jvalues).map(|v| dsl::TensorValue::Float(dsl::DenseTensor::new(dims, v))),
-                              "double" => Vec::<f64>::try_from(jvalues).map(|v| dsl::TensorValue::Double(dsl::DenseTensor::new(dims, v))),
+                              "boolean" => Vec::<bool>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Bool(t)),
+                              "string" => Vec::<String>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::String(t)),
+                              "byte" => Vec::<i8>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Byte(t)),
+                              "short" => Vec::<i16>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Short(t)),
+                              "int" => Vec::<i32>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Int(t)),
+                              "long" => Vec::<i64>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Long(t)),
+                              "float" => Vec::<f32>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Float(t)),
+                              "double" => Vec::<f64>::try_from(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::Double(t)),
+                              "byte_string" => byte_strings(jvalues).and_then(|v| dense_tensor(dims, &indices, v)).map(|t| dsl::TensorValue::ByteString(t)),
                               _ => Err(Error::ReadError("Invalid base for tensor".to_string()))
                             })
                           }).map(|t| dsl::Attribute::Tensor(t));
@@ -281,7 +354,8 @@ impl<'a> TryFrom<&'a Value> for dsl::Attribute {
                         "long" => Vec::<i64>::try_from(jvalues).map(|v| dsl::VectorValue::Long(v)),
                         "float" => Vec::<f32>::try_from(jvalues).map(|v| dsl::VectorValue::Float(v)),
                         "double" => Vec::<f64>::try_from(jvalues).map(|v| dsl::VectorValue::Double(v)),
-                        _ => Err(Error::ReadError("".to_string()))
+                        "byte_string" => byte_strings(jvalues).map(|v| dsl::VectorValue::ByteString(v)),
+                        _ => Err(Error::ReadError("Invalid base for list".to_string()))
                       }).map(|values| dsl::Attribute::Array(values));
 
                       Some(r)
@@ -440,6 +514,105 @@ impl<'a> TryFrom<&'a Value> for dsl::Node {
   }
 }
 
+// tree nodes use the protobuf JSON mapping of ml.bundle.tree.decision.Node
+impl<'a> From<&'a dsl::Split> for Value {
+  fn from(value: &'a dsl::Split) -> Self {
+    let (t, split) = match value {
+      &dsl::Split::Continuous { feature_index, threshold } => {
+        let mut map = Map::with_capacity(2);
+        map.insert(String::from("featureIndex"), Value::from(feature_index));
+        map.insert(String::from("threshold"), Value::from(threshold));
+        ("continuous", map)
+      },
+      &dsl::Split::Categorical { feature_index, num_categories, ref categories, is_left } => {
+        let mut map = Map::with_capacity(4);
+        map.insert(String::from("featureIndex"), Value::from(feature_index));
+        map.insert(String::from("isLeft"), Value::from(is_left));
+        map.insert(String::from("numCategories"), Value::from(num_categories));
+        map.insert(String::from("categories"), Value::from(categories.as_slice()));
+        ("categorical", map)
+      }
+    };
+
+    let mut map = Map::with_capacity(1);
+    map.insert(String::from(t), Value::Object(split));
+
+    Value::Object(map)
+  }
+}
+
+impl<'a> TryFrom<&'a Value> for dsl::Split {
+  type Err = Error;
+
+  fn try_from(value: &'a Value) -> Result<Self, Self::Err> {
+    let continuous = value.get("continuous").and_then(|split| {
+      match (split.get("featureIndex").map(usize::try_from), split.get("threshold").map(f64::try_from)) {
+        (Some(Ok(feature_index)), Some(Ok(threshold))) => {
+          Some(dsl::Split::Continuous { feature_index: feature_index, threshold: threshold })
+        },
+        _ => None
+      }
+    });
+
+    let categorical = || value.get("categorical").and_then(|split| {
+      let categories = split.get("categories").map(Vec::<f64>::try_from).unwrap_or_else(|| Ok(Vec::new()));
+
+      match (split.get("featureIndex").map(usize::try_from), split.get("numCategories").map(usize::try_from), categories) {
+        (Some(Ok(feature_index)), Some(Ok(num_categories)), Ok(categories)) => {
+          Some(dsl::Split::Categorical {
+            feature_index: feature_index,
+            num_categories: num_categories,
+            categories: categories,
+            is_left: split.get("isLeft").and_then(|x| x.as_bool()).unwrap_or(false)
+          })
+        },
+        _ => None
+      }
+    });
+
+    continuous.or_else(categorical).
+      map(|x| Ok(x)).
+      unwrap_or_else(|| Err(Error::ReadError(String::from("Invalid split"))))
+  }
+}
+
+impl<'a> From<&'a dsl::TreeNode> for Value {
+  fn from(value: &'a dsl::TreeNode) -> Self {
+    let mut node = Map::with_capacity(1);
+    let t = match value {
+      &dsl::TreeNode::Internal(ref split) => {
+        node.insert(String::from("split"), Value::from(split));
+        "internal"
+      },
+      &dsl::TreeNode::Leaf(ref values) => {
+        node.insert(String::from("values"), Value::from(values.as_slice()));
+        "leaf"
+      }
+    };
+
+    let mut map = Map::with_capacity(1);
+    map.insert(String::from(t), Value::Object(node));
+
+    Value::Object(map)
+  }
+}
+
+impl<'a> TryFrom<&'a Value> for dsl::TreeNode {
+  type Err = Error;
+
+  fn try_from(value: &'a Value) -> Result<Self, Self::Err> {
+    match (value.get("internal").and_then(|x| x.get("split")), value.get("leaf")) {
+      (Some(split), _) => dsl::Split::try_from(split).map(|split| dsl::TreeNode::Internal(split)),
+      (None, Some(leaf)) => {
+        leaf.get("values").map(Vec::<f64>::try_from).
+          unwrap_or_else(|| Ok(Vec::new())).
+          map(|values| dsl::TreeNode::Leaf(values))
+      },
+      _ => Err(Error::ReadError(String::from("Invalid tree node")))
+    }
+  }
+}
+
 impl<'a> From<&'a dsl::Format> for Value {
   fn from(value: &'a dsl::Format) -> Self {
     match *value {
@@ -458,7 +631,7 @@ impl<'a> TryFrom<&'a Value> for dsl::Format {
       &Value::String(ref name) => {
         match name.as_ref() {
           "json" => Ok(dsl::Format::Concrete(dsl::ConcreteFormat::Json)),
-          "proto" => Ok(dsl::Format::Concrete(dsl::ConcreteFormat::Json)),
+          "proto" | "protobuf" => Ok(dsl::Format::Concrete(dsl::ConcreteFormat::Proto)),
           "mixed" => Ok(dsl::Format::Mixed),
           _ => Err(Error::ReadError(String::from("")))
         }
@@ -517,3 +690,126 @@ impl<'a> TryFrom<&'a Value> for dsl::Bundle {
     }).unwrap_or_else(|| Err(Error::ReadError(String::from(""))))
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json;
+
+  fn round_trip(attr: dsl::Attribute) {
+    let json = serde_json::to_string(&Value::from(&attr)).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(dsl::Attribute::try_from(&value).unwrap(), attr);
+  }
+
+  fn tensor<T>(values: Vec<T>) -> dsl::DenseTensor<T> {
+    dsl::DenseTensor::new(vec![values.len()], values)
+  }
+
+  #[test]
+  fn test_basic_round_trip() {
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Bool(true)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::String(String::from("hello"))));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Byte(-8)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Short(-300)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Int(70000)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Long(-5000000000)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Float(0.25)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Double(236.76099900182078)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::ByteString(vec![0, 1, 254, 255])));
+  }
+
+  #[test]
+  fn test_vector_round_trip() {
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Bool(vec![true, false])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::String(vec![String::from("a"), String::from("b")])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Byte(vec![-1, 0, 1])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Short(vec![-300, 300])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Int(vec![1, 2, 3])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Long(vec![-5000000000, 5000000000])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Float(vec![0.5, -1.75])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Double(vec![1.1, 2.2])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::ByteString(vec![vec![1, 2], vec![]])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Double(vec![])));
+  }
+
+  #[test]
+  fn test_tensor_round_trip() {
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Bool(tensor(vec![true, false]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::String(tensor(vec![String::from("a"), String::new()]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Byte(tensor(vec![-1, 1]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Short(tensor(vec![-300, 300]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Int(dsl::DenseTensor::new(vec![2, 2], vec![1, 2, 3, 4]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Long(tensor(vec![5000000000]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Float(tensor(vec![0.5, -1.75]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Double(tensor(vec![1.1, 2.2, 3.3]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::ByteString(tensor(vec![vec![1, 2], vec![3]]))));
+  }
+
+  #[test]
+  fn test_sparse_tensor() {
+    let value: Value = serde_json::from_str(r#"{
+      "type": {"type": "tensor", "tensor": {"base": "double"}},
+      "value": {"dimensions": [2, 3], "indices": [[0, 1], [1, 2]], "values": [1.5, -2.0]}
+    }"#).unwrap();
+
+    assert_eq!(dsl::Attribute::try_from(&value).unwrap(),
+               dsl::Attribute::Tensor(dsl::TensorValue::Double(dsl::DenseTensor::new(vec![2, 3], vec![0.0, 1.5, 0.0, 0.0, 0.0, -2.0]))));
+
+    let value: Value = serde_json::from_str(r#"{
+      "type": {"type": "tensor", "tensor": {"base": "double"}},
+      "value": {"dimensions": [3], "indices": [[3]], "values": [1.0]}
+    }"#).unwrap();
+    assert!(dsl::Attribute::try_from(&value).is_err());
+  }
+
+  #[test]
+  fn test_flat_indices_overflow() {
+    assert_eq!(flat_indices(&[2, 3], &[vec![1, 2]]).unwrap(), vec![5]);
+    match flat_indices(&[2, usize::max_value()], &[vec![1, 1]]) {
+      Err(Error::ReadError(msg)) => assert!(msg.contains("overflows")),
+      r => panic!("Expected an overflowing index, got {:?}", r)
+    }
+  }
+
+  #[test]
+  fn test_model_round_trip() {
+    let mut model = dsl::Model::new(String::from("linear_regression"), HashMap::new());
+    model.with_attr("intercept", dsl::Attribute::Basic(dsl::BasicValue::Double(1.0))).
+      with_attr("coefficients", dsl::Attribute::Tensor(dsl::TensorValue::Double(tensor(vec![1.0, 2.0]))));
+
+    let value = Value::from(&model);
+    assert_eq!(dsl::Model::try_from(&value).unwrap(), model);
+
+    let shape = dsl::Shape::with_standard_io(String::from("input"), String::from("output"));
+    let node = dsl::Node::new(String::from("node"), shape);
+    let value = Value::from(&node);
+    assert_eq!(dsl::Node::try_from(&value).unwrap(), node);
+  }
+
+  #[test]
+  fn test_tree_node_round_trip() {
+    let nodes = vec![dsl::TreeNode::Internal(dsl::Split::Continuous { feature_index: 1, threshold: 0.5 }),
+                     dsl::TreeNode::Internal(dsl::Split::Categorical { feature_index: 0,
+                                                                       num_categories: 3,
+                                                                       categories: vec![0.0, 2.0],
+                                                                       is_left: true }),
+                     dsl::TreeNode::Leaf(vec![1.0, 3.0])];
+
+    for node in nodes.iter() {
+      let json = serde_json::to_string(&Value::from(node)).unwrap();
+      let value: Value = serde_json::from_str(&json).unwrap();
+      assert_eq!(dsl::TreeNode::try_from(&value).unwrap(), *node);
+    }
+  }
+
+  #[test]
+  fn test_format() {
+    for format in [dsl::Format::Concrete(dsl::ConcreteFormat::Json),
+                   dsl::Format::Concrete(dsl::ConcreteFormat::Proto),
+                   dsl::Format::Mixed].iter() {
+      assert_eq!(dsl::Format::try_from(&Value::from(format)).unwrap(), *format);
+    }
+  }
+}
@@ -1,5 +1,6 @@
 pub mod dsl;
 pub mod json;
+pub mod proto;
 pub mod ser;
 pub mod tform;
 pub mod frame;
@@ -7,120 +8,1122 @@ pub mod frame;
 #[cfg(test)]
 mod test {
   use super::*;
+  use std::env;
   use std::ffi;
+  use std::fs;
+  use std::path;
+  use std::io::Read;
+  use std::collections::HashMap;
+  use uuid::Uuid;
+  use semver::Version;
   use c;
+  use self::ser::Op;
 
-  #[test]
-  fn test_airbnb() {
-    let path = "/tmp/model";
-    let builder = ser::FileBuilder::try_new(path).unwrap();
-    let mut registry = ser::Registry::new();
+  const BUNDLES: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bundles");
 
-    registry.insert_op(tform::linear_regression::OP);
-    registry.insert_op(tform::string_indexer::OP);
-    registry.insert_op(tform::one_hot_encoder::OP);
-    registry.insert_op(tform::pipeline::OP);
-    registry.insert_op(tform::vector_assembler::OP);
-    registry.insert_op(tform::standard_scaler::OP);
+  fn registry() -> ser::Registry<'static, Box<tform::DefaultNode>> {
+    tform::default_registry()
+  }
+
+  fn room_type_indexer(handle_invalid: tform::HandleInvalid) -> Box<tform::DefaultNode> {
+    let labels = vec![String::from("Entire home/apt"), String::from("Private room"), String::from("Shared room")];
+    Box::new(tform::string_indexer::StringIndexer::new(String::from("room_type_indexer"),
+                                                       String::from("room_type"),
+                                                       String::from("room_type_index"),
+                                                       tform::string_indexer::StringIndexerModel::with_handle_invalid(labels, handle_invalid)))
+  }
+
+  fn room_type_encoder() -> Box<tform::DefaultNode> {
+    Box::new(tform::one_hot_encoder::OneHotEncoder::new(String::from("room_type_encoder"),
+                                                        String::from("room_type_index"),
+                                                        String::from("room_type_oh"),
+                                                        tform::one_hot_encoder::OneHotEncoderModel::new(3)))
+  }
+
+  fn categorical_indexer() -> Box<tform::DefaultNode> {
+    let labels_array = vec![vec![String::from("Entire home/apt"), String::from("Private room"), String::from("Shared room")],
+                            vec![String::from("NY"), String::from("CA")]];
+    Box::new(tform::string_indexer::StringIndexer::with_cols(String::from("categorical_indexer"),
+                                                             vec![String::from("room_type"), String::from("state")],
+                                                             vec![String::from("room_type_index"), String::from("state_index")],
+                                                             tform::string_indexer::StringIndexerModel::with_labels_array(labels_array, tform::HandleInvalid::Skip)))
+  }
+
+  fn categorical_encoder() -> Box<tform::DefaultNode> {
+    Box::new(tform::one_hot_encoder::OneHotEncoder::with_cols(String::from("categorical_encoder"),
+                                                              vec![String::from("room_type_index"), String::from("state_index")],
+                                                              vec![String::from("room_type_oh"), String::from("state_oh")],
+                                                              tform::one_hot_encoder::OneHotEncoderModel::with_sizes(vec![3, 2])))
+  }
+
+  fn room_type_encoder_drop_last_keep() -> Box<tform::DefaultNode> {
+    Box::new(tform::one_hot_encoder::OneHotEncoder::new(String::from("room_type_encoder"),
+                                                        String::from("room_type_index"),
+                                                        String::from("room_type_oh"),
+                                                        tform::one_hot_encoder::OneHotEncoderModel::with_options(vec![3], true, tform::HandleInvalid::Keep)))
+  }
+
+  fn continuous_assembler() -> Box<tform::DefaultNode> {
+    Box::new(tform::vector_assembler::VectorAssembler::new(String::from("continuous_assembler"),
+                                                           vec![String::from("bathrooms"), String::from("bedrooms")],
+                                                           String::from("continuous")))
+  }
+
+  fn standard_scaler(mean: Option<Vec<f64>>, std: Option<Vec<f64>>) -> Box<tform::DefaultNode> {
+    let mean = mean.map(|m| dsl::DenseTensor::new(vec![m.len()], m));
+    let std = std.map(|s| dsl::DenseTensor::new(vec![s.len()], s));
+    Box::new(tform::standard_scaler::StandardScaler::new(String::from("continuous_scaler"),
+                                                         String::from("continuous"),
+                                                         String::from("continuous_scaled"),
+                                                         tform::standard_scaler::StandardScalerModel::try_new(mean, std).unwrap()))
+  }
+
+  fn continuous_scaler() -> Box<tform::DefaultNode> {
+    standard_scaler(None, Some(vec![0.5, 2.0]))
+  }
+
+  fn price_regression(coefficients: Vec<f64>, intercept: f64) -> Box<tform::DefaultNode> {
+    let coefficients = dsl::DenseTensor::new(vec![coefficients.len()], coefficients);
+    Box::new(tform::linear_regression::LinearRegression::new(String::from("price_regression"),
+                                                             String::from("features"),
+                                                             String::from("price_prediction"),
+                                                             tform::linear_regression::LinearRegressionModel::new(coefficients, intercept)))
+  }
+
+  fn logistic_regression(model: tform::logistic_regression::LogisticRegressionModel) -> Box<tform::DefaultNode> {
+    Box::new(tform::logistic_regression::LogisticRegression::new(String::from("classifier"),
+                                                                 String::from("features"),
+                                                                 String::from("prediction"),
+                                                                 Some(String::from("probability")),
+                                                                 Some(String::from("raw_prediction")),
+                                                                 model))
+  }
+
+  // x0 <= 1.5 ? (x1 in {0, 2} ? leaf 0 : leaf 1) : leaf 2
+  fn tree_nodes(leaves: Vec<Vec<f64>>) -> Vec<dsl::TreeNode> {
+    let mut leaves = leaves.into_iter().map(|v| dsl::TreeNode::Leaf(v));
+
+    vec![dsl::TreeNode::Internal(dsl::Split::Continuous { feature_index: 0, threshold: 1.5 }),
+         dsl::TreeNode::Internal(dsl::Split::Categorical { feature_index: 1,
+                                                           num_categories: 3,
+                                                           categories: vec![0.0, 2.0],
+                                                           is_left: true }),
+         leaves.next().unwrap(),
+         leaves.next().unwrap(),
+         leaves.next().unwrap()]
+  }
+
+  fn decision_tree_regression() -> Box<tform::DefaultNode> {
+    let nodes = tree_nodes(vec![vec![1.0], vec![2.0], vec![3.0]]);
+    let model = tform::decision_tree_regression::DecisionTreeRegressionModel::try_new(&nodes, 2).unwrap();
+    Box::new(tform::decision_tree_regression::DecisionTreeRegression::new(String::from("tree"),
+                                                                          String::from("features"),
+                                                                          String::from("prediction"),
+                                                                          model))
+  }
+
+  fn decision_tree_classifier() -> Box<tform::DefaultNode> {
+    let nodes = tree_nodes(vec![vec![8.0, 2.0], vec![1.0, 3.0], vec![0.0, 0.0]]);
+    let model = tform::decision_tree_classifier::DecisionTreeClassifierModel::try_new(&nodes, 2, 2).unwrap();
+    Box::new(tform::decision_tree_classifier::DecisionTreeClassifier::new(String::from("tree"),
+                                                                          String::from("features"),
+                                                                          String::from("prediction"),
+                                                                          Some(String::from("probability")),
+                                                                          Some(String::from("raw_prediction")),
+                                                                          model))
+  }
+
+  fn regression_trees(leaves: Vec<[f64; 3]>) -> Vec<tform::decision_tree_regression::DecisionTreeRegressionModel> {
+    leaves.iter().map(|l| {
+      let nodes = tree_nodes(l.iter().map(|v| vec![*v]).collect());
+      tform::decision_tree_regression::DecisionTreeRegressionModel::try_new(&nodes, 2).unwrap()
+    }).collect()
+  }
+
+  fn random_forest_regression() -> Box<tform::DefaultNode> {
+    let ensemble = tform::ensemble::Ensemble::try_new(regression_trees(vec![[1.0, 2.0, 3.0], [3.0, 4.0, 5.0]]), vec![1.0, 1.0]).unwrap();
+    let model = tform::random_forest_regression::RandomForestRegressionModel::new(ensemble, 2);
+    Box::new(tform::random_forest_regression::RandomForestRegression::new(String::from("forest"),
+                                                                          String::from("features"),
+                                                                          String::from("prediction"),
+                                                                          model))
+  }
+
+  fn gbt_regression() -> Box<tform::DefaultNode> {
+    let ensemble = tform::ensemble::Ensemble::try_new(regression_trees(vec![[1.0, 2.0, 3.0], [3.0, 4.0, 5.0]]), vec![1.0, 0.5]).unwrap();
+    let model = tform::gbt_regression::GbtRegressionModel::new(ensemble, 2);
+    Box::new(tform::gbt_regression::GbtRegression::new(String::from("gbt"),
+                                                       String::from("features"),
+                                                       String::from("prediction"),
+                                                       model))
+  }
+
+  fn random_forest_classifier() -> Box<tform::DefaultNode> {
+    let trees = vec![tree_nodes(vec![vec![8.0, 2.0], vec![1.0, 3.0], vec![0.0, 4.0]]),
+                     tree_nodes(vec![vec![2.0, 2.0], vec![0.0, 5.0], vec![1.0, 3.0]])];
+    let trees = trees.iter().map(|nodes| {
+      tform::decision_tree_classifier::DecisionTreeClassifierModel::try_new(nodes, 2, 2).unwrap()
+    }).collect();
+    let ensemble = tform::ensemble::Ensemble::try_new(trees, vec![1.0, 1.0]).unwrap();
+    let model = tform::random_forest_classifier::RandomForestClassifierModel::try_new(ensemble, 2, 2).unwrap();
+    Box::new(tform::random_forest_classifier::RandomForestClassifier::new(String::from("forest"),
+                                                                          String::from("features"),
+                                                                          String::from("prediction"),
+                                                                          Some(String::from("probability")),
+                                                                          Some(String::from("raw_prediction")),
+                                                                          model))
+  }
+
+  fn gbt_classifier() -> Box<tform::DefaultNode> {
+    let ensemble = tform::ensemble::Ensemble::try_new(regression_trees(vec![[1.0, -1.0, 0.0], [0.5, 0.5, -0.5]]), vec![1.0, 0.1]).unwrap();
+    let model = tform::gbt_classifier::GbtClassifierModel::new(ensemble, 2);
+    Box::new(tform::gbt_classifier::GbtClassifier::new(String::from("gbt"),
+                                                       String::from("features"),
+                                                       String::from("prediction"),
+                                                       Some(String::from("probability")),
+                                                       Some(String::from("raw_prediction")),
+                                                       model))
+  }
+
+  fn airbnb() -> Box<tform::DefaultNode> {
+    let state_indexer = tform::string_indexer::StringIndexer::new(String::from("state_indexer"),
+                                                                  String::from("state"),
+                                                                  String::from("state_index"),
+                                                                  tform::string_indexer::StringIndexerModel::new(vec![String::from("NY"), String::from("CA")]));
+    let state_encoder = tform::one_hot_encoder::OneHotEncoder::new(String::from("state_encoder"),
+                                                                   String::from("state_index"),
+                                                                   String::from("state_oh"),
+                                                                   tform::one_hot_encoder::OneHotEncoderModel::new(2));
+    let features_assembler = tform::vector_assembler::VectorAssembler::new(String::from("features_assembler"),
+                                                                           vec![String::from("continuous_scaled"),
+                                                                                String::from("room_type_oh"),
+                                                                                String::from("state_oh")],
+                                                                           String::from("features"));
+    let children: Vec<Box<tform::DefaultNode>> = vec![room_type_indexer(tform::HandleInvalid::Error),
+                                                      room_type_encoder(),
+                                                      Box::new(state_indexer),
+                                                      Box::new(state_encoder),
+                                                      continuous_assembler(),
+                                                      continuous_scaler(),
+                                                      Box::new(features_assembler),
+                                                      price_regression(vec![10.0, 20.0, 50.0, 30.0, 5.0, 40.0, 25.0], 12.5)];
 
+    Box::new(tform::pipeline::Pipeline::new(String::from("airbnb"), tform::pipeline::PipelineModel::new(children)))
+  }
+
+  fn golden_bundles() -> Vec<(dsl::Bundle, Box<tform::DefaultNode>)> {
+    let bundles: Vec<(&str, &str, Box<tform::DefaultNode>)> = vec![
+      ("string_indexer", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e50", room_type_indexer(tform::HandleInvalid::Error)),
+      ("one_hot_encoder", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e51", room_type_encoder()),
+      ("vector_assembler", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e52", continuous_assembler()),
+      ("standard_scaler", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e53", continuous_scaler()),
+      ("linear_regression", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e54", price_regression(vec![2.0, -1.0], 1.5)),
+      ("airbnb", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e55", airbnb()),
+      ("logistic_regression", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e56",
+       logistic_regression(tform::logistic_regression::LogisticRegressionModel::binary(dsl::DenseTensor::new(vec![2], vec![1.0, -1.0]), 0.0, 0.4))),
+      ("multinomial_logistic_regression", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e57",
+       logistic_regression(tform::logistic_regression::LogisticRegressionModel::multinomial(dsl::DenseTensor::new(vec![3, 2], vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+                                                                                            vec![0.0, 0.0, 1.0]))),
+      ("decision_tree_regression", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e58", decision_tree_regression()),
+      ("decision_tree_classifier", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e59", decision_tree_classifier()),
+      ("random_forest_regression", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e5a", random_forest_regression()),
+      ("random_forest_classifier", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e5b", random_forest_classifier()),
+      ("gbt_regression", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e5c", gbt_regression()),
+      ("gbt_classifier", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e5d", gbt_classifier()),
+      ("standard_scaler_with_mean", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e5e", standard_scaler(Some(vec![1.0, 2.0]), Some(vec![0.5, 0.0]))),
+      ("standard_scaler_mean_only", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e5f", standard_scaler(Some(vec![1.0, 2.0]), None)),
+      ("string_indexer_keep", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e60", room_type_indexer(tform::HandleInvalid::Keep)),
+      ("string_indexer_skip", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e61", room_type_indexer(tform::HandleInvalid::Skip)),
+      ("string_indexer_multi", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e62", categorical_indexer()),
+      ("one_hot_encoder_multi", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e63", categorical_encoder()),
+      ("one_hot_encoder_drop_last_keep", "8a2f5c0e-3b1d-4c6a-9e7f-0d1b2c3a4e64", room_type_encoder_drop_last_keep())
+    ];
+
+    bundles.into_iter().map(|(name, uid, node)| {
+      let bundle = dsl::Bundle::new(Uuid::parse_str(uid).unwrap(),
+                                    String::from(name),
+                                    dsl::Format::Concrete(dsl::ConcreteFormat::Json),
+                                    Version::parse("0.6.0").unwrap());
+      (bundle, node)
+    }).collect()
+  }
+
+  fn read_golden_files(path: &path::Path, prefix: &str, files: &mut HashMap<String, Vec<u8>>) {
+    for entry in fs::read_dir(path).unwrap() {
+      let entry = entry.unwrap();
+      let name = format!("{}{}", prefix, entry.file_name().to_str().unwrap());
+
+      if entry.file_type().unwrap().is_dir() {
+        read_golden_files(&entry.path(), &format!("{}/", name), files);
+      } else {
+        let mut buf = Vec::new();
+        fs::File::open(entry.path()).and_then(|mut f| f.read_to_end(&mut buf)).unwrap();
+        files.insert(name, buf);
+      }
+    }
+  }
+
+  fn load_golden(name: &str) -> Box<tform::DefaultNode> {
+    let registry = registry();
+    let builder = ser::FileBuilder::try_new(path::Path::new(BUNDLES).join(name)).unwrap();
     let ctx = ser::Context::new(Box::new(builder), &registry);
-    let ctx2 = ctx.try_next("root").unwrap();
-    let node = ctx2.read_node().unwrap();
+
+    ctx.read_bundle().map(|(_, node)| node).unwrap()
+  }
+
+  /// Regenerate the bundles in tests/bundles after changing the writer or
+  /// the fixtures above with `cargo test -- --ignored write_golden_bundles`.
+  #[test]
+  #[ignore]
+  fn write_golden_bundles() {
+    let registry = registry();
+
+    for (bundle, node) in golden_bundles() {
+      let path = path::Path::new(BUNDLES).join(bundle.name());
+      if path.exists() {
+        fs::remove_dir_all(&path).unwrap();
+      }
+
+      let builder = ser::FileBuilder::try_new(&path).unwrap();
+      let ctx = ser::Context::new(Box::new(builder), &registry);
+      ctx.write_bundle(&bundle, &node).unwrap();
+    }
+  }
+
+  #[test]
+  fn test_golden_bundles_up_to_date() {
+    let registry = registry();
+
+    for (bundle, node) in golden_bundles() {
+      let builder = ser::MemoryBuilder::new();
+      let ctx = ser::Context::new(Box::new(builder.clone()), &registry);
+      ctx.write_bundle(&bundle, &node).unwrap();
+
+      let mut files = HashMap::new();
+      read_golden_files(&path::Path::new(BUNDLES).join(bundle.name()), "", &mut files);
+
+      assert!(builder.to_map() == files, "tests/bundles/{} is out of date", bundle.name());
+    }
+  }
+
+  #[test]
+  fn test_golden_string_indexer() {
+    let node = load_golden("string_indexer");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_strings(String::from("room_type"), vec![String::from("Shared room"),
+                                                           String::from("Entire home/apt"),
+                                                           String::from("Private room")]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_ints("room_type_index").unwrap(), &[2, 0, 1]);
+  }
+
+  fn room_types_with_unseen() -> frame::LeapFrame {
+    let mut frame = frame::LeapFrame::with_size(4);
+    frame.try_with_strings(String::from("room_type"), vec![String::from("Shared room"),
+                                                           String::from("Treehouse"),
+                                                           String::from("Private room"),
+                                                           String::from("Boat")]).unwrap();
+    frame.try_with_doubles(String::from("price"), vec![50.0, 80.0, 100.0, 120.0]).unwrap();
+    frame
+  }
+
+  #[test]
+  fn test_golden_string_indexer_error() {
+    let node = load_golden("string_indexer");
+    let mut frame = room_types_with_unseen();
+
+    assert!(node.transform(&mut frame).is_err());
+  }
+
+  #[test]
+  fn test_golden_string_indexer_keep() {
+    let node = load_golden("string_indexer_keep");
+    let mut frame = room_types_with_unseen();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_ints("room_type_index").unwrap(), &[2, 3, 1, 3]);
+    assert!(frame.skipped_rows().is_empty());
+  }
+
+  #[test]
+  fn test_golden_string_indexer_skip() {
+    let node = load_golden("string_indexer_skip");
+    let mut frame = room_types_with_unseen();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.size(), 2);
+    assert_eq!(frame.get_ints("room_type_index").unwrap(), &[2, 1]);
+    assert_eq!(frame.get_doubles("price").unwrap(), &[50.0, 100.0]);
+    assert_eq!(frame.skipped_rows(), &[1, 3]);
+  }
+
+  #[test]
+  fn test_golden_string_indexer_multi() {
+    let node = load_golden("string_indexer_multi");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_strings(String::from("room_type"), vec![String::from("Shared room"),
+                                                           String::from("Treehouse"),
+                                                           String::from("Entire home/apt")]).unwrap();
+    frame.try_with_strings(String::from("state"), vec![String::from("CA"),
+                                                       String::from("NY"),
+                                                       String::from("NY")]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(node.create_shape().get_io("input1", "output1").map(|(i, o)| (i.name(), o.name())),
+               Some(("state", "state_index")));
+    assert_eq!(frame.get_ints("room_type_index").unwrap(), &[2, 0]);
+    assert_eq!(frame.get_ints("state_index").unwrap(), &[1, 0]);
+    assert_eq!(frame.skipped_rows(), &[1]);
+  }
+
+  #[test]
+  fn test_golden_one_hot_encoder() {
+    let node = load_golden("one_hot_encoder");
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_ints(String::from("room_type_index"), vec![1, 2]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_sparse_double_tensors("room_type_oh").unwrap(),
+               &[dsl::SparseTensor::new(vec![3], vec![1], vec![1.0]),
+                 dsl::SparseTensor::new(vec![3], vec![2], vec![1.0])]);
+
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_ints(String::from("room_type_index"), vec![3]).unwrap();
+    assert!(node.transform(&mut frame).is_err());
+
+    // Spark writes indices as doubles
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_doubles(String::from("room_type_index"), vec![2.0]).unwrap();
+    node.input_schema().try_validate(&frame.schema()).unwrap();
+    node.transform(&mut frame).unwrap();
+    assert_eq!(frame.get_sparse_double_tensors("room_type_oh").unwrap(), &[dsl::SparseTensor::new(vec![3], vec![2], vec![1.0])]);
+
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_strings(String::from("room_type_index"), vec![String::from("2")]).unwrap();
+    assert!(node.input_schema().try_validate(&frame.schema()).is_err());
+  }
+
+  #[test]
+  fn test_null_labels() {
+    let room_types = || {
+      let mut frame = frame::LeapFrame::with_size(2);
+      let col = frame::Col::from_strings(String::from("room_type"), vec![String::from("Shared room"), String::new()]).
+        with_validity(vec![true, false]);
+      frame.try_with_col(col).unwrap();
+      frame
+    };
+
+    let mut frame = room_types();
+    load_golden("string_indexer_keep").transform(&mut frame).unwrap();
+    assert_eq!(frame.get_ints("room_type_index").unwrap(), &[2, 3]);
+
+    let mut frame = room_types();
+    assert!(load_golden("string_indexer").transform(&mut frame).is_err());
+
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_col(frame::Col::from_ints(String::from("room_type_index"), vec![1, 0]).with_validity(vec![false, true])).unwrap();
+    load_golden("one_hot_encoder_drop_last_keep").transform(&mut frame).unwrap();
+    assert_eq!(frame.get_sparse_double_tensors("room_type_oh").unwrap(),
+               &[dsl::SparseTensor::new(vec![3], vec![], vec![]),
+                 dsl::SparseTensor::new(vec![3], vec![0], vec![1.0])]);
+  }
+
+  #[test]
+  fn test_golden_one_hot_encoder_drop_last_keep() {
+    let node = load_golden("one_hot_encoder_drop_last_keep");
+    let mut frame = frame::LeapFrame::with_size(4);
+    frame.try_with_doubles(String::from("room_type_index"), vec![0.0, 2.0, 3.0, 1.5]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    // the invalid category is last, so drop_last encodes it as zeros
+    assert_eq!(&*frame.get_dense_double_tensors("room_type_oh").unwrap(),
+               &[dsl::DenseTensor::new(vec![3], vec![1.0, 0.0, 0.0]),
+                 dsl::DenseTensor::new(vec![3], vec![0.0, 0.0, 1.0]),
+                 dsl::DenseTensor::new(vec![3], vec![0.0, 0.0, 0.0]),
+                 dsl::DenseTensor::new(vec![3], vec![0.0, 0.0, 0.0])]);
+  }
+
+  #[test]
+  fn test_golden_one_hot_encoder_multi() {
+    let node = load_golden("one_hot_encoder_multi");
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_ints(String::from("room_type_index"), vec![2]).unwrap();
+    frame.try_with_ints(String::from("state_index"), vec![0]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(&*frame.get_dense_double_tensors("room_type_oh").unwrap(), &[dsl::DenseTensor::new(vec![3], vec![0.0, 0.0, 1.0])]);
+    assert_eq!(&*frame.get_dense_double_tensors("state_oh").unwrap(), &[dsl::DenseTensor::new(vec![2], vec![1.0, 0.0])]);
+  }
+
+  #[test]
+  fn test_golden_vector_assembler() {
+    let node = load_golden("vector_assembler");
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_doubles(String::from("bathrooms"), vec![2.0, 1.0]).unwrap();
+    frame.try_with_doubles(String::from("bedrooms"), vec![3.0, 1.0]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_double_tensors("continuous").unwrap(),
+               &[dsl::DenseTensor::new(vec![2], vec![2.0, 3.0]),
+                 dsl::DenseTensor::new(vec![2], vec![1.0, 1.0])]);
+  }
+
+  #[test]
+  fn test_vector_assembler_sparse() {
+    let node = load_golden("vector_assembler");
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_doubles(String::from("bathrooms"), vec![2.0, 0.0]).unwrap();
+    frame.try_with_sparse_double_tensors(String::from("bedrooms"), vec![dsl::SparseTensor::new(vec![3], vec![2], vec![1.0]),
+                                                                         dsl::SparseTensor::new(vec![3], vec![], vec![])]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_sparse_double_tensors("continuous").unwrap(),
+               &[dsl::SparseTensor::new(vec![4], vec![0, 3], vec![2.0, 1.0]),
+                 dsl::SparseTensor::new(vec![4], vec![], vec![])]);
+    assert!(node.input_schema().try_validate(&frame.schema()).is_ok());
+
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_ints(String::from("bathrooms"), vec![2]).unwrap();
+    frame.try_with_strings(String::from("bedrooms"), vec![String::from("3")]).unwrap();
+    match node.input_schema().try_validate(&frame.schema()) {
+      Err(frame::Error::InvalidType(msg)) => assert_eq!(msg, "bedrooms: expected numeric or tensor<numeric>, got string"),
+      r => panic!("Expected a mistyped bedrooms column, got {:?}", r)
+    }
+  }
+
+  #[test]
+  fn test_vector_assembler_sizes() {
+    let node = load_golden("vector_assembler");
+
+    // the size of a column is taken from its first valid row
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_doubles(String::from("bathrooms"), vec![2.0, 1.0]).unwrap();
+    frame.try_with_col(frame::Col::from_double_tensors(String::from("bedrooms"), vec![dsl::DenseTensor::new(vec![2], vec![]),
+                                                                                      dsl::DenseTensor::new(vec![2], vec![3.0, 4.0])]).
+                       with_validity(vec![false, true])).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    let continuous = frame.get_double_tensors("continuous").unwrap();
+    assert_eq!(continuous[0].dimensions(), &[3]);
+    assert!(continuous[0].values()[0] == 2.0 && continuous[0].values()[1..].iter().all(|x| x.is_nan()));
+    assert_eq!(continuous[1], dsl::DenseTensor::new(vec![3], vec![1.0, 3.0, 4.0]));
+
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_doubles(String::from("bathrooms"), vec![2.0, 1.0]).unwrap();
+    frame.try_with_double_tensors(String::from("bedrooms"), vec![dsl::DenseTensor::new(vec![2], vec![3.0, 4.0]),
+                                                                 dsl::DenseTensor::new(vec![3], vec![3.0, 4.0, 5.0])]).unwrap();
+    match node.transform(&mut frame) {
+      Err(frame::Error::TransformError(msg)) => assert_eq!(msg, "Column bedrooms has a tensor of size 3 in row 1, expected 2"),
+      r => panic!("Expected mismatched tensor sizes, got {:?}", r)
+    }
+
+    let mut frame = frame::LeapFrame::with_size(0);
+    frame.try_with_doubles(String::from("bathrooms"), vec![]).unwrap();
+    frame.try_with_double_tensors(String::from("bedrooms"), vec![]).unwrap();
+    node.transform(&mut frame).unwrap();
+    assert!(frame.get_double_tensors("continuous").unwrap().is_empty());
 
     let mut frame = frame::LeapFrame::with_size(1);
     frame.try_with_doubles(String::from("bathrooms"), vec![2.0]).unwrap();
-    frame.try_with_doubles(String::from("bedrooms"), vec![3.0]).unwrap();
-    frame.try_with_doubles(String::from("security_deposit"), vec![50.0]).unwrap();
-    frame.try_with_doubles(String::from("cleaning_fee"), vec![30.0]).unwrap();
-    frame.try_with_doubles(String::from("extra_people"), vec![0.0]).unwrap();
-    frame.try_with_doubles(String::from("number_of_reviews"), vec![23.0]).unwrap();
-    frame.try_with_doubles(String::from("square_feet"), vec![1200.0]).unwrap();
-    frame.try_with_doubles(String::from("review_scores_rating"), vec![93.0]).unwrap();
+    frame.try_with_strings(String::from("bedrooms"), vec![String::from("3")]).unwrap();
+    match node.transform(&mut frame) {
+      Err(frame::Error::TransformError(msg)) => assert_eq!(msg, "Column bedrooms is not numeric or a tensor of numbers"),
+      r => panic!("Expected a string column to be rejected, got {:?}", r)
+    }
+  }
 
-    frame.try_with_strings(String::from("cancellation_policy"), vec![String::from("strict")]).unwrap();
-    frame.try_with_strings(String::from("host_is_superhost"), vec![String::from("1.0")]).unwrap();
-    frame.try_with_strings(String::from("instant_bookable"), vec![String::from("1.0")]).unwrap();
-    frame.try_with_strings(String::from("room_type"), vec![String::from("Entire home/apt")]).unwrap();
-    frame.try_with_strings(String::from("state"), vec![String::from("NY")]).unwrap();
+  #[test]
+  fn test_golden_standard_scaler() {
+    let node = load_golden("standard_scaler");
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_double_tensors(String::from("continuous"), vec![dsl::DenseTensor::new(vec![2], vec![2.0, 3.0])]).unwrap();
+    node.transform(&mut frame).unwrap();
 
+    assert_eq!(frame.get_double_tensors("continuous_scaled").unwrap(),
+               &[dsl::DenseTensor::new(vec![2], vec![4.0, 1.5])]);
+  }
+
+  #[test]
+  fn test_golden_standard_scaler_with_mean() {
+    for &(name, expected) in [("standard_scaler_with_mean", [2.0, 0.0]), ("standard_scaler_mean_only", [1.0, 1.0])].iter() {
+      let node = load_golden(name);
+      let mut frame = frame::LeapFrame::with_size(1);
+      frame.try_with_double_tensors(String::from("continuous"), vec![dsl::DenseTensor::new(vec![2], vec![2.0, 3.0])]).unwrap();
+      node.transform(&mut frame).unwrap();
+
+      assert_eq!(frame.get_double_tensors("continuous_scaled").unwrap(),
+                 &[dsl::DenseTensor::new(vec![2], expected.to_vec())]);
+
+      let mut frame = frame::LeapFrame::with_size(1);
+      frame.try_with_double_tensors(String::from("continuous"), vec![dsl::DenseTensor::new(vec![3], vec![2.0, 3.0, 4.0])]).unwrap();
+      assert!(node.transform(&mut frame).is_err());
+    }
+  }
+
+  #[test]
+  fn test_standard_scaler_dimension_mismatch() {
+    let registry = registry();
+    let ctx = ser::Context::new(Box::new(ser::MemoryBuilder::new()), &registry);
+
+    let mut model = dsl::Model::new(String::from("standard_scaler"), HashMap::new());
+    model.with_attr("mean", dsl::Attribute::Tensor(dsl::TensorValue::Double(dsl::DenseTensor::new(vec![2], vec![1.0, 2.0])))).
+      with_attr("std", dsl::Attribute::Tensor(dsl::TensorValue::Double(dsl::DenseTensor::new(vec![3], vec![1.0, 1.0, 1.0]))));
+    assert!(tform::standard_scaler::OP.load_model(&model, &ctx).is_err());
+
+    let empty = dsl::Model::new(String::from("standard_scaler"), HashMap::new());
+    assert!(tform::standard_scaler::OP.load_model(&empty, &ctx).is_err());
+  }
+
+  #[test]
+  fn test_string_indexer_missing_labels() {
+    let registry = registry();
+    let ctx = ser::Context::new(Box::new(ser::MemoryBuilder::new()), &registry);
+
+    let empty = dsl::Model::new(String::from("string_indexer"), HashMap::new());
+    match tform::string_indexer::OP.load_model(&empty, &ctx) {
+      Err(ser::Error::InvalidModel(msg)) => assert_eq!(msg, "Missing string list attribute labels or labels_length"),
+      _ => panic!("Expected an InvalidModel error")
+    }
+
+    let mut model = dsl::Model::new(String::from("string_indexer"), HashMap::new());
+    model.with_attr("labels_length", dsl::Attribute::Basic(dsl::BasicValue::Int(2))).
+      with_attr("labels_array_0", dsl::Attribute::Array(dsl::VectorValue::String(vec![String::from("a")])));
+    match tform::string_indexer::OP.load_model(&model, &ctx) {
+      Err(ser::Error::InvalidModel(msg)) => assert_eq!(msg, "Missing string list attribute labels_array_1"),
+      _ => panic!("Expected an InvalidModel error")
+    }
+  }
+
+  #[test]
+  fn test_golden_linear_regression() {
+    let node = load_golden("linear_regression");
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![2], vec![4.0, 1.5])]).unwrap();
     node.transform(&mut frame).unwrap();
 
-    let r = frame.get_doubles("price_prediction").and_then(|x| x.first()).unwrap();
+    assert_eq!(frame.get_doubles("price_prediction").unwrap(), &[8.0]);
 
-    assert_eq!(*r, 236.76099900182078);
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_sparse_double_tensors(String::from("features"), vec![dsl::SparseTensor::new(vec![2], vec![0], vec![4.0])]).unwrap();
+    node.transform(&mut frame).unwrap();
+    assert_eq!(frame.get_doubles("price_prediction").unwrap(), &[9.5]);
+
+    for features in [dsl::SparseTensor::new(vec![3], vec![2], vec![4.0]), dsl::SparseTensor::new(vec![1], vec![], vec![])].iter() {
+      let mut frame = frame::LeapFrame::with_size(1);
+      frame.try_with_sparse_double_tensors(String::from("features"), vec![features.clone()]).unwrap();
+
+      match node.transform(&mut frame) {
+        Err(frame::Error::TransformError(msg)) => assert_eq!(msg, format!("Expected 2 features, got {}", features.dense_len())),
+        r => panic!("expected a TransformError, got {:?}", r)
+      }
+    }
   }
 
   #[test]
-  fn test_airbnb_c() {
-    let path = "/tmp/model";
-    let c_path = ffi::CString::new(path).unwrap();
+  fn test_golden_logistic_regression() {
+    let node = load_golden("logistic_regression");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![2], vec![2.0, 1.0]),
+                                                                 dsl::DenseTensor::new(vec![2], vec![1.0, 1.0]),
+                                                                 dsl::DenseTensor::new(vec![2], vec![0.0, 1.0])]).unwrap();
+    node.transform(&mut frame).unwrap();
 
-    let c_transformer = c::mleap_transformer_load(c_path.as_ptr());
-    let c_frame = c::mleap_frame_with_size(1);
+    let p = 1.0 / (1.0 + (-1.0f64).exp());
+    let q = 1.0 / (1.0 + 1.0f64.exp());
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[1.0, 1.0, 0.0]);
+    assert_eq!(frame.get_double_tensors("raw_prediction").unwrap(),
+               &[dsl::DenseTensor::new(vec![2], vec![-1.0, 1.0]),
+                 dsl::DenseTensor::new(vec![2], vec![-0.0, 0.0]),
+                 dsl::DenseTensor::new(vec![2], vec![1.0, -1.0])]);
+    assert_eq!(frame.get_double_tensors("probability").unwrap(),
+               &[dsl::DenseTensor::new(vec![2], vec![1.0 - p, p]),
+                 dsl::DenseTensor::new(vec![2], vec![0.5, 0.5]),
+                 dsl::DenseTensor::new(vec![2], vec![1.0 - q, q])]);
+  }
+
+  #[test]
+  fn test_golden_multinomial_logistic_regression() {
+    let node = load_golden("multinomial_logistic_regression");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![2], vec![3.0, 1.0]),
+                                                                 dsl::DenseTensor::new(vec![2], vec![0.0, 2.0]),
+                                                                 dsl::DenseTensor::new(vec![2], vec![0.0, 0.0])]).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[0.0, 1.0, 2.0]);
+    assert_eq!(frame.get_double_tensors("raw_prediction").unwrap()[0],
+               dsl::DenseTensor::new(vec![3], vec![3.0, 1.0, 1.0]));
+
+    let probability = &frame.get_double_tensors("probability").unwrap()[2];
+    let e = 1.0f64.exp();
+    for (a, b) in probability.values().iter().zip([1.0 / (e + 2.0), 1.0 / (e + 2.0), e / (e + 2.0)].iter()) {
+      assert!((a - b).abs() < 1e-12);
+    }
+  }
+
+  #[test]
+  fn test_logistic_regression_feature_count() {
+    for name in ["logistic_regression", "multinomial_logistic_regression"].iter() {
+      let node = load_golden(name);
+
+      for features in [vec![1.0], vec![], vec![1.0, 2.0, 3.0]].iter() {
+        let mut frame = frame::LeapFrame::with_size(1);
+        frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![features.len()], features.clone())]).unwrap();
+
+        match node.transform(&mut frame) {
+          Err(frame::Error::TransformError(msg)) => assert_eq!(msg, format!("Expected 2 features, got {}", features.len())),
+          r => panic!("{}: expected a TransformError, got {:?}", name, r)
+        }
+      }
+    }
+  }
+
+  fn tree_features() -> Vec<dsl::DenseTensor<f64>> {
+    vec![dsl::DenseTensor::new(vec![2], vec![1.0, 2.0]),
+         dsl::DenseTensor::new(vec![2], vec![1.5, 1.0]),
+         dsl::DenseTensor::new(vec![2], vec![3.0, 0.0])]
+  }
+
+  #[test]
+  fn test_golden_decision_tree_regression() {
+    let node = load_golden("decision_tree_regression");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[1.0, 2.0, 3.0]);
+
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![1], vec![1.0])]).unwrap();
+    assert!(node.transform(&mut frame).is_err());
+  }
+
+  #[test]
+  fn test_golden_decision_tree_classifier() {
+    let node = load_golden("decision_tree_classifier");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[0.0, 1.0, 0.0]);
+    assert_eq!(frame.get_double_tensors("raw_prediction").unwrap()[0], dsl::DenseTensor::new(vec![2], vec![8.0, 2.0]));
+    assert_eq!(frame.get_double_tensors("probability").unwrap(),
+               &[dsl::DenseTensor::new(vec![2], vec![0.8, 0.2]),
+                 dsl::DenseTensor::new(vec![2], vec![0.25, 0.75]),
+                 dsl::DenseTensor::new(vec![2], vec![0.0, 0.0])]);
+  }
+
+  #[test]
+  fn test_golden_random_forest_regression() {
+    let node = load_golden("random_forest_regression");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[2.0, 3.0, 4.0]);
+  }
+
+  #[test]
+  fn test_golden_gbt_regression() {
+    let node = load_golden("gbt_regression");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[2.5, 4.0, 5.5]);
+  }
+
+  #[test]
+  fn test_golden_random_forest_classifier() {
+    let node = load_golden("random_forest_classifier");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[0.0, 1.0, 1.0]);
+    assert_eq!(frame.get_double_tensors("raw_prediction").unwrap(),
+               &[dsl::DenseTensor::new(vec![2], vec![1.3, 0.7]),
+                 dsl::DenseTensor::new(vec![2], vec![0.25, 1.75]),
+                 dsl::DenseTensor::new(vec![2], vec![0.25, 1.75])]);
+    assert_eq!(frame.get_double_tensors("probability").unwrap()[1],
+               dsl::DenseTensor::new(vec![2], vec![0.125, 0.875]));
+  }
+
+  #[test]
+  fn test_golden_gbt_classifier() {
+    let node = load_golden("gbt_classifier");
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[1.0, 0.0, 0.0]);
+
+    let margins: [f64; 3] = [1.05, -0.95, -0.05];
+    let raw = frame.get_double_tensors("raw_prediction").unwrap();
+    let probability = frame.get_double_tensors("probability").unwrap();
+    for ((m, r), p) in margins.iter().zip(raw.iter()).zip(probability.iter()) {
+      let p1 = 1.0 / (1.0 + (-2.0 * m).exp());
+      assert!((r.values()[1] - m).abs() < 1e-12 && (r.values()[0] + m).abs() < 1e-12);
+      assert!((p.values()[1] - p1).abs() < 1e-12 && (p.values()[0] - (1.0 - p1)).abs() < 1e-12);
+    }
+  }
+
+  #[test]
+  fn test_proto_decision_tree() {
+    let registry = registry();
+    let builder = ser::MemoryBuilder::new();
+    let ctx = ser::Context::new(Box::new(builder.clone()), &registry);
+    let format = dsl::Format::Concrete(dsl::ConcreteFormat::Proto);
+    ctx.write_bundle(&bundle(format), &decision_tree_regression()).unwrap();
+
+    assert!(builder.to_map().contains_key("root/tree.pb"));
+
+    let (_, node) = ctx.read_bundle().unwrap();
+    let mut frame = frame::LeapFrame::with_size(3);
+    frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[1.0, 2.0, 3.0]);
+  }
+
+  struct ProtoPolicy;
+
+  impl ser::FormatPolicy for ProtoPolicy {
+    fn format(&self, _model: &dsl::Model) -> dsl::ConcreteFormat { dsl::ConcreteFormat::Proto }
+  }
+
+  #[test]
+  fn test_mixed_decision_tree() {
+    let registry = registry();
+    let proto_policy = ProtoPolicy;
+    let policies: [(&ser::FormatPolicy, &str, &str); 2] = [(&ser::DEFAULT_POLICY, "root/model.json", "root/tree.json"),
+                                                           (&proto_policy, "root/model.pb", "root/tree.pb")];
+
+    for &(policy, model, tree) in policies.iter() {
+      let builder = ser::MemoryBuilder::new();
+      let ctx = ser::Context::new(Box::new(builder.clone()), &registry).with_policy(policy);
+      ctx.write_bundle(&bundle(dsl::Format::Mixed), &decision_tree_regression()).unwrap();
+
+      let entries = builder.to_map();
+      assert!(entries.contains_key(model) && entries.contains_key(tree));
+      assert_eq!(entries.keys().filter(|k| k.starts_with("root/tree.")).count(), 1);
+
+      let (_, node) = ctx.read_bundle().unwrap();
+      let mut frame = frame::LeapFrame::with_size(3);
+      frame.try_with_double_tensors(String::from("features"), tree_features()).unwrap();
+      node.transform(&mut frame).unwrap();
+
+      assert_eq!(frame.get_doubles("prediction").unwrap(), &[1.0, 2.0, 3.0]);
+    }
+  }
+
+  #[test]
+  fn test_airbnb() {
+    let node = load_golden("airbnb");
+
+    let mut frame = frame::LeapFrame::with_size(2);
+    frame.try_with_doubles(String::from("bathrooms"), vec![2.0, 1.0]).unwrap();
+    frame.try_with_doubles(String::from("bedrooms"), vec![3.0, 1.0]).unwrap();
+    frame.try_with_strings(String::from("room_type"), vec![String::from("Entire home/apt"), String::from("Private room")]).unwrap();
+    frame.try_with_strings(String::from("state"), vec![String::from("NY"), String::from("CA")]).unwrap();
+
+    node.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("price_prediction").unwrap(), &[172.5, 97.5]);
+    assert_eq!(frame.get_sparse_double_tensors("features").unwrap()[1].indices(), &[0, 1, 3, 6]);
+  }
+
+  #[test]
+  fn test_airbnb_rows() {
+    let node = load_golden("airbnb");
+    let rows = vec![frame::Row::new(vec![frame::Value::from("Entire home/apt"), frame::Value::from("NY"), frame::Value::from(2.0), frame::Value::from(3.0)]),
+                    frame::Row::new(vec![frame::Value::from("Private room"), frame::Value::from("CA"), frame::Value::from(1.0), frame::Value::from(1.0)])];
+
+    let mut frame = frame::LeapFrame::from_rows(&node.input_schema(), rows).unwrap();
+    node.transform(&mut frame).unwrap();
+
+    frame.select(&["price_prediction"]).unwrap();
+    let predictions: Vec<Option<f64>> = frame.rows().map(|row| row.get(0).and_then(|v| v.get_double())).collect();
+    assert_eq!(predictions, vec![Some(172.5), Some(97.5)]);
+  }
+
+  #[test]
+  fn test_airbnb_csv() {
+    let node = load_golden("airbnb");
+    let csv = "room_type,state,bathrooms,bedrooms\n\
+               Entire home/apt,NY,2.0,3.0\n\
+               Private room,CA,1.0,1.0\n\
+               Entire home/apt,NY,2.0,3.0\n";
+    let reader = frame::csv::CsvReader::new(csv.as_bytes(), node.input_schema(), 2).unwrap();
+    let mut writer = frame::csv::CsvWriter::new(Vec::new(), vec![String::from("price_prediction")], frame::csv::TensorFormat::Json);
+
+    for chunk in reader {
+      let mut frame = chunk.unwrap();
+      node.transform(&mut frame).unwrap();
+      writer.write_frame(&frame).unwrap();
+    }
+
+    assert_eq!(String::from_utf8(writer.into_inner().unwrap()).unwrap(), "price_prediction\n172.5\n97.5\n172.5\n");
+  }
+
+  #[test]
+  fn test_airbnb_schema() {
+    let node = load_golden("airbnb");
+    let input_schema = node.input_schema();
+
+    let inputs: Vec<(&str, String)> = input_schema.fields().iter().map(|f| (f.name(), f.data_type().to_string())).collect();
+    assert_eq!(inputs, vec![("room_type", String::from("string")),
+                            ("state", String::from("string")),
+                            ("bathrooms", String::from("numeric or tensor<numeric>")),
+                            ("bedrooms", String::from("numeric or tensor<numeric>"))]);
+    assert_eq!(node.output_schema().get_field("price_prediction").map(|f| f.data_type().to_string()),
+               Some(String::from("double")));
+
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_doubles(String::from("bathrooms"), vec![2.0]).unwrap().
+      try_with_doubles(String::from("bedrooms"), vec![3.0]).unwrap().
+      try_with_strings(String::from("room_type"), vec![String::from("Entire home/apt")]).unwrap();
+    match input_schema.try_validate(&frame.schema()) {
+      Err(frame::Error::NoSuchColumn(ref col)) => assert_eq!(col, "state"),
+      r => panic!("Expected a missing state column, got {:?}", r)
+    }
+
+    frame.try_with_ints(String::from("state"), vec![0]).unwrap();
+    assert!(input_schema.try_validate(&frame.schema()).is_err());
+  }
+
+  fn transform_airbnb_c(c_transformer: *mut Box<tform::DefaultNode>) -> [f64; 2] {
+    let c_frame = c::mleap_frame_with_size(2);
 
     let bathrooms = ffi::CString::new("bathrooms").unwrap();
     let bedrooms = ffi::CString::new("bedrooms").unwrap();
-    let security_deposit = ffi::CString::new("security_deposit").unwrap();
-    let cleaning_fee = ffi::CString::new("cleaning_fee").unwrap();
-    let extra_people = ffi::CString::new("extra_people").unwrap();
-    let number_of_reviews = ffi::CString::new("number_of_reviews").unwrap();
-    let square_feet = ffi::CString::new("square_feet").unwrap();
-    let review_scores_rating = ffi::CString::new("review_scores_rating").unwrap();
-
-    let bathrooms_doubles: Vec<f64> = vec![2.0];
-    let bedrooms_doubles: Vec<f64> = vec![3.0];
-    let security_deposit_doubles: Vec<f64> = vec![50.0];
-    let cleaning_fee_doubles: Vec<f64> = vec![30.0];
-    let extra_people_doubles: Vec<f64> = vec![0.0];
-    let number_of_reviews_doubles: Vec<f64> = vec![23.0];
-    let square_feet_doubles: Vec<f64> = vec![1200.0];
-    let review_scores_rating_doubles: Vec<f64> = vec![93.0];
-
-    bathrooms_doubles.as_ptr();
-    bedrooms_doubles.as_ptr();
-    security_deposit_doubles.as_ptr();
+    let bathrooms_doubles: Vec<f64> = vec![2.0, 1.0];
+    let bedrooms_doubles: Vec<f64> = vec![3.0, 1.0];
+
     c::mleap_frame_with_doubles(c_frame, bathrooms.as_ptr(), bathrooms_doubles.as_ptr());
     c::mleap_frame_with_doubles(c_frame, bedrooms.as_ptr(), bedrooms_doubles.as_ptr());
-    c::mleap_frame_with_doubles(c_frame, security_deposit.as_ptr(), security_deposit_doubles.as_ptr());
-    c::mleap_frame_with_doubles(c_frame, cleaning_fee.as_ptr(), cleaning_fee_doubles.as_ptr());
-    c::mleap_frame_with_doubles(c_frame, extra_people.as_ptr(), extra_people_doubles.as_ptr());
-    c::mleap_frame_with_doubles(c_frame, number_of_reviews.as_ptr(), number_of_reviews_doubles.as_ptr());
-    c::mleap_frame_with_doubles(c_frame, square_feet.as_ptr(), square_feet_doubles.as_ptr());
-    c::mleap_frame_with_doubles(c_frame, review_scores_rating.as_ptr(), review_scores_rating_doubles.as_ptr());
-
-    let strict = ffi::CString::new("strict").unwrap();
-    let s10 = ffi::CString::new("1.0").unwrap();
+
     let entire_home = ffi::CString::new("Entire home/apt").unwrap();
+    let private_room = ffi::CString::new("Private room").unwrap();
     let ny = ffi::CString::new("NY").unwrap();
+    let ca = ffi::CString::new("CA").unwrap();
 
-    let cp = vec![strict.as_ptr()];
-    let his = vec![s10.as_ptr()];
-    let ib = vec![s10.as_ptr()];
-    let rt = vec![entire_home.as_ptr()];
-    let states = vec![ny.as_ptr()];
+    let rt = vec![entire_home.as_ptr(), private_room.as_ptr()];
+    let states = vec![ny.as_ptr(), ca.as_ptr()];
 
-    let cancellation_policy = ffi::CString::new("cancellation_policy").unwrap();
-    let host_is_superhost = ffi::CString::new("host_is_superhost").unwrap();
-    let instant_bookable = ffi::CString::new("instant_bookable").unwrap();
     let room_type = ffi::CString::new("room_type").unwrap();
     let state = ffi::CString::new("state").unwrap();
 
-    c::mleap_frame_with_strings(c_frame, cancellation_policy.as_ptr(), cp.as_ptr());
-    c::mleap_frame_with_strings(c_frame, host_is_superhost.as_ptr(), his.as_ptr());
-    c::mleap_frame_with_strings(c_frame, instant_bookable.as_ptr(), ib.as_ptr());
     c::mleap_frame_with_strings(c_frame, room_type.as_ptr(), rt.as_ptr());
     c::mleap_frame_with_strings(c_frame, state.as_ptr(), states.as_ptr());
 
     c::mleap_transform(c_transformer, c_frame);
 
-    let mut buffer: [f64; 1] = [0.0];
+    let mut buffer: [f64; 2] = [0.0; 2];
     let price_prediction = ffi::CString::new("price_prediction").unwrap();
     c::mleap_frame_get_doubles(c_frame, price_prediction.as_ptr(), buffer.as_mut_ptr());
-    let r = buffer[0];
 
-    assert_eq!(r, 236.76099900182078);
+    c::mleap_transformer_free(c_transformer);
+    c::mleap_frame_free(c_frame);
+
+    buffer
+  }
+
+  #[test]
+  fn test_airbnb_c() {
+    let path = path::Path::new(BUNDLES).join("airbnb");
+    let c_path = ffi::CString::new(path.to_str().unwrap()).unwrap();
+
+    let c_transformer = c::mleap_transformer_load(c_path.as_ptr());
+    assert_eq!(transform_airbnb_c(c_transformer), [172.5, 97.5]);
+  }
+
+  #[test]
+  fn test_string_indexer_skip_c() {
+    let path = path::Path::new(BUNDLES).join("string_indexer_skip");
+    let c_path = ffi::CString::new(path.to_str().unwrap()).unwrap();
+    let c_transformer = c::mleap_transformer_load(c_path.as_ptr());
+    let c_frame = c::mleap_frame_with_size(3);
+
+    let boat = ffi::CString::new("Boat").unwrap();
+    let shared_room = ffi::CString::new("Shared room").unwrap();
+    let room_types = vec![shared_room.as_ptr(), boat.as_ptr(), boat.as_ptr()];
+    let room_type = ffi::CString::new("room_type").unwrap();
+
+    c::mleap_frame_with_strings(c_frame, room_type.as_ptr(), room_types.as_ptr());
+    c::mleap_transform(c_transformer, c_frame);
+
+    let mut skipped = vec![0; c::mleap_frame_get_skipped_rows_len(c_frame)];
+    assert_eq!(c::mleap_frame_get_skipped_rows(c_frame, skipped.as_mut_ptr()), 0);
 
+    assert_eq!(c::mleap_frame_get_size(c_frame), 1);
+    assert_eq!(skipped, vec![1, 2]);
+
+    c::mleap_frame_free(c_frame);
     c::mleap_transformer_free(c_transformer);
+  }
+
+  #[test]
+  fn test_airbnb_c_nullable() {
+    let path = path::Path::new(BUNDLES).join("airbnb");
+    let c_path = ffi::CString::new(path.to_str().unwrap()).unwrap();
+    let c_transformer = c::mleap_transformer_load(c_path.as_ptr());
+    let c_frame = c::mleap_frame_with_size(2);
+
+    let bathrooms = ffi::CString::new("bathrooms").unwrap();
+    let bedrooms = ffi::CString::new("bedrooms").unwrap();
+    let room_type = ffi::CString::new("room_type").unwrap();
+    let state = ffi::CString::new("state").unwrap();
+    let entire_home = ffi::CString::new("Entire home/apt").unwrap();
+    let ny = ffi::CString::new("NY").unwrap();
+
+    assert_eq!(c::mleap_frame_with_doubles_nullable(c_frame, bathrooms.as_ptr(), vec![2.0, 0.0].as_ptr(), vec![1, 0].as_ptr()), 0);
+    c::mleap_frame_with_doubles(c_frame, bedrooms.as_ptr(), vec![3.0, 1.0].as_ptr());
+    c::mleap_frame_with_strings(c_frame, room_type.as_ptr(), vec![entire_home.as_ptr(), entire_home.as_ptr()].as_ptr());
+    assert_eq!(c::mleap_frame_with_strings_nullable(c_frame, state.as_ptr(), vec![ny.as_ptr(), ny.as_ptr()].as_ptr(), vec![1, 1].as_ptr()), 0);
+
+    let mut validity = vec![2; 2];
+    assert_eq!(c::mleap_frame_get_validity(c_frame, bathrooms.as_ptr(), validity.as_mut_ptr()), 0);
+    assert_eq!(validity, vec![1, 0]);
+
+    // the null bathrooms are assembled as NaN
+    c::mleap_transform(c_transformer, c_frame);
+    unsafe {
+      let frame = c_frame.as_ref().unwrap();
+      assert_eq!(frame.schema().get_field("bathrooms").map(|f| f.nullable()), Some(true));
+      assert_eq!(frame.schema().get_field("state").map(|f| f.nullable()), Some(true));
+      assert_eq!(frame.get_doubles("price_prediction").unwrap()[0], 172.5);
+      assert!(frame.get_doubles("price_prediction").unwrap()[1].is_nan());
+    }
+
+    c::mleap_frame_free(c_frame);
+    c::mleap_transformer_free(c_transformer);
+  }
+
+  #[test]
+  fn test_sparse_double_tensor_c() {
+    let node = load_golden("vector_assembler");
+    let c_frame = c::mleap_frame_with_size(2);
+    unsafe {
+      let frame = c_frame.as_mut().unwrap();
+      frame.try_with_doubles(String::from("bathrooms"), vec![2.0, 0.0]).unwrap();
+      frame.try_with_sparse_double_tensors(String::from("bedrooms"), vec![dsl::SparseTensor::new(vec![3], vec![2], vec![1.0]),
+                                                                           dsl::SparseTensor::new(vec![3], vec![], vec![])]).unwrap();
+      node.transform(frame).unwrap();
+    }
+
+    let continuous = ffi::CString::new("continuous").unwrap();
+    let (mut dimensions_len, mut values_len) = (0, 0);
+    assert_eq!(c::mleap_frame_get_double_tensor_len(c_frame, continuous.as_ptr(), 0, &mut dimensions_len, &mut values_len), 0);
+    assert_eq!((dimensions_len, values_len), (1, 4));
+
+    let mut dimensions = vec![0; dimensions_len];
+    let mut values = vec![0.0; values_len];
+    assert_eq!(c::mleap_frame_get_double_tensor(c_frame, continuous.as_ptr(), 0, dimensions.as_mut_ptr(), values.as_mut_ptr()), 0);
+    assert_eq!(dimensions, vec![4]);
+    assert_eq!(values, vec![2.0, 0.0, 0.0, 1.0]);
+
+    let bathrooms = ffi::CString::new("bathrooms").unwrap();
+    let missing = ffi::CString::new("missing").unwrap();
+    assert_eq!(c::mleap_frame_get_double_tensor_len(c_frame, continuous.as_ptr(), 2, &mut dimensions_len, &mut values_len), -1);
+    assert_eq!(c::mleap_frame_get_double_tensor_len(c_frame, bathrooms.as_ptr(), 0, &mut dimensions_len, &mut values_len), -1);
+    assert_eq!(c::mleap_frame_get_double_tensor(c_frame, missing.as_ptr(), 0, dimensions.as_mut_ptr(), values.as_mut_ptr()), -1);
+    assert_eq!(c::mleap_frame_get_validity(c_frame, missing.as_ptr(), vec![0; 2].as_mut_ptr()), -1);
+    assert_eq!(c::mleap_frame_with_doubles_nullable(c_frame, bathrooms.as_ptr(), vec![1.0, 2.0].as_ptr(), vec![1, 1].as_ptr()), -1);
+
     c::mleap_frame_free(c_frame);
+    assert!(c::mleap_transformer_load_from_bytes(vec![0u8; 4].as_ptr(), 4).is_null());
+  }
+
+  #[test]
+  fn test_airbnb_c_zip() {
+    let mut files = HashMap::new();
+    read_golden_files(&path::Path::new(BUNDLES).join("airbnb"), "", &mut files);
+    let path = env::temp_dir().join(format!("mleap-airbnb-{}.zip", Uuid::new_v4()));
+    fs::write(&path, ser::MemoryBuilder::from_map(files).try_to_zip().unwrap()).unwrap();
+
+    let c_path = ffi::CString::new(path.to_str().unwrap()).unwrap();
+    let c_transformer = c::mleap_transformer_load(c_path.as_ptr());
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(transform_airbnb_c(c_transformer), [172.5, 97.5]);
+  }
+
+  #[test]
+  fn test_airbnb_c_from_bytes() {
+    let mut files = HashMap::new();
+    read_golden_files(&path::Path::new(BUNDLES).join("airbnb"), "", &mut files);
+    let bytes = ser::MemoryBuilder::from_map(files).try_to_zip().unwrap();
+
+    let c_transformer = c::mleap_transformer_load_from_bytes(bytes.as_ptr(), bytes.len());
+    assert_eq!(transform_airbnb_c(c_transformer), [172.5, 97.5]);
+  }
+
+  fn linear_regression(ctx: &ser::Context<Box<tform::DefaultNode>>,
+                       coefficients: Vec<f64>) -> Box<tform::DefaultNode> {
+    let mut model = dsl::Model::new(String::from("linear_regression"), HashMap::new());
+    model.with_attr("intercept", dsl::Attribute::Basic(dsl::BasicValue::Double(1.5))).
+      with_attr("coefficients", dsl::Attribute::Tensor(dsl::TensorValue::Double(dsl::DenseTensor::new(vec![coefficients.len()], coefficients))));
+    let shape = dsl::Shape::new(vec![dsl::Socket::new(String::from("features"), String::from("features"))],
+                                vec![dsl::Socket::new(String::from("prediction"), String::from("prediction"))]);
+    let node = dsl::Node::new(String::from("lr"), shape);
+
+    tform::linear_regression::OP.load_model(&model, ctx).and_then(|m| {
+      tform::linear_regression::OP.load(&node, m, ctx)
+    }).unwrap()
+  }
+
+  fn bundle(format: dsl::Format) -> dsl::Bundle {
+    dsl::Bundle::new(Uuid::new_v4(), String::from("lr"), format, Version::parse("0.6.0").unwrap())
+  }
+
+  #[test]
+  fn test_proto_bundle() {
+    let mut registry = ser::Registry::new();
+    registry.insert_op(tform::linear_regression::OP);
+
+    let builder = ser::MemoryBuilder::new();
+    let ctx = ser::Context::new(Box::new(builder.clone()), &registry);
+    let lr = linear_regression(&ctx, vec![2.0, -1.0]);
+
+    let format = dsl::Format::Concrete(dsl::ConcreteFormat::Proto);
+    ctx.write_bundle(&bundle(format), &lr).unwrap();
+
+    let entries = builder.to_map();
+    assert!(entries.contains_key("root/model.pb"));
+    assert!(entries.contains_key("root/node.pb"));
+
+    let (r_bundle, r_lr) = ctx.read_bundle().unwrap();
+    assert_eq!(*r_bundle.format(), format);
+
+    let mut frame = frame::LeapFrame::with_size(1);
+    frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![2], vec![3.0, 4.0])]).unwrap();
+    r_lr.transform(&mut frame).unwrap();
+
+    assert_eq!(frame.get_doubles("prediction").unwrap(), &[3.5]);
+  }
+
+  #[test]
+  fn test_mixed_bundle() {
+    let mut registry = ser::Registry::new();
+    registry.insert_op(tform::linear_regression::OP);
+    let policy = ser::TensorSizePolicy::new(2);
+
+    for &(size, file) in [(2, "root/model.json"), (3, "root/model.pb")].iter() {
+      let builder = ser::MemoryBuilder::new();
+      let ctx = ser::Context::new(Box::new(builder.clone()), &registry).with_policy(&policy);
+      let lr = linear_regression(&ctx, vec![1.0; size]);
+
+      ctx.write_bundle(&bundle(dsl::Format::Mixed), &lr).unwrap();
+      assert!(builder.to_map().contains_key(file));
+
+      let (_, r_lr) = ctx.read_bundle().unwrap();
+      let mut frame = frame::LeapFrame::with_size(1);
+      frame.try_with_double_tensors(String::from("features"), vec![dsl::DenseTensor::new(vec![size], vec![1.0; size])]).unwrap();
+      r_lr.transform(&mut frame).unwrap();
+
+      assert_eq!(frame.get_doubles("prediction").unwrap(), &[size as f64 + 1.5]);
+    }
   }
 }
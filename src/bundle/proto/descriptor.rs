@@ -0,0 +1,190 @@
+// Checks hand-written prost messages against the .proto files they
+// implement. Every field of a .proto message is encoded on its own, with its
+// number and wire type, decoded as the Rust message and encoded again; a
+// missing field, a wrong number or a wrong wire type changes the bytes.
+//
+// The parser reads the subset of proto3 used by the checked in files:
+// top-level messages, enums, oneofs, maps and services. Also built into the
+// tests of mleap-grpc.
+
+use prost::Message;
+use prost::DecodeError;
+use prost::encoding::encode_varint;
+
+pub struct Field {
+  pub name: String,
+  pub ty: String,
+  pub number: u64,
+  pub repeated: bool
+}
+
+pub struct Proto {
+  pub messages: Vec<(String, Vec<Field>)>,
+  pub enums: Vec<(String, Vec<(String, i32)>)>
+}
+
+fn tokens(src: &str) -> Vec<String> {
+  let mut tokens = Vec::new();
+
+  for line in src.lines() {
+    let line = match line.find("//") {
+      Some(i) => &line[..i],
+      None => line
+    };
+    let mut token = String::new();
+
+    for c in line.chars() {
+      if c.is_whitespace() || "{}=;<>,".contains(c) {
+        if !token.is_empty() { tokens.push(token.clone()); token.clear(); }
+        if !c.is_whitespace() { tokens.push(c.to_string()); }
+      } else {
+        token.push(c);
+      }
+    }
+    if !token.is_empty() { tokens.push(token); }
+  }
+
+  tokens
+}
+
+fn number(token: &str) -> u64 {
+  token.parse().unwrap_or_else(|_| panic!("expected a field number, got {}", token))
+}
+
+pub fn parse(src: &str) -> Proto {
+  let tokens = tokens(src);
+  let mut proto = Proto { messages: Vec::new(), enums: Vec::new() };
+  let mut i = 0;
+
+  while i < tokens.len() {
+    match tokens[i].as_str() {
+      "enum" => {
+        let name = tokens[i + 1].clone();
+        let mut values = Vec::new();
+        i += 3;
+        while tokens[i] != "}" {
+          values.push((tokens[i].clone(), number(&tokens[i + 2]) as i32));
+          i += 4;
+        }
+        proto.enums.push((name, values));
+        i += 1;
+      },
+      "message" => {
+        let name = tokens[i + 1].clone();
+        let mut fields = Vec::new();
+        let mut oneofs = 0;
+        i += 3;
+        loop {
+          match tokens[i].as_str() {
+            // the fields of a oneof are fields of the message
+            "oneof" => {
+              oneofs += 1;
+              i += 3;
+            },
+            "}" if oneofs > 0 => {
+              oneofs -= 1;
+              i += 1;
+            },
+            "}" => break,
+            "map" => {
+              fields.push(Field { name: tokens[i + 6].clone(), ty: String::from("map"), number: number(&tokens[i + 8]), repeated: false });
+              i += 10;
+            },
+            "repeated" => {
+              fields.push(Field { name: tokens[i + 2].clone(), ty: tokens[i + 1].clone(), number: number(&tokens[i + 4]), repeated: true });
+              i += 6;
+            },
+            _ => {
+              fields.push(Field { name: tokens[i + 1].clone(), ty: tokens[i].clone(), number: number(&tokens[i + 3]), repeated: false });
+              i += 5;
+            }
+          }
+        }
+        proto.messages.push((name, fields));
+        i += 1;
+      },
+      // only the messages of a service are checked
+      "service" => {
+        let mut depth = 0;
+        loop {
+          match tokens[i].as_str() {
+            "{" => depth += 1,
+            "}" if depth == 1 => break,
+            "}" => depth -= 1,
+            _ => { }
+          }
+          i += 1;
+        }
+        i += 1;
+      },
+      // syntax, package, import and option statements
+      _ => {
+        while tokens[i] != ";" { i += 1; }
+        i += 1;
+      }
+    }
+  }
+
+  proto
+}
+
+/// Encodes a non default value of the field, enums and messages (here
+/// anything that isn't a scalar) as 1 and as an empty message.
+pub fn encode_field(field: &Field, enums: &[String]) -> Vec<u8> {
+  let (wire_type, value): (u64, Vec<u8>) = match field.ty.as_str() {
+    "bool" | "int32" | "int64" | "uint32" | "uint64" => (0, vec![1]),
+    "double" => (1, 1.0f64.to_bits().to_le_bytes().to_vec()),
+    "float" => (5, 1.0f32.to_bits().to_le_bytes().to_vec()),
+    "string" => (2, b"x".to_vec()),
+    "bytes" => (2, vec![1]),
+    // enums of imported files are named with their package
+    ty if enums.iter().any(|e| ty.rsplit('.').next() == Some(e.as_str())) => (0, vec![1]),
+    _ => (2, vec![])
+  };
+  let mut buf = Vec::new();
+
+  if field.repeated && wire_type != 2 {
+    // repeated scalars are packed
+    encode_varint(field.number << 3 | 2, &mut buf);
+    encode_varint(value.len() as u64, &mut buf);
+  } else {
+    encode_varint(field.number << 3 | wire_type, &mut buf);
+    if wire_type == 2 { encode_varint(value.len() as u64, &mut buf); }
+  }
+  buf.extend_from_slice(&value);
+
+  buf
+}
+
+pub fn round_trip<M: Message + Default>(buf: &[u8]) -> Result<Vec<u8>, DecodeError> {
+  M::decode(buf).map(|m| m.encode_to_vec())
+}
+
+/// Checks the messages and enums of `src`, `imports` are the files it
+/// imports enums from. `messages` decodes and encodes again with the Rust
+/// message of a name, `enums` names the Rust variant of an enum value.
+pub fn check<F, G>(imports: &[&str], src: &str, messages: F, enums: G)
+  where F: Fn(&str, &[u8]) -> Option<Result<Vec<u8>, DecodeError>>,
+        G: Fn(&str, i32) -> Option<String> {
+  let proto = parse(src);
+  let enum_names: Vec<String> = imports.iter().map(|i| parse(i)).chain(Some(parse(src))).
+    flat_map(|p| p.enums.into_iter().map(|e| e.0)).collect();
+
+  for &(ref name, ref values) in proto.enums.iter() {
+    for &(ref value, number) in values.iter() {
+      let expected = value.replace('_', "").to_lowercase();
+      assert_eq!(enums(name, number).map(|v| v.to_lowercase()), Some(expected), "{} = {}", value, number);
+    }
+  }
+
+  for &(ref name, ref fields) in proto.messages.iter() {
+    for field in fields.iter() {
+      let buf = encode_field(field, &enum_names);
+      match messages(name, &buf) {
+        Some(Ok(read)) => assert_eq!(read, buf, "{}.{} = {}", name, field.name, field.number),
+        Some(Err(err)) => panic!("{}.{} = {}: {}", name, field.name, field.number, err),
+        None => panic!("no Rust message {}", name)
+      }
+    }
+  }
+}
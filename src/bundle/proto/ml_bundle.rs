@@ -0,0 +1,153 @@
+// Messages from the ml.bundle package of bundle-protobuf, written out by
+// hand so building does not depend on protoc. The wire contract is
+// proto/bundle.proto, test_proto_files checks both agree.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum BasicType {
+  Undefined = 0,
+  Boolean = 1,
+  Byte = 2,
+  Short = 3,
+  Int = 4,
+  Long = 5,
+  Float = 6,
+  Double = 7,
+  String = 8,
+  ByteString = 9
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum DataShapeType {
+  UndefinedShape = 0,
+  Scalar = 1,
+  List = 2,
+  Tensor = 3
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct DataType {
+  #[prost(enumeration="BasicType", tag="1")]
+  pub base: i32,
+  #[prost(enumeration="DataShapeType", tag="2")]
+  pub shape: i32
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Tensor {
+  #[prost(enumeration="BasicType", tag="1")]
+  pub base: i32,
+  #[prost(int32, repeated, tag="2")]
+  pub shape: Vec<i32>,
+  #[prost(bytes="vec", tag="3")]
+  pub value: Vec<u8>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Scalar {
+  #[prost(bool, tag="1")]
+  pub b: bool,
+  #[prost(string, tag="2")]
+  pub s: String,
+  #[prost(int32, tag="3")]
+  pub i: i32,
+  #[prost(int64, tag="4")]
+  pub l: i64,
+  #[prost(double, tag="5")]
+  pub f: f64,
+  #[prost(bytes="vec", tag="6")]
+  pub bs: Vec<u8>,
+  #[prost(message, optional, tag="7")]
+  pub t: Option<Tensor>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct List {
+  #[prost(bool, repeated, tag="1")]
+  pub b: Vec<bool>,
+  #[prost(string, repeated, tag="2")]
+  pub s: Vec<String>,
+  #[prost(int32, repeated, tag="3")]
+  pub i: Vec<i32>,
+  #[prost(int64, repeated, tag="4")]
+  pub l: Vec<i64>,
+  #[prost(double, repeated, tag="5")]
+  pub f: Vec<f64>,
+  #[prost(bytes="vec", repeated, tag="6")]
+  pub bs: Vec<Vec<u8>>
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum V {
+  #[prost(message, tag="1")]
+  S(Scalar),
+  #[prost(message, tag="2")]
+  L(List)
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Value {
+  #[prost(oneof="V", tags="1, 2")]
+  pub v: Option<V>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Attribute {
+  #[prost(message, optional, tag="1")]
+  pub data_type: Option<DataType>,
+  #[prost(message, optional, tag="2")]
+  pub value: Option<Value>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Model {
+  #[prost(string, tag="1")]
+  pub op: String,
+  #[prost(map="string, message", tag="2")]
+  pub attributes: ::std::collections::HashMap<String, Attribute>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Socket {
+  #[prost(string, tag="1")]
+  pub name: String,
+  #[prost(string, tag="2")]
+  pub port: String
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct NodeShape {
+  #[prost(message, repeated, tag="1")]
+  pub inputs: Vec<Socket>,
+  #[prost(message, repeated, tag="2")]
+  pub outputs: Vec<Socket>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Node {
+  #[prost(string, tag="1")]
+  pub name: String,
+  #[prost(message, optional, tag="2")]
+  pub shape: Option<NodeShape>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum Format {
+  Json = 0,
+  Protobuf = 1,
+  Mixed = 2
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Bundle {
+  #[prost(string, tag="1")]
+  pub uid: String,
+  #[prost(string, tag="2")]
+  pub name: String,
+  #[prost(enumeration="Format", tag="3")]
+  pub format: i32,
+  #[prost(string, tag="4")]
+  pub version: String
+}
@@ -0,0 +1,620 @@
+pub mod ml_bundle;
+pub mod tree;
+pub mod runtime;
+#[cfg(test)]
+mod descriptor;
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::result::Result;
+use prost::DecodeError;
+use uuid::Uuid;
+use semver::Version;
+use super::json::TryFrom;
+use super::dsl;
+
+use self::ml_bundle::{BasicType, DataShapeType};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+  WriteError(String),
+  ReadError(String)
+}
+
+impl From<DecodeError> for Error {
+  fn from(err: DecodeError) -> Self {
+    Error::ReadError(err.to_string())
+  }
+}
+
+fn basic_type(value: i32) -> BasicType {
+  ::std::convert::TryFrom::try_from(value).unwrap_or(BasicType::Undefined)
+}
+
+fn data_shape_type(value: i32) -> DataShapeType {
+  ::std::convert::TryFrom::try_from(value).unwrap_or(DataShapeType::UndefinedShape)
+}
+
+fn data_type(base: BasicType, shape: DataShapeType) -> ml_bundle::DataType {
+  ml_bundle::DataType {
+    base: base as i32,
+    shape: shape as i32
+  }
+}
+
+fn scalar_value(scalar: ml_bundle::Scalar) -> ml_bundle::Value {
+  ml_bundle::Value { v: Some(ml_bundle::V::S(scalar)) }
+}
+
+fn list_value(list: ml_bundle::List) -> ml_bundle::Value {
+  ml_bundle::Value { v: Some(ml_bundle::V::L(list)) }
+}
+
+// tensor values are packed big endian, strings and byte strings are
+// prefixed with their length
+fn pack_values<T, F>(values: &[T], f: F) -> Vec<u8>
+  where F: Fn(&T, &mut Vec<u8>) {
+    let mut buf = Vec::new();
+    for v in values.iter() {
+      f(v, &mut buf);
+    }
+    buf
+  }
+
+fn pack_bytes(v: &[u8], buf: &mut Vec<u8>) {
+  buf.extend_from_slice(&(v.len() as i32).to_be_bytes());
+  buf.extend_from_slice(v);
+}
+
+fn unpack_fixed<T, F>(buf: &[u8], width: usize, f: F) -> Result<Vec<T>, Error>
+  where F: Fn(&[u8]) -> T {
+    if buf.len() % width != 0 {
+      return Err(Error::ReadError(String::from("Invalid tensor value length")));
+    }
+
+    Ok(buf.chunks(width).map(f).collect())
+  }
+
+fn unpack_bytes(buf: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+  let mut values = Vec::new();
+  let mut offset = 0;
+
+  while offset < buf.len() {
+    if offset + 4 > buf.len() {
+      return Err(Error::ReadError(String::from("Invalid tensor value length")));
+    }
+
+    let len = i32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap());
+    offset += 4;
+
+    // a negative length would wrap around as a usize
+    let end = if len < 0 { None } else { offset.checked_add(len as usize) };
+    match end {
+      Some(end) if end <= buf.len() => {
+        values.push(buf[offset..end].to_vec());
+        offset = end;
+      },
+      _ => return Err(Error::ReadError(String::from("Invalid tensor value length")))
+    }
+  }
+
+  Ok(values)
+}
+
+fn dense<T>(dims: Vec<usize>, values: Vec<T>) -> Result<dsl::DenseTensor<T>, Error> {
+  let size = dims.iter().fold(Some(1usize), |n, d| n.and_then(|n| n.checked_mul(*d)));
+
+  if size == Some(values.len()) {
+    Ok(dsl::DenseTensor::new(dims, values))
+  } else {
+    Err(Error::ReadError(format!("Tensor of shape {:?} has {} values", dims, values.len())))
+  }
+}
+
+fn tensor<T, F>(base: BasicType, t: &dsl::DenseTensor<T>, f: F) -> ml_bundle::Tensor
+  where F: Fn(&T, &mut Vec<u8>) {
+    ml_bundle::Tensor {
+      base: base as i32,
+      shape: t.dimensions().iter().map(|d| *d as i32).collect(),
+      value: pack_values(t.values(), f)
+    }
+  }
+
+impl<'a> From<&'a dsl::TensorValue> for ml_bundle::Tensor {
+  fn from(value: &'a dsl::TensorValue) -> Self {
+    match value {
+      &dsl::TensorValue::Bool(ref t) => tensor(BasicType::Boolean, t, |v, buf| buf.push(*v as u8)),
+      &dsl::TensorValue::String(ref t) => tensor(BasicType::String, t, |v, buf| pack_bytes(v.as_bytes(), buf)),
+      &dsl::TensorValue::Byte(ref t) => tensor(BasicType::Byte, t, |v, buf| buf.extend_from_slice(&v.to_be_bytes())),
+      &dsl::TensorValue::Short(ref t) => tensor(BasicType::Short, t, |v, buf| buf.extend_from_slice(&v.to_be_bytes())),
+      &dsl::TensorValue::Int(ref t) => tensor(BasicType::Int, t, |v, buf| buf.extend_from_slice(&v.to_be_bytes())),
+      &dsl::TensorValue::Long(ref t) => tensor(BasicType::Long, t, |v, buf| buf.extend_from_slice(&v.to_be_bytes())),
+      &dsl::TensorValue::Float(ref t) => tensor(BasicType::Float, t, |v, buf| buf.extend_from_slice(&v.to_be_bytes())),
+      &dsl::TensorValue::Double(ref t) => tensor(BasicType::Double, t, |v, buf| buf.extend_from_slice(&v.to_be_bytes())),
+      &dsl::TensorValue::ByteString(ref t) => tensor(BasicType::ByteString, t, |v, buf| pack_bytes(v, buf))
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a ml_bundle::Tensor> for dsl::TensorValue {
+  type Err = Error;
+
+  fn try_from(value: &'a ml_bundle::Tensor) -> Result<Self, Self::Err> {
+    let mut dims: Vec<usize> = Vec::with_capacity(value.shape.len());
+    for d in value.shape.iter() {
+      if *d < 0 {
+        return Err(Error::ReadError(format!("Invalid tensor dimension {}", d)));
+      }
+      dims.push(*d as usize);
+    }
+    let buf = &value.value;
+
+    match basic_type(value.base) {
+      BasicType::Boolean => unpack_fixed(buf, 1, |c| c[0] != 0).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Bool),
+      BasicType::String => {
+        unpack_bytes(buf).and_then(|vs| {
+          let mut strings = Vec::with_capacity(vs.len());
+          for v in vs {
+            match String::from_utf8(v) {
+              Ok(s) => strings.push(s),
+              Err(_) => return Err(Error::ReadError(String::from("Invalid utf-8 string in tensor")))
+            }
+          }
+          dense(dims, strings).map(dsl::TensorValue::String)
+        })
+      },
+      BasicType::Byte => unpack_fixed(buf, 1, |c| c[0] as i8).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Byte),
+      BasicType::Short => unpack_fixed(buf, 2, |c| i16::from_be_bytes(c.try_into().unwrap())).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Short),
+      BasicType::Int => unpack_fixed(buf, 4, |c| i32::from_be_bytes(c.try_into().unwrap())).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Int),
+      BasicType::Long => unpack_fixed(buf, 8, |c| i64::from_be_bytes(c.try_into().unwrap())).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Long),
+      BasicType::Float => unpack_fixed(buf, 4, |c| f32::from_be_bytes(c.try_into().unwrap())).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Float),
+      BasicType::Double => unpack_fixed(buf, 8, |c| f64::from_be_bytes(c.try_into().unwrap())).and_then(|v| dense(dims, v)).map(dsl::TensorValue::Double),
+      BasicType::ByteString => unpack_bytes(buf).and_then(|v| dense(dims, v)).map(dsl::TensorValue::ByteString),
+      _ => Err(Error::ReadError(String::from("Invalid base for tensor")))
+    }
+  }
+}
+
+/// The type and encoded value of an attribute, frame cells are encoded the
+/// same way.
+pub fn attribute_value(value: &dsl::Attribute) -> (ml_bundle::DataType, ml_bundle::Value) {
+  let (base, shape, v) = match value {
+    &dsl::Attribute::Basic(ref basic) => {
+      let mut scalar = ml_bundle::Scalar::default();
+      let base = match basic {
+        &dsl::BasicValue::Bool(v) => { scalar.b = v; BasicType::Boolean },
+        &dsl::BasicValue::String(ref v) => { scalar.s = v.clone(); BasicType::String },
+        &dsl::BasicValue::Byte(v) => { scalar.i = v as i32; BasicType::Byte },
+        &dsl::BasicValue::Short(v) => { scalar.i = v as i32; BasicType::Short },
+        &dsl::BasicValue::Int(v) => { scalar.i = v; BasicType::Int },
+        &dsl::BasicValue::Long(v) => { scalar.l = v; BasicType::Long },
+        &dsl::BasicValue::Float(v) => { scalar.f = v as f64; BasicType::Float },
+        &dsl::BasicValue::Double(v) => { scalar.f = v; BasicType::Double },
+        &dsl::BasicValue::ByteString(ref v) => { scalar.bs = v.clone(); BasicType::ByteString }
+      };
+
+      (base, DataShapeType::Scalar, scalar_value(scalar))
+    },
+    &dsl::Attribute::Array(ref values) => {
+      let mut list = ml_bundle::List::default();
+      let base = match values {
+        &dsl::VectorValue::Bool(ref v) => { list.b = v.clone(); BasicType::Boolean },
+        &dsl::VectorValue::String(ref v) => { list.s = v.clone(); BasicType::String },
+        &dsl::VectorValue::Byte(ref v) => { list.i = v.iter().map(|x| *x as i32).collect(); BasicType::Byte },
+        &dsl::VectorValue::Short(ref v) => { list.i = v.iter().map(|x| *x as i32).collect(); BasicType::Short },
+        &dsl::VectorValue::Int(ref v) => { list.i = v.clone(); BasicType::Int },
+        &dsl::VectorValue::Long(ref v) => { list.l = v.clone(); BasicType::Long },
+        &dsl::VectorValue::Float(ref v) => { list.f = v.iter().map(|x| *x as f64).collect(); BasicType::Float },
+        &dsl::VectorValue::Double(ref v) => { list.f = v.clone(); BasicType::Double },
+        &dsl::VectorValue::ByteString(ref v) => { list.bs = v.clone(); BasicType::ByteString }
+      };
+
+      (base, DataShapeType::List, list_value(list))
+    },
+    &dsl::Attribute::Tensor(ref tv) => {
+      let t = ml_bundle::Tensor::from(tv);
+      let base = basic_type(t.base);
+      let mut scalar = ml_bundle::Scalar::default();
+      scalar.t = Some(t);
+
+      (base, DataShapeType::Tensor, scalar_value(scalar))
+    }
+  };
+
+  (data_type(base, shape), v)
+}
+
+impl<'a> From<&'a dsl::Attribute> for ml_bundle::Attribute {
+  fn from(value: &'a dsl::Attribute) -> Self {
+    let (dt, v) = attribute_value(value);
+
+    ml_bundle::Attribute {
+      data_type: Some(dt),
+      value: Some(v)
+    }
+  }
+}
+
+/// Reads an attribute of type `dt` from its encoded value.
+pub fn try_attribute_from_value(dt: &ml_bundle::DataType, value: Option<&ml_bundle::Value>) -> Result<dsl::Attribute, Error> {
+  let base = basic_type(dt.base);
+  let shape = data_shape_type(dt.shape);
+  let empty_scalar = ml_bundle::Scalar::default();
+  let empty_list = ml_bundle::List::default();
+
+  match (shape, value.and_then(|v| v.v.as_ref())) {
+    (DataShapeType::Scalar, m_scalar) => {
+      let s = match m_scalar {
+        Some(&ml_bundle::V::S(ref s)) => s,
+        None => &empty_scalar,
+        _ => return Err(Error::ReadError(String::from("Expected a scalar value")))
+      };
+      let basic = match base {
+        BasicType::Boolean => dsl::BasicValue::Bool(s.b),
+        BasicType::String => dsl::BasicValue::String(s.s.clone()),
+        BasicType::Byte => dsl::BasicValue::Byte(s.i as i8),
+        BasicType::Short => dsl::BasicValue::Short(s.i as i16),
+        BasicType::Int => dsl::BasicValue::Int(s.i),
+        BasicType::Long => dsl::BasicValue::Long(s.l),
+        BasicType::Float => dsl::BasicValue::Float(s.f as f32),
+        BasicType::Double => dsl::BasicValue::Double(s.f),
+        BasicType::ByteString => dsl::BasicValue::ByteString(s.bs.clone()),
+        BasicType::Undefined => return Err(Error::ReadError(String::from("Invalid basic type")))
+      };
+
+      Ok(dsl::Attribute::Basic(basic))
+    },
+    (DataShapeType::List, m_list) => {
+      let l = match m_list {
+        Some(&ml_bundle::V::L(ref l)) => l,
+        None => &empty_list,
+        _ => return Err(Error::ReadError(String::from("Expected a list value")))
+      };
+      let values = match base {
+        BasicType::Boolean => dsl::VectorValue::Bool(l.b.clone()),
+        BasicType::String => dsl::VectorValue::String(l.s.clone()),
+        BasicType::Byte => dsl::VectorValue::Byte(l.i.iter().map(|x| *x as i8).collect()),
+        BasicType::Short => dsl::VectorValue::Short(l.i.iter().map(|x| *x as i16).collect()),
+        BasicType::Int => dsl::VectorValue::Int(l.i.clone()),
+        BasicType::Long => dsl::VectorValue::Long(l.l.clone()),
+        BasicType::Float => dsl::VectorValue::Float(l.f.iter().map(|x| *x as f32).collect()),
+        BasicType::Double => dsl::VectorValue::Double(l.f.clone()),
+        BasicType::ByteString => dsl::VectorValue::ByteString(l.bs.clone()),
+        BasicType::Undefined => return Err(Error::ReadError(String::from("Invalid base for list")))
+      };
+
+      Ok(dsl::Attribute::Array(values))
+    },
+    (DataShapeType::Tensor, Some(&ml_bundle::V::S(ml_bundle::Scalar { t: Some(ref t), .. }))) => {
+      dsl::TensorValue::try_from(t).map(dsl::Attribute::Tensor)
+    },
+    _ => Err(Error::ReadError(String::from("Attribute value does not match its type")))
+  }
+}
+
+impl<'a> TryFrom<&'a ml_bundle::Attribute> for dsl::Attribute {
+  type Err = Error;
+
+  fn try_from(value: &'a ml_bundle::Attribute) -> Result<Self, Self::Err> {
+    match value.data_type {
+      Some(ref dt) => try_attribute_from_value(dt, value.value.as_ref()),
+      None => Err(Error::ReadError(String::from("Attribute is missing a type")))
+    }
+  }
+}
+
+impl<'a> From<&'a dsl::Socket> for ml_bundle::Socket {
+  fn from(value: &'a dsl::Socket) -> Self {
+    ml_bundle::Socket {
+      name: value.name().to_string(),
+      port: value.port().to_string()
+    }
+  }
+}
+
+impl<'a> From<&'a ml_bundle::Socket> for dsl::Socket {
+  fn from(value: &'a ml_bundle::Socket) -> Self {
+    dsl::Socket::new(value.name.clone(), value.port.clone())
+  }
+}
+
+impl<'a> From<&'a dsl::Shape> for ml_bundle::NodeShape {
+  fn from(value: &'a dsl::Shape) -> Self {
+    ml_bundle::NodeShape {
+      inputs: value.inputs().iter().map(ml_bundle::Socket::from).collect(),
+      outputs: value.outputs().iter().map(ml_bundle::Socket::from).collect()
+    }
+  }
+}
+
+impl<'a> From<&'a ml_bundle::NodeShape> for dsl::Shape {
+  fn from(value: &'a ml_bundle::NodeShape) -> Self {
+    dsl::Shape::new(value.inputs.iter().map(dsl::Socket::from).collect(),
+                    value.outputs.iter().map(dsl::Socket::from).collect())
+  }
+}
+
+impl<'a> From<&'a dsl::Node> for ml_bundle::Node {
+  fn from(value: &'a dsl::Node) -> Self {
+    ml_bundle::Node {
+      name: value.name().to_string(),
+      shape: Some(ml_bundle::NodeShape::from(value.shape()))
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a ml_bundle::Node> for dsl::Node {
+  type Err = Error;
+
+  fn try_from(value: &'a ml_bundle::Node) -> Result<Self, Self::Err> {
+    value.shape.as_ref().map(|shape| {
+      Ok(dsl::Node::new(value.name.clone(), dsl::Shape::from(shape)))
+    }).unwrap_or_else(|| Err(Error::ReadError(String::from("Node is missing a shape"))))
+  }
+}
+
+impl<'a> From<&'a dsl::Model> for ml_bundle::Model {
+  fn from(value: &'a dsl::Model) -> Self {
+    ml_bundle::Model {
+      op: value.op().to_string(),
+      attributes: value.attributes().iter().map(|(k, v)| (k.clone(), ml_bundle::Attribute::from(v))).collect()
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a ml_bundle::Model> for dsl::Model {
+  type Err = Error;
+
+  fn try_from(value: &'a ml_bundle::Model) -> Result<Self, Self::Err> {
+    let mut attrs = HashMap::with_capacity(value.attributes.len());
+
+    for (k, v) in value.attributes.iter() {
+      match dsl::Attribute::try_from(v) {
+        Ok(attr) => { attrs.insert(k.clone(), attr); },
+        Err(err) => return Err(err)
+      }
+    }
+
+    Ok(dsl::Model::new(value.op.clone(), attrs))
+  }
+}
+
+impl<'a> From<&'a dsl::Format> for ml_bundle::Format {
+  fn from(value: &'a dsl::Format) -> Self {
+    match *value {
+      dsl::Format::Concrete(dsl::ConcreteFormat::Json) => ml_bundle::Format::Json,
+      dsl::Format::Concrete(dsl::ConcreteFormat::Proto) => ml_bundle::Format::Protobuf,
+      dsl::Format::Mixed => ml_bundle::Format::Mixed
+    }
+  }
+}
+
+impl<'a> From<&'a ml_bundle::Format> for dsl::Format {
+  fn from(value: &'a ml_bundle::Format) -> Self {
+    match *value {
+      ml_bundle::Format::Json => dsl::Format::Concrete(dsl::ConcreteFormat::Json),
+      ml_bundle::Format::Protobuf => dsl::Format::Concrete(dsl::ConcreteFormat::Proto),
+      ml_bundle::Format::Mixed => dsl::Format::Mixed
+    }
+  }
+}
+
+impl<'a> From<&'a dsl::Bundle> for ml_bundle::Bundle {
+  fn from(value: &'a dsl::Bundle) -> Self {
+    ml_bundle::Bundle {
+      uid: value.uid().to_string(),
+      name: value.name().to_string(),
+      format: ml_bundle::Format::from(value.format()) as i32,
+      version: value.version().to_string()
+    }
+  }
+}
+
+impl<'a> TryFrom<&'a ml_bundle::Bundle> for dsl::Bundle {
+  type Err = Error;
+
+  fn try_from(value: &'a ml_bundle::Bundle) -> Result<Self, Self::Err> {
+    let format = match ::std::convert::TryFrom::try_from(value.format) {
+      Ok(format) => dsl::Format::from(&format),
+      Err(_) => return Err(Error::ReadError(String::from("Invalid bundle format")))
+    };
+
+    match (Uuid::parse_str(&value.uid), Version::parse(&value.version)) {
+      (Ok(uid), Ok(version)) => Ok(dsl::Bundle::new(uid, value.name.clone(), format, version)),
+      (Err(_), _) => Err(Error::ReadError(String::from("Invalid UUID"))),
+      (_, Err(_)) => Err(Error::ReadError(String::from("Invalid semantic version")))
+    }
+  }
+}
+
+impl<'a> From<&'a dsl::TreeNode> for tree::Node {
+  fn from(value: &'a dsl::TreeNode) -> Self {
+    let n = match value {
+      &dsl::TreeNode::Internal(dsl::Split::Continuous { feature_index, threshold }) => {
+        let split = tree::S::Continuous(tree::ContinuousSplit {
+          feature_index: feature_index as i32,
+          threshold: threshold
+        });
+        tree::N::Internal(tree::InternalNode { split: Some(tree::Split { s: Some(split) }) })
+      },
+      &dsl::TreeNode::Internal(dsl::Split::Categorical { feature_index, num_categories, ref categories, is_left }) => {
+        let split = tree::S::Categorical(tree::CategoricalSplit {
+          feature_index: feature_index as i32,
+          is_left: is_left,
+          num_categories: num_categories as i32,
+          categories: categories.clone()
+        });
+        tree::N::Internal(tree::InternalNode { split: Some(tree::Split { s: Some(split) }) })
+      },
+      &dsl::TreeNode::Leaf(ref values) => tree::N::Leaf(tree::LeafNode { values: values.clone() })
+    };
+
+    tree::Node { n: Some(n) }
+  }
+}
+
+impl<'a> TryFrom<&'a tree::Node> for dsl::TreeNode {
+  type Err = Error;
+
+  fn try_from(value: &'a tree::Node) -> Result<Self, Self::Err> {
+    match value.n {
+      Some(tree::N::Internal(tree::InternalNode { split: Some(tree::Split { s: Some(ref s) }) })) => {
+        match s {
+          &tree::S::Continuous(ref split) if split.feature_index >= 0 => Ok(dsl::Split::Continuous {
+            feature_index: split.feature_index as usize,
+            threshold: split.threshold
+          }),
+          &tree::S::Categorical(ref split) if split.feature_index >= 0 && split.num_categories >= 0 => Ok(dsl::Split::Categorical {
+            feature_index: split.feature_index as usize,
+            num_categories: split.num_categories as usize,
+            categories: split.categories.clone(),
+            is_left: split.is_left
+          }),
+          _ => Err(Error::ReadError(String::from("Negative feature index or category count in tree split")))
+        }.map(dsl::TreeNode::Internal)
+      },
+      Some(tree::N::Leaf(ref leaf)) => Ok(dsl::TreeNode::Leaf(leaf.values.clone())),
+      _ => Err(Error::ReadError(String::from("Invalid tree node")))
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use prost::Message;
+
+  fn round_trip(attr: dsl::Attribute) {
+    let buf = ml_bundle::Attribute::from(&attr).encode_to_vec();
+    let pb = ml_bundle::Attribute::decode(buf.as_slice()).unwrap();
+
+    assert_eq!(dsl::Attribute::try_from(&pb).unwrap(), attr);
+  }
+
+  #[test]
+  fn test_attribute_round_trip() {
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Bool(false)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Short(-12)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::Double(0.0)));
+    round_trip(dsl::Attribute::Basic(dsl::BasicValue::ByteString(vec![1, 2, 3])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Byte(vec![-1, 0, 1])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::String(vec![String::from("a"), String::from("b")])));
+    round_trip(dsl::Attribute::Array(dsl::VectorValue::Float(vec![])));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Double(dsl::DenseTensor::new(vec![3], vec![1.5, -2.0, 3.25]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::Int(dsl::DenseTensor::new(vec![2, 2], vec![1, 2, 3, 4]))));
+    round_trip(dsl::Attribute::Tensor(dsl::TensorValue::String(dsl::DenseTensor::new(vec![2], vec![String::from("x"), String::new()]))));
+  }
+
+  #[test]
+  fn test_malformed_tensor() {
+    let tensor = |base: BasicType, shape: Vec<i32>, value: Vec<u8>| {
+      dsl::TensorValue::try_from(&ml_bundle::Tensor { base: base as i32, shape: shape, value: value })
+    };
+
+    // negative and truncated lengths of packed strings
+    assert!(tensor(BasicType::String, vec![1], vec![0xff, 0xff, 0xff, 0xff, b'a']).is_err());
+    assert!(tensor(BasicType::ByteString, vec![1], vec![0x7f, 0xff, 0xff, 0xff, 1]).is_err());
+    assert!(tensor(BasicType::String, vec![1], vec![0, 0, 0]).is_err());
+
+    assert!(tensor(BasicType::Int, vec![-1], vec![0, 0, 0, 1]).is_err());
+    assert!(tensor(BasicType::Int, vec![2], vec![0, 0, 0, 1]).is_err());
+    assert!(tensor(BasicType::Byte, vec![1 << 30, 1 << 30, 1 << 30], vec![1]).is_err());
+    assert_eq!(tensor(BasicType::Byte, vec![1, 2], vec![1, 2]).unwrap(),
+               dsl::TensorValue::Byte(dsl::DenseTensor::new(vec![1, 2], vec![1, 2])));
+  }
+
+  #[test]
+  fn test_node_round_trip() {
+    let shape = dsl::Shape::with_standard_io(String::from("a"), String::from("b"));
+    let node = dsl::Node::new(String::from("node"), shape);
+    let buf = ml_bundle::Node::from(&node).encode_to_vec();
+    let pb = ml_bundle::Node::decode(buf.as_slice()).unwrap();
+
+    assert_eq!(dsl::Node::try_from(&pb).unwrap(), node);
+  }
+
+  #[test]
+  fn test_bundle_round_trip() {
+    let bundle = dsl::Bundle::new(Uuid::new_v4(),
+                                  String::from("bundle"),
+                                  dsl::Format::Mixed,
+                                  Version::parse("0.6.0").unwrap());
+    let buf = ml_bundle::Bundle::from(&bundle).encode_to_vec();
+    let read = dsl::Bundle::try_from(&ml_bundle::Bundle::decode(buf.as_slice()).unwrap()).unwrap();
+
+    assert_eq!((read.uid(), read.name(), read.format(), read.version()),
+               (bundle.uid(), bundle.name(), bundle.format(), bundle.version()));
+  }
+
+  #[test]
+  fn test_tree_node_round_trip() {
+    let nodes = vec![dsl::TreeNode::Internal(dsl::Split::Continuous { feature_index: 2, threshold: -1.5 }),
+                     dsl::TreeNode::Internal(dsl::Split::Categorical { feature_index: 0,
+                                                                       num_categories: 4,
+                                                                       categories: vec![1.0, 3.0],
+                                                                       is_left: false }),
+                     dsl::TreeNode::Leaf(vec![0.25])];
+
+    for node in nodes.iter() {
+      let buf = tree::Node::from(node).encode_to_vec();
+      let pb = tree::Node::decode(buf.as_slice()).unwrap();
+      assert_eq!(dsl::TreeNode::try_from(&pb).unwrap(), *node);
+    }
+
+    let split = tree::S::Continuous(tree::ContinuousSplit { feature_index: -1, threshold: 0.0 });
+    let pb = tree::Node { n: Some(tree::N::Internal(tree::InternalNode { split: Some(tree::Split { s: Some(split) }) })) };
+    assert!(dsl::TreeNode::try_from(&pb).is_err());
+  }
+  #[test]
+  fn test_proto_files() {
+    let bundle = include_str!("../../../proto/bundle.proto");
+
+    descriptor::check(&[], bundle, |name, buf| {
+      match name {
+        "DataType" => Some(descriptor::round_trip::<ml_bundle::DataType>(buf)),
+        "Tensor" => Some(descriptor::round_trip::<ml_bundle::Tensor>(buf)),
+        "Scalar" => Some(descriptor::round_trip::<ml_bundle::Scalar>(buf)),
+        "List" => Some(descriptor::round_trip::<ml_bundle::List>(buf)),
+        "Value" => Some(descriptor::round_trip::<ml_bundle::Value>(buf)),
+        "Attribute" => Some(descriptor::round_trip::<ml_bundle::Attribute>(buf)),
+        "Model" => Some(descriptor::round_trip::<ml_bundle::Model>(buf)),
+        "Socket" => Some(descriptor::round_trip::<ml_bundle::Socket>(buf)),
+        "NodeShape" => Some(descriptor::round_trip::<ml_bundle::NodeShape>(buf)),
+        "Node" => Some(descriptor::round_trip::<ml_bundle::Node>(buf)),
+        "Bundle" => Some(descriptor::round_trip::<ml_bundle::Bundle>(buf)),
+        _ => None
+      }
+    }, |name, value| {
+      match name {
+        "BasicType" => ::std::convert::TryFrom::try_from(value).ok().map(|v: ml_bundle::BasicType| format!("{:?}", v)),
+        "DataShapeType" => ::std::convert::TryFrom::try_from(value).ok().map(|v: ml_bundle::DataShapeType| format!("{:?}", v)),
+        "Format" => ::std::convert::TryFrom::try_from(value).ok().map(|v: ml_bundle::Format| format!("{:?}", v)),
+        _ => None
+      }
+    });
+
+    descriptor::check(&[], include_str!("../../../proto/decision_tree.proto"), |name, buf| {
+      match name {
+        "CategoricalSplit" => Some(descriptor::round_trip::<tree::CategoricalSplit>(buf)),
+        "ContinuousSplit" => Some(descriptor::round_trip::<tree::ContinuousSplit>(buf)),
+        "Split" => Some(descriptor::round_trip::<tree::Split>(buf)),
+        "InternalNode" => Some(descriptor::round_trip::<tree::InternalNode>(buf)),
+        "LeafNode" => Some(descriptor::round_trip::<tree::LeafNode>(buf)),
+        "Node" => Some(descriptor::round_trip::<tree::Node>(buf)),
+        _ => None
+      }
+    }, |_, _| None);
+
+    descriptor::check(&[bundle], include_str!("../../../proto/mleap/runtime.proto"), |name, buf| {
+      match name {
+        "TensorShape" => Some(descriptor::round_trip::<runtime::TensorShape>(buf)),
+        "DataType" => Some(descriptor::round_trip::<runtime::DataType>(buf)),
+        "StructField" => Some(descriptor::round_trip::<runtime::StructField>(buf)),
+        "Schema" => Some(descriptor::round_trip::<runtime::Schema>(buf)),
+        "Row" => Some(descriptor::round_trip::<runtime::Row>(buf)),
+        "LeapFrame" => Some(descriptor::round_trip::<runtime::LeapFrame>(buf)),
+        _ => None
+      }
+    }, |_, _| None);
+  }
+}
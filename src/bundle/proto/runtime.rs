@@ -0,0 +1,51 @@
+// Messages from the ml.combust.mleap.pb package of the MLeap runtime,
+// written out by hand like ml_bundle from proto/mleap/runtime.proto. Frame
+// cells are ml_bundle values.
+
+use super::ml_bundle;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TensorShape {
+  #[prost(int32, repeated, tag="1")]
+  pub dimensions: Vec<i32>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct DataType {
+  #[prost(enumeration="ml_bundle::BasicType", tag="1")]
+  pub base: i32,
+  #[prost(enumeration="ml_bundle::DataShapeType", tag="2")]
+  pub shape: i32,
+  #[prost(bool, tag="3")]
+  pub is_nullable: bool,
+  #[prost(message, optional, tag="4")]
+  pub tensor_shape: Option<TensorShape>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct StructField {
+  #[prost(string, tag="1")]
+  pub name: String,
+  #[prost(message, optional, tag="2")]
+  pub data_type: Option<DataType>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Schema {
+  #[prost(message, repeated, tag="1")]
+  pub fields: Vec<StructField>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Row {
+  #[prost(message, repeated, tag="1")]
+  pub values: Vec<ml_bundle::Value>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct LeapFrame {
+  #[prost(message, optional, tag="1")]
+  pub schema: Option<Schema>,
+  #[prost(message, repeated, tag="2")]
+  pub rows: Vec<Row>
+}
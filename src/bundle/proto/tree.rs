@@ -0,0 +1,62 @@
+// Messages from the ml.bundle.tree.decision package of bundle-protobuf,
+// written out by hand like ml_bundle from proto/decision_tree.proto.
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CategoricalSplit {
+  #[prost(int32, tag="1")]
+  pub feature_index: i32,
+  #[prost(bool, tag="2")]
+  pub is_left: bool,
+  #[prost(int32, tag="3")]
+  pub num_categories: i32,
+  #[prost(double, repeated, tag="4")]
+  pub categories: Vec<f64>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ContinuousSplit {
+  #[prost(int32, tag="1")]
+  pub feature_index: i32,
+  #[prost(double, tag="2")]
+  pub threshold: f64
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum S {
+  #[prost(message, tag="1")]
+  Categorical(CategoricalSplit),
+  #[prost(message, tag="2")]
+  Continuous(ContinuousSplit)
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Split {
+  #[prost(oneof="S", tags="1, 2")]
+  pub s: Option<S>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct InternalNode {
+  #[prost(message, optional, tag="1")]
+  pub split: Option<Split>
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct LeafNode {
+  #[prost(double, repeated, tag="1")]
+  pub values: Vec<f64>
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum N {
+  #[prost(message, tag="1")]
+  Internal(InternalNode),
+  #[prost(message, tag="2")]
+  Leaf(LeafNode)
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Node {
+  #[prost(oneof="N", tags="1, 2")]
+  pub n: Option<N>
+}
@@ -2,10 +2,16 @@ use std::result;
 use std::io;
 use std::path;
 use std::fs;
+use std::mem;
+use std::rc::Rc;
+use std::cell::RefCell;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use serde_json::{self, Value};
+use zip;
+use prost::Message;
 use super::json::{self, TryFrom};
+use super::proto::{self, ml_bundle, tree};
 use super::dsl;
 
 #[derive(Debug)]
@@ -13,6 +19,8 @@ pub enum Error {
   IoError(io::Error),
   SerdeJsonError(serde_json::Error),
   JsonError(json::Error),
+  ZipError(zip::result::ZipError),
+  ProtoError(proto::Error),
   DowncastError(String),
   InvalidOp(String),
   InvalidModel(String)
@@ -59,6 +67,7 @@ pub trait Builder {
 
   fn write(&self, name: &str) -> Result<Box<io::Write>>;
   fn read(&self, name: &str) -> Result<Box<io::Read>>;
+  fn exists(&self, name: &str) -> bool;
 
   fn write_buf(&self, name: &str) -> Result<io::BufWriter<Box<io::Write>>> {
     self.write(name).map(io::BufWriter::new)
@@ -69,15 +78,65 @@ pub trait Builder {
   }
 }
 
+pub trait FormatPolicy {
+  fn format(&self, model: &dsl::Model) -> dsl::ConcreteFormat;
+}
+
 pub struct Context<'a, Node: 'a> {
   builder: Box<Builder>,
-  registry: &'a Registry<'a, Node>
+  registry: &'a Registry<'a, Node>,
+  format: dsl::Format,
+  policy: &'a FormatPolicy,
+  // the tree of a model in a mixed bundle, written once the format of the
+  // model is resolved
+  tree: RefCell<Option<Vec<dsl::TreeNode>>>
+}
+
+pub struct TensorSizePolicy {
+  threshold: usize
 }
 
+pub static DEFAULT_POLICY: TensorSizePolicy = TensorSizePolicy { threshold: 1024 };
+
 pub struct FileBuilder {
   path: path::PathBuf
 }
 
+#[derive(Clone)]
+pub struct ZipBuilder {
+  archive: Rc<RefCell<ZipArchive>>,
+  path: String
+}
+
+enum ZipArchive {
+  Read(zip::ZipArchive<fs::File>),
+  // the first failed entry and the number of entry writers not committed
+  Write(zip::ZipWriter<fs::File>, Option<io::Error>, usize),
+  Closed
+}
+
+#[derive(Clone)]
+pub struct MemoryBuilder {
+  entries: Rc<RefCell<HashMap<String, Vec<u8>>>>,
+  path: String
+}
+
+struct MemoryEntryWriter {
+  entries: Rc<RefCell<HashMap<String, Vec<u8>>>>,
+  name: String,
+  buf: Vec<u8>
+}
+
+/// Buffers one entry of a zip bundle, which is added to the archive by
+/// `flush` or on drop. Writes after that, or after the bundle is finished,
+/// fail.
+struct ZipEntryWriter {
+  archive: Rc<RefCell<ZipArchive>>,
+  name: String,
+  buf: Vec<u8>,
+  committed: bool
+}
+
 fn from_io_result<T>(result: io::Result<T>) -> Result<T> {
   result.map_err(|e| {
     Error::IoError(e)
@@ -90,6 +149,18 @@ fn from_json_result<T>(result: result::Result<T, json::Error>) -> Result<T> {
   })
 }
 
+fn from_zip_result<T>(result: zip::result::ZipResult<T>) -> Result<T> {
+  result.map_err(|e| {
+    Error::ZipError(e)
+  })
+}
+
+fn from_proto_result<T>(result: result::Result<T, proto::Error>) -> Result<T> {
+  result.map_err(|e| {
+    Error::ProtoError(e)
+  })
+}
+
 fn from_serde_json_result<T>(result: serde_json::Result<T>) -> Result<T> {
   result.map_err(|e| {
     Error::SerdeJsonError(e)
@@ -132,15 +203,165 @@ impl<'a, Node: Sized + 'a> Context<'a, Node> {
              registry: &'a Registry<'a, Node>) -> Context<'a, Node> {
     Context {
       builder: builder,
-      registry: registry
+      registry: registry,
+      format: dsl::Format::Concrete(dsl::ConcreteFormat::Json),
+      policy: &DEFAULT_POLICY,
+      tree: RefCell::new(None)
     }
   }
 
+  pub fn with_format(mut self, format: dsl::Format) -> Context<'a, Node> {
+    self.format = format;
+    self
+  }
+
+  pub fn with_policy(mut self, policy: &'a FormatPolicy) -> Context<'a, Node> {
+    self.policy = policy;
+    self
+  }
+
   pub fn builder(&self) -> &Builder { self.builder.as_ref() }
   pub fn registry(&self) -> &Registry<Node> { &self.registry }
+  pub fn format(&self) -> dsl::Format { self.format }
 
   pub fn try_next(&self, name: &str) -> Result<Context<'a, Node>> {
-    self.builder.try_next(name).map(|b| Context { builder: b, registry: self.registry })
+    self.builder.try_next(name).map(|b| {
+      Context {
+        builder: b,
+        registry: self.registry,
+        format: self.format,
+        policy: self.policy,
+        tree: RefCell::new(None)
+      }
+    })
+  }
+
+  fn read_format(&self) -> dsl::ConcreteFormat {
+    match self.format {
+      dsl::Format::Concrete(format) => format,
+      dsl::Format::Mixed => {
+        if self.builder.exists("model.pb") {
+          dsl::ConcreteFormat::Proto
+        } else {
+          dsl::ConcreteFormat::Json
+        }
+      }
+    }
+  }
+
+  fn write_format(&self, model: &dsl::Model) -> dsl::ConcreteFormat {
+    match self.format {
+      dsl::Format::Concrete(format) => format,
+      dsl::Format::Mixed => self.policy.format(model)
+    }
+  }
+
+  fn read_bytes(&self, name: &str) -> Result<Vec<u8>> {
+    self.builder.read_buf(name).and_then(|ref mut r| {
+      let mut buf = Vec::new();
+      from_io_result(io::Read::read_to_end(r, &mut buf)).map(|_| buf)
+    })
+  }
+
+  fn write_bytes(&self, name: &str, buf: &[u8]) -> Result<()> {
+    self.builder.write_buf(name).and_then(|ref mut out| {
+      from_io_result(io::Write::write_all(out, buf).and_then(|_| io::Write::flush(out)))
+    })
+  }
+
+  // decision trees are written next to the model as one JSON node per line
+  // or as length delimited protobuf nodes, both in pre-order, in the format
+  // of the model
+  pub fn write_dsl_tree(&self, nodes: &[dsl::TreeNode]) -> Result<()> {
+    match self.format {
+      dsl::Format::Concrete(format) => self.write_tree(nodes, format),
+      dsl::Format::Mixed => {
+        *self.tree.borrow_mut() = Some(nodes.to_vec());
+        Ok(())
+      }
+    }
+  }
+
+  fn write_tree(&self, nodes: &[dsl::TreeNode], format: dsl::ConcreteFormat) -> Result<()> {
+    match format {
+      dsl::ConcreteFormat::Proto => {
+        let mut buf = Vec::new();
+
+        for node in nodes.iter() {
+          match tree::Node::from(node).encode_length_delimited(&mut buf) {
+            Ok(_) => { },
+            Err(err) => return Err(Error::ProtoError(proto::Error::WriteError(err.to_string())))
+          }
+        }
+
+        self.write_bytes("tree.pb", &buf)
+      },
+      dsl::ConcreteFormat::Json => {
+        let mut buf = Vec::new();
+
+        for node in nodes.iter() {
+          match serde_json::to_writer(&mut buf, &Value::from(node)) {
+            Ok(_) => buf.push(b'\n'),
+            Err(err) => return Err(Error::SerdeJsonError(err))
+          }
+        }
+
+        self.write_bytes("tree.json", &buf)
+      }
+    }
+  }
+
+  pub fn read_dsl_tree(&self) -> Result<Vec<dsl::TreeNode>> {
+    let format = match self.format {
+      dsl::Format::Concrete(format) => format,
+      dsl::Format::Mixed => {
+        if self.builder.exists("tree.pb") {
+          dsl::ConcreteFormat::Proto
+        } else {
+          dsl::ConcreteFormat::Json
+        }
+      }
+    };
+
+    match format {
+      dsl::ConcreteFormat::Json => {
+        self.read_bytes("tree.json").and_then(|buf| {
+          let mut nodes = Vec::new();
+
+          for line in buf.split(|b| *b == b'\n').filter(|l| !l.is_empty()) {
+            let r = from_serde_json_result(serde_json::from_slice(line)).and_then(|json: Value| {
+              from_json_result(dsl::TreeNode::try_from(&json))
+            });
+
+            match r {
+              Ok(node) => nodes.push(node),
+              Err(err) => return Err(err)
+            }
+          }
+
+          Ok(nodes)
+        })
+      },
+      dsl::ConcreteFormat::Proto => {
+        self.read_bytes("tree.pb").and_then(|buf| {
+          let mut nodes = Vec::new();
+          let mut rest = buf.as_slice();
+
+          while !rest.is_empty() {
+            let r = from_proto_result(tree::Node::decode_length_delimited(&mut rest).map_err(proto::Error::from)).and_then(|pb| {
+              from_proto_result(dsl::TreeNode::try_from(&pb))
+            });
+
+            match r {
+              Ok(node) => nodes.push(node),
+              Err(err) => return Err(err)
+            }
+          }
+
+          Ok(nodes)
+        })
+      }
+    }
   }
 }
 
@@ -165,12 +386,303 @@ impl Builder for FileBuilder {
     from_io_result(fs::OpenOptions::new().
                    write(true).
                    create(true).
+                   truncate(true).
                    open(self.path.join(name))).map(|x| Box::new(x) as Box<io::Write>)
   }
 
   fn read(&self, name: &str) -> Result<Box<io::Read>> {
     from_io_result(fs::File::open(self.path.join(name))).map(|x| Box::new(x) as Box<io::Read>)
   }
+
+  fn exists(&self, name: &str) -> bool {
+    self.path.join(name).is_file()
+  }
+}
+
+impl ZipBuilder {
+  pub fn try_open<P: AsRef<path::Path>>(path: P) -> Result<ZipBuilder> {
+    from_io_result(fs::File::open(path)).and_then(|file| {
+      from_zip_result(zip::ZipArchive::new(file)).map(|archive| {
+        ZipBuilder {
+          archive: Rc::new(RefCell::new(ZipArchive::Read(archive))),
+          path: String::new()
+        }
+      })
+    })
+  }
+
+  pub fn try_create<P: AsRef<path::Path>>(path: P) -> Result<ZipBuilder> {
+    from_io_result(fs::File::create(path)).map(|file| {
+      ZipBuilder {
+        archive: Rc::new(RefCell::new(ZipArchive::Write(zip::ZipWriter::new(file), None, 0))),
+        path: String::new()
+      }
+    })
+  }
+
+  pub fn finish(&self) -> Result<()> {
+    let archive = mem::replace(&mut *self.archive.borrow_mut(), ZipArchive::Closed);
+
+    match archive {
+      ZipArchive::Write(_, _, open) if open > 0 => {
+        Err(Error::IoError(io::Error::new(io::ErrorKind::Other, format!("{} zip bundle entries are still being written", open))))
+      },
+      ZipArchive::Write(mut writer, None, _) => from_zip_result(writer.finish()).map(|_| ()),
+      ZipArchive::Write(_, Some(err), _) => Err(Error::IoError(err)),
+      _ => Ok(())
+    }
+  }
+}
+
+impl Builder for ZipBuilder {
+  fn try_next(&self, name: &str) -> Result<Box<Builder>> {
+    Ok(Box::new(ZipBuilder {
+      archive: self.archive.clone(),
+      path: format!("{}{}/", self.path, name)
+    }) as Box<Builder>)
+  }
+
+  fn write(&self, name: &str) -> Result<Box<io::Write>> {
+    match *self.archive.borrow_mut() {
+      ZipArchive::Write(_, _, ref mut open) => {
+        *open += 1;
+
+        Ok(Box::new(ZipEntryWriter {
+          archive: self.archive.clone(),
+          name: format!("{}{}", self.path, name),
+          buf: Vec::new(),
+          committed: false
+        }) as Box<io::Write>)
+      },
+      _ => Err(Error::IoError(not_writable()))
+    }
+  }
+
+  fn read(&self, name: &str) -> Result<Box<io::Read>> {
+    match *self.archive.borrow_mut() {
+      ZipArchive::Read(ref mut archive) => {
+        from_zip_result(archive.by_name(&format!("{}{}", self.path, name))).and_then(|mut file| {
+          let mut buf = Vec::with_capacity(file.size() as usize);
+
+          from_io_result(io::Read::read_to_end(&mut file, &mut buf)).map(|_| {
+            Box::new(io::Cursor::new(buf)) as Box<io::Read>
+          })
+        })
+      },
+      _ => Err(Error::IoError(io::Error::new(io::ErrorKind::PermissionDenied, "zip bundle is not open for reading")))
+    }
+  }
+
+  fn exists(&self, name: &str) -> bool {
+    match *self.archive.borrow_mut() {
+      ZipArchive::Read(ref mut archive) => archive.by_name(&format!("{}{}", self.path, name)).is_ok(),
+      _ => false
+    }
+  }
+}
+
+fn not_writable() -> io::Error {
+  io::Error::new(io::ErrorKind::PermissionDenied, "zip bundle is not open for writing")
+}
+
+impl ZipEntryWriter {
+  fn commit(&mut self) -> io::Result<()> {
+    if self.committed {
+      return Ok(());
+    }
+    self.committed = true;
+
+    let mut archive = match self.archive.try_borrow_mut() {
+      Ok(archive) => archive,
+      Err(_) => return Err(io::Error::new(io::ErrorKind::Other, format!("zip bundle is busy, cannot write {}", self.name)))
+    };
+
+    match *archive {
+      ZipArchive::Write(ref mut writer, ref mut error, ref mut open) => {
+        *open -= 1;
+
+        if error.is_some() {
+          return Err(io::Error::new(io::ErrorKind::Other, format!("zip bundle failed before {} was written", self.name)));
+        }
+
+        let r = writer.start_file(self.name.clone(), zip::write::FileOptions::default()).
+          map_err(io::Error::from).
+          and_then(|_| io::Write::write_all(writer, &self.buf));
+
+        // kept for finish, which reports the first failure
+        if let Err(ref err) = r {
+          *error = Some(io::Error::new(err.kind(), err.to_string()));
+        }
+
+        r
+      },
+      _ => Err(not_writable())
+    }
+  }
+}
+
+impl io::Write for ZipEntryWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    if self.committed {
+      return Err(io::Error::new(io::ErrorKind::Other, format!("zip entry {} has already been written", self.name)));
+    }
+
+    match self.archive.try_borrow() {
+      Ok(ref archive) => {
+        match **archive {
+          ZipArchive::Write(_, _, _) => (),
+          _ => return Err(not_writable())
+        }
+      },
+      Err(_) => return Err(io::Error::new(io::ErrorKind::Other, format!("zip bundle is busy, cannot write {}", self.name)))
+    }
+
+    self.buf.extend_from_slice(buf);
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> { self.commit() }
+}
+
+impl Drop for ZipEntryWriter {
+  fn drop(&mut self) {
+    // entries are buffered so nested nodes can be written while their
+    // parent is still open, a failure here is reported by finish
+    let _ = self.commit();
+  }
+}
+
+impl MemoryBuilder {
+  pub fn new() -> MemoryBuilder {
+    MemoryBuilder::from_map(HashMap::new())
+  }
+
+  pub fn from_map(entries: HashMap<String, Vec<u8>>) -> MemoryBuilder {
+    MemoryBuilder {
+      entries: Rc::new(RefCell::new(entries)),
+      path: String::new()
+    }
+  }
+
+  pub fn try_from_zip(bytes: Vec<u8>) -> Result<MemoryBuilder> {
+    from_zip_result(zip::ZipArchive::new(io::Cursor::new(bytes))).and_then(|mut archive| {
+      let mut entries = HashMap::with_capacity(archive.len());
+
+      for i in 0..archive.len() {
+        let r = from_zip_result(archive.by_index(i)).and_then(|mut file| {
+          let mut buf = Vec::with_capacity(file.size() as usize);
+          let name = file.name().to_string();
+
+          from_io_result(io::Read::read_to_end(&mut file, &mut buf)).map(|_| (name, buf))
+        });
+
+        match r {
+          Ok((name, buf)) => { entries.insert(name, buf); },
+          Err(err) => return Err(err)
+        }
+      }
+
+      Ok(MemoryBuilder::from_map(entries))
+    })
+  }
+
+  pub fn to_map(&self) -> HashMap<String, Vec<u8>> {
+    self.entries.borrow().clone()
+  }
+
+  pub fn try_to_zip(&self) -> Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+    let mut names: Vec<&String> = Vec::new();
+    let entries = self.entries.borrow();
+    names.extend(entries.keys());
+    names.sort();
+
+    for name in names {
+      let r = from_zip_result(writer.start_file(name.clone(), zip::write::FileOptions::default())).and_then(|_| {
+        from_io_result(io::Write::write_all(&mut writer, &entries[name]))
+      });
+
+      if let Err(err) = r {
+        return Err(err);
+      }
+    }
+
+    from_zip_result(writer.finish()).map(|cursor| cursor.into_inner())
+  }
+}
+
+impl Builder for MemoryBuilder {
+  fn try_next(&self, name: &str) -> Result<Box<Builder>> {
+    Ok(Box::new(MemoryBuilder {
+      entries: self.entries.clone(),
+      path: format!("{}{}/", self.path, name)
+    }) as Box<Builder>)
+  }
+
+  fn write(&self, name: &str) -> Result<Box<io::Write>> {
+    Ok(Box::new(MemoryEntryWriter {
+      entries: self.entries.clone(),
+      name: format!("{}{}", self.path, name),
+      buf: Vec::new()
+    }) as Box<io::Write>)
+  }
+
+  fn read(&self, name: &str) -> Result<Box<io::Read>> {
+    let name = format!("{}{}", self.path, name);
+
+    self.entries.borrow().get(&name).map(|buf| {
+      Ok(Box::new(io::Cursor::new(buf.clone())) as Box<io::Read>)
+    }).unwrap_or_else(|| Err(Error::IoError(io::Error::new(io::ErrorKind::NotFound, name))))
+  }
+
+  fn exists(&self, name: &str) -> bool {
+    self.entries.borrow().contains_key(&format!("{}{}", self.path, name))
+  }
+}
+
+impl io::Write for MemoryEntryWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.buf.extend_from_slice(buf);
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Drop for MemoryEntryWriter {
+  fn drop(&mut self) {
+    let buf = mem::replace(&mut self.buf, Vec::new());
+    self.entries.borrow_mut().insert(self.name.clone(), buf);
+  }
+}
+
+impl TensorSizePolicy {
+  pub fn new(threshold: usize) -> TensorSizePolicy {
+    TensorSizePolicy {
+      threshold: threshold
+    }
+  }
+}
+
+impl FormatPolicy for TensorSizePolicy {
+  fn format(&self, model: &dsl::Model) -> dsl::ConcreteFormat {
+    let large = model.attributes().values().any(|attr| {
+      match attr {
+        &dsl::Attribute::Tensor(ref tv) => tv.len() > self.threshold,
+        _ => false
+      }
+    });
+
+    if large { dsl::ConcreteFormat::Proto } else { dsl::ConcreteFormat::Json }
+  }
+}
+
+pub fn open_builder<P: AsRef<path::Path>>(path: P) -> Result<Box<Builder>> {
+  if path.as_ref().is_file() {
+    ZipBuilder::try_open(path).map(|b| Box::new(b) as Box<Builder>)
+  } else {
+    FileBuilder::try_new(path).map(|b| Box::new(b) as Box<Builder>)
+  }
 }
 
 impl<'a, Node: OpNode + 'a> Context<'a, Node> {
@@ -178,11 +690,12 @@ impl<'a, Node: OpNode + 'a> Context<'a, Node> {
     self.builder.write_buf("bundle.json").
       and_then(|ref mut out| {
         let json = Value::from(bundle);
-        from_serde_json_result(serde_json::to_writer_pretty(out, &json)).
+        from_serde_json_result(serde_json::to_writer_pretty(&mut *out, &json)).
+          and_then(|_| from_io_result(io::Write::flush(out))).
           and_then(|_| {
             self.try_next("root").
               and_then(|ctx| {
-                ctx.write_node_and_model(root)
+                ctx.with_format(*bundle.format()).write_node_and_model(root)
               })
           })
       })
@@ -190,33 +703,55 @@ impl<'a, Node: OpNode + 'a> Context<'a, Node> {
 
   pub fn write_node_and_model(&self, node: &Node) -> Result<()> {
     self.registry.try_op_for_node(node).and_then(|op| {
-      self.write_node(node, *op).and_then(|_| {
-        let model = op.model(node);
-        self.write_model(model, *op)
+      let mut model = dsl::Model::new(op.op().to_string(), HashMap::new());
+
+      op.store_model(op.model(node), &mut model, self).and_then(|_| {
+        let format = self.write_format(&model);
+
+        self.write_dsl_node(&op.node(node, self), format).and_then(|_| {
+          self.write_dsl_model(&model, format)
+        }).and_then(|_| {
+          match self.tree.borrow_mut().take() {
+            Some(nodes) => self.write_tree(&nodes, format),
+            None => Ok(())
+          }
+        })
       })
     })
   }
 
-  fn write_node(&self, node: &Node, op: &Op<Node=Node>) -> Result<()> {
-    self.builder.write_buf("node.json").
-      and_then(|ref mut out| {
-        let dsl_node = op.node(node, self);
-        let json = Value::from(&dsl_node);
+  fn write_dsl_node(&self, dsl_node: &dsl::Node, format: dsl::ConcreteFormat) -> Result<()> {
+    match format {
+      dsl::ConcreteFormat::Json => {
+        self.builder.write_buf("node.json").
+          and_then(|ref mut out| {
+            let json = Value::from(dsl_node);
 
-        from_serde_json_result(serde_json::to_writer_pretty(out, &json))
-      })
+            from_serde_json_result(serde_json::to_writer_pretty(&mut *out, &json)).
+              and_then(|_| from_io_result(io::Write::flush(out)))
+          })
+      },
+      dsl::ConcreteFormat::Proto => {
+        self.write_bytes("node.pb", &ml_bundle::Node::from(dsl_node).encode_to_vec())
+      }
+    }
   }
 
-  fn write_model(&self, obj: &Any, op: &Op<Node=Node>) -> Result<()> {
-    self.builder.write_buf("model.json").
-      and_then(|ref mut out| {
-        let mut model = dsl::Model::new(op.op().to_string(), HashMap::new());
-        op.store_model(obj, &mut model, self).and_then(|_| {
-          let json = Value::from(&model);
+  fn write_dsl_model(&self, model: &dsl::Model, format: dsl::ConcreteFormat) -> Result<()> {
+    match format {
+      dsl::ConcreteFormat::Json => {
+        self.builder.write_buf("model.json").
+          and_then(|ref mut out| {
+            let json = Value::from(model);
 
-          from_serde_json_result(serde_json::to_writer_pretty(out, &json))
-        })
-      })
+            from_serde_json_result(serde_json::to_writer_pretty(&mut *out, &json)).
+              and_then(|_| from_io_result(io::Write::flush(out)))
+          })
+      },
+      dsl::ConcreteFormat::Proto => {
+        self.write_bytes("model.pb", &ml_bundle::Model::from(model).encode_to_vec())
+      }
+    }
   }
 }
 
@@ -224,7 +759,7 @@ impl<'a, Node: OpNode + 'a> Context<'a, Node> {
   pub fn read_bundle(&self) -> Result<(dsl::Bundle, Node)> {
     self.read_dsl_bundle().and_then(|bundle| {
       self.try_next("root").and_then(|ctx| {
-        ctx.read_node().map(|node| (bundle, node))
+        ctx.with_format(*bundle.format()).read_node().map(|node| (bundle, node))
       })
     })
   }
@@ -250,18 +785,116 @@ impl<'a, Node: OpNode + 'a> Context<'a, Node> {
   }
 
   pub fn read_dsl_node(&self) -> Result<dsl::Node> {
-    self.builder.read_buf("node.json").and_then(|r| {
-      from_serde_json_result(serde_json::from_reader(r)).and_then(|json: Value| {
-        from_json_result(dsl::Node::try_from(&json))
-      })
-    })
+    match self.read_format() {
+      dsl::ConcreteFormat::Json => {
+        self.builder.read_buf("node.json").and_then(|r| {
+          from_serde_json_result(serde_json::from_reader(r)).and_then(|json: Value| {
+            from_json_result(dsl::Node::try_from(&json))
+          })
+        })
+      },
+      dsl::ConcreteFormat::Proto => {
+        self.read_bytes("node.pb").and_then(|buf| {
+          from_proto_result(ml_bundle::Node::decode(buf.as_slice()).map_err(proto::Error::from)).and_then(|pb| {
+            from_proto_result(dsl::Node::try_from(&pb))
+          })
+        })
+      }
+    }
   }
 
   pub fn read_dsl_model(&self) -> Result<dsl::Model> {
-    self.builder.read_buf("model.json").and_then(|r| {
-      from_serde_json_result(serde_json::from_reader(r)).and_then(|json: Value| {
-        from_json_result(dsl::Model::try_from(&json))
-      })
-    })
+    match self.read_format() {
+      dsl::ConcreteFormat::Json => {
+        self.builder.read_buf("model.json").and_then(|r| {
+          from_serde_json_result(serde_json::from_reader(r)).and_then(|json: Value| {
+            from_json_result(dsl::Model::try_from(&json))
+          })
+        })
+      },
+      dsl::ConcreteFormat::Proto => {
+        self.read_bytes("model.pb").and_then(|buf| {
+          from_proto_result(ml_bundle::Model::decode(buf.as_slice()).map_err(proto::Error::from)).and_then(|pb| {
+            from_proto_result(dsl::Model::try_from(&pb))
+          })
+        })
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::env;
+  use std::io::{Read, Write};
+  use uuid::Uuid;
+
+  #[test]
+  fn test_zip_builder() {
+    let path = env::temp_dir().join(format!("mleap-{}.zip", Uuid::new_v4()));
+
+    {
+      let builder = ZipBuilder::try_create(&path).unwrap();
+      let mut out = builder.write("bundle.json").unwrap();
+      out.write_all(b"bundle").unwrap();
+
+      let child = builder.try_next("root").and_then(|b| b.try_next("child.node")).unwrap();
+      child.write("model.json").unwrap().write_all(b"child model").unwrap();
+      drop(out);
+
+      builder.finish().unwrap();
+    }
+
+    let builder = open_builder(&path).unwrap();
+    let mut bundle = String::new();
+    builder.read("bundle.json").unwrap().read_to_string(&mut bundle).unwrap();
+    assert_eq!(bundle, "bundle");
+
+    let mut model = String::new();
+    builder.try_next("root").and_then(|b| b.try_next("child.node")).and_then(|b| b.read("model.json")).unwrap().
+      read_to_string(&mut model).unwrap();
+    assert_eq!(model, "child model");
+    assert!(builder.read("missing.json").is_err());
+
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_zip_writes_after_commit() {
+    let path = env::temp_dir().join(format!("mleap-{}.zip", Uuid::new_v4()));
+    let builder = ZipBuilder::try_create(&path).unwrap();
+
+    let mut out = builder.write("bundle.json").unwrap();
+    out.write_all(b"bundle").unwrap();
+    out.flush().unwrap();
+    assert!(out.write_all(b"more").is_err());
+
+    let mut open = builder.write("model.json").unwrap();
+    open.write_all(b"model").unwrap();
+    assert!(builder.finish().is_err());
+    assert!(open.write_all(b"more").is_err());
+    assert!(open.flush().is_err());
+    assert!(builder.write("node.json").is_err());
+
+    drop(open);
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_memory_builder() {
+    let builder = MemoryBuilder::new();
+    builder.write("bundle.json").unwrap().write_all(b"bundle").unwrap();
+    builder.try_next("root").and_then(|b| b.write("node.json")).unwrap().write_all(b"node").unwrap();
+
+    let entries = builder.to_map();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries["root/node.json"], b"node".to_vec());
+
+    let zipped = MemoryBuilder::try_from_zip(builder.try_to_zip().unwrap()).unwrap();
+    let mut node = String::new();
+    zipped.try_next("root").and_then(|b| b.read("node.json")).unwrap().read_to_string(&mut node).unwrap();
+    assert_eq!(node, "node");
+    assert!(zipped.read("node.json").is_err());
   }
 }
@@ -0,0 +1,183 @@
+use std::any::*;
+
+use bundle::tform::{self, tree, DefaultNode};
+use bundle::tform::tree::Tree;
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+pub const OP: &'static DecisionTreeClassifierOp = &DecisionTreeClassifierOp { };
+
+#[derive(Clone)]
+pub struct DecisionTreeClassifierModel {
+  tree: Tree,
+  num_features: usize,
+  num_classes: usize
+}
+
+pub struct DecisionTreeClassifier {
+  name: String,
+  features_col: String,
+  prediction_col: String,
+  probability_col: Option<String>,
+  raw_prediction_col: Option<String>,
+  model: DecisionTreeClassifierModel
+}
+
+pub struct DecisionTreeClassifierOp { }
+
+impl DecisionTreeClassifierModel {
+  pub fn try_new(nodes: &[dsl::TreeNode],
+                 num_features: usize,
+                 num_classes: usize) -> Option<DecisionTreeClassifierModel> {
+    Tree::try_new(nodes).and_then(|tree| {
+      if tree.feature_count() <= num_features && tree.all_leaves(|v| v.len() == num_classes) {
+        Some(DecisionTreeClassifierModel {
+          tree: tree,
+          num_features: num_features,
+          num_classes: num_classes
+        })
+      } else {
+        None
+      }
+    })
+  }
+
+  pub fn tree(&self) -> &Tree { &self.tree }
+  pub fn num_features(&self) -> usize { self.num_features }
+  pub fn num_classes(&self) -> usize { self.num_classes }
+
+  /// Leaves carry the impurity stats of the training rows that reached
+  /// them, which is the raw prediction.
+  pub fn try_raw_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<&[f64]> {
+    self.tree.try_predict(features)
+  }
+}
+
+impl DecisionTreeClassifier {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             probability_col: Option<String>,
+             raw_prediction_col: Option<String>,
+             model: DecisionTreeClassifierModel) -> DecisionTreeClassifier {
+    DecisionTreeClassifier {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      probability_col: probability_col,
+      raw_prediction_col: raw_prediction_col,
+      model: model
+    }
+  }
+}
+
+impl OpNode for DecisionTreeClassifier {
+  fn op(&self) -> &'static str { "decision_tree_classifier" }
+}
+
+impl frame::Transformer for DecisionTreeClassifier {
+  fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
+    tform::classify(frame,
+                    &self.features_col,
+                    &self.prediction_col,
+                    self.probability_col.as_ref(),
+                    self.raw_prediction_col.as_ref(),
+                    |features| {
+      self.model.try_raw_predict(features).map(|raw| {
+        let probability = tree::probability(raw);
+        (tree::argmax(&probability) as f64, probability, raw.to_vec())
+      })
+    })
+  }
+}
+
+impl DefaultNode for DecisionTreeClassifier {
+  fn name(&self) -> &str { &self.name }
+  fn model(&self) -> &Any { &self.model as &Any }
+
+  fn create_shape(&self) -> dsl::Shape {
+    let mut outputs = vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))];
+
+    if let Some(ref col) = self.probability_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("probability")));
+    }
+
+    if let Some(ref col) = self.raw_prediction_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("raw_prediction")));
+    }
+
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))], outputs)
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    tform::classifier_output_schema(&self.prediction_col,
+                                    self.probability_col.as_ref(),
+                                    self.raw_prediction_col.as_ref(),
+                                    self.model.num_classes)
+  }
+}
+
+impl Op for DecisionTreeClassifierOp {
+  type Node = Box<tform::DefaultNode>;
+
+  fn type_id(&self) -> TypeId { TypeId::of::<DecisionTreeClassifier>() }
+  fn op(&self) -> &'static str { "decision_tree_classifier" }
+
+  fn name<'a>(&self, node: &'a Self::Node) -> &'a str { node.name() }
+
+  fn model<'a>(&self, node: &'a Self::Node) -> &'a Any { DefaultNode::model(node.as_ref()) }
+
+  fn store_model(&self,
+                 obj: &Any,
+                 model: &mut dsl::Model,
+                 ctx: &Context<Self::Node>) -> Result<()> {
+    obj.downcast_ref::<DecisionTreeClassifierModel>().map(|dt| {
+      model.with_attr("num_features", dsl::Attribute::Basic(dsl::BasicValue::Long(dt.num_features as i64))).
+        with_attr("num_classes", dsl::Attribute::Basic(dsl::BasicValue::Long(dt.num_classes as i64)));
+      ctx.write_dsl_tree(&dt.tree.to_dsl())
+    }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a DecisionTreeClassifierModel".to_string())))
+  }
+
+  fn load_model(&self,
+                model: &dsl::Model,
+                ctx: &Context<Self::Node>) -> Result<Box<Any>> {
+    ctx.read_dsl_tree().and_then(|nodes| {
+      model.get_long("num_features").and_then(|num_features| {
+        model.get_long("num_classes").and_then(|num_classes| {
+          DecisionTreeClassifierModel::try_new(&nodes, num_features as usize, num_classes as usize)
+        })
+      }).map(|x| Ok(Box::new(x) as Box<Any>)).unwrap_or_else(|| Err(Error::InvalidModel("Invalid DecisionTreeClassifierModel".to_string())))
+    })
+  }
+
+  fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
+    node.create_node()
+  }
+
+  fn load(&self,
+          node: &dsl::Node,
+          model: Box<Any>,
+          _ctx: &Context<Self::Node>) -> Result<Self::Node> {
+    model.downcast::<DecisionTreeClassifierModel>().
+      map_err(|_| Error::DowncastError(String::from(""))).
+      and_then(|dt| {
+      node.shape().get_io("features", "prediction").map(move |(i, o)| {
+        let shape = node.shape();
+
+        Ok(Box::new(DecisionTreeClassifier {
+          name: node.name().to_string(),
+          features_col: i.name().to_string(),
+          prediction_col: o.name().to_string(),
+          probability_col: shape.get_output("probability").map(|s| s.name().to_string()),
+          raw_prediction_col: shape.get_output("raw_prediction").map(|s| s.name().to_string()),
+          model: *dt
+        }) as Box<DefaultNode>)
+      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading DecisionTreeClassifier"))))
+    })
+  }
+}
@@ -0,0 +1,154 @@
+use std::any::*;
+
+use bundle::tform::{self, DefaultNode};
+use bundle::tform::tree::Tree;
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+pub const OP: &'static DecisionTreeRegressionOp = &DecisionTreeRegressionOp { };
+
+#[derive(Clone)]
+pub struct DecisionTreeRegressionModel {
+  tree: Tree,
+  num_features: usize
+}
+
+pub struct DecisionTreeRegression {
+  name: String,
+  features_col: String,
+  prediction_col: String,
+  model: DecisionTreeRegressionModel
+}
+
+pub struct DecisionTreeRegressionOp { }
+
+impl DecisionTreeRegressionModel {
+  pub fn try_new(nodes: &[dsl::TreeNode], num_features: usize) -> Option<DecisionTreeRegressionModel> {
+    Tree::try_new(nodes).and_then(|tree| {
+      if tree.feature_count() <= num_features && tree.all_leaves(|v| !v.is_empty()) {
+        Some(DecisionTreeRegressionModel {
+          tree: tree,
+          num_features: num_features
+        })
+      } else {
+        None
+      }
+    })
+  }
+
+  pub fn tree(&self) -> &Tree { &self.tree }
+  pub fn num_features(&self) -> usize { self.num_features }
+
+  pub fn try_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<f64> {
+    self.tree.try_predict(features).map(|values| values[0])
+  }
+}
+
+impl DecisionTreeRegression {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             model: DecisionTreeRegressionModel) -> DecisionTreeRegression {
+    DecisionTreeRegression {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      model: model
+    }
+  }
+}
+
+impl OpNode for DecisionTreeRegression {
+  fn op(&self) -> &'static str { "decision_tree_regression" }
+}
+
+impl frame::Transformer for DecisionTreeRegression {
+  fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
+    frame.try_dense_double_tensors(&self.features_col).and_then(|features_data| {
+      let mut predictions: Vec<f64> = Vec::with_capacity(features_data.len());
+
+      for features in features_data.iter() {
+        match self.model.try_predict(features) {
+          Ok(p) => predictions.push(p),
+          Err(err) => return Err(err)
+        }
+      }
+
+      Ok(predictions)
+    }).and_then(|predictions| {
+      frame.try_with_doubles(self.prediction_col.clone(), predictions).map(|_| ())
+    })
+  }
+}
+
+impl DefaultNode for DecisionTreeRegression {
+  fn name(&self) -> &str { &self.name }
+  fn model(&self) -> &Any { &self.model as &Any }
+
+  fn create_shape(&self) -> dsl::Shape {
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))],
+    vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))])
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(vec![frame::StructField::required(&self.prediction_col, frame::DataType::scalar(frame::BasicType::Double))])
+  }
+}
+
+impl Op for DecisionTreeRegressionOp {
+  type Node = Box<tform::DefaultNode>;
+
+  fn type_id(&self) -> TypeId { TypeId::of::<DecisionTreeRegression>() }
+  fn op(&self) -> &'static str { "decision_tree_regression" }
+
+  fn name<'a>(&self, node: &'a Self::Node) -> &'a str { node.name() }
+
+  fn model<'a>(&self, node: &'a Self::Node) -> &'a Any { DefaultNode::model(node.as_ref()) }
+
+  fn store_model(&self,
+                 obj: &Any,
+                 model: &mut dsl::Model,
+                 ctx: &Context<Self::Node>) -> Result<()> {
+    obj.downcast_ref::<DecisionTreeRegressionModel>().map(|dt| {
+      model.with_attr("num_features", dsl::Attribute::Basic(dsl::BasicValue::Long(dt.num_features as i64)));
+      ctx.write_dsl_tree(&dt.tree.to_dsl())
+    }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a DecisionTreeRegressionModel".to_string())))
+  }
+
+  fn load_model(&self,
+                model: &dsl::Model,
+                ctx: &Context<Self::Node>) -> Result<Box<Any>> {
+    ctx.read_dsl_tree().and_then(|nodes| {
+      model.get_long("num_features").and_then(|num_features| {
+        DecisionTreeRegressionModel::try_new(&nodes, num_features as usize)
+      }).map(|x| Ok(Box::new(x) as Box<Any>)).unwrap_or_else(|| Err(Error::InvalidModel("Invalid DecisionTreeRegressionModel".to_string())))
+    })
+  }
+
+  fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
+    node.create_node()
+  }
+
+  fn load(&self,
+          node: &dsl::Node,
+          model: Box<Any>,
+          _ctx: &Context<Self::Node>) -> Result<Self::Node> {
+    model.downcast::<DecisionTreeRegressionModel>().
+      map_err(|_| Error::DowncastError(String::from(""))).
+      and_then(|dt| {
+      node.shape().get_io("features", "prediction").map(move |(i, o)| {
+        Ok(Box::new(DecisionTreeRegression {
+          name: node.name().to_string(),
+          features_col: i.name().to_string(),
+          prediction_col: o.name().to_string(),
+          model: *dt
+        }) as Box<DefaultNode>)
+      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading DecisionTreeRegression"))))
+    })
+  }
+}
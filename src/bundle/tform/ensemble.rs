@@ -0,0 +1,116 @@
+use bundle::tform::{DefaultNode, decision_tree_regression, decision_tree_classifier};
+use bundle::tform::tree::Tree;
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+/// Decision tree models that can be members of an ensemble.
+pub trait TreeModel: Clone + 'static {
+  fn tree(&self) -> &Tree;
+  fn to_node(&self, name: String) -> Box<DefaultNode>;
+}
+
+/// Trees of a forest or boosted model, stored as child nodes named in the
+/// `trees` attribute the same way a pipeline stores its children.
+pub struct Ensemble<T: TreeModel> {
+  trees: Vec<T>,
+  weights: Vec<f64>,
+  feature_count: usize
+}
+
+impl TreeModel for decision_tree_regression::DecisionTreeRegressionModel {
+  fn tree(&self) -> &Tree { decision_tree_regression::DecisionTreeRegressionModel::tree(self) }
+
+  fn to_node(&self, name: String) -> Box<DefaultNode> {
+    Box::new(decision_tree_regression::DecisionTreeRegression::new(name,
+                                                                   String::from("features"),
+                                                                   String::from("prediction"),
+                                                                   self.clone()))
+  }
+}
+
+impl TreeModel for decision_tree_classifier::DecisionTreeClassifierModel {
+  fn tree(&self) -> &Tree { decision_tree_classifier::DecisionTreeClassifierModel::tree(self) }
+
+  fn to_node(&self, name: String) -> Box<DefaultNode> {
+    Box::new(decision_tree_classifier::DecisionTreeClassifier::new(name,
+                                                                   String::from("features"),
+                                                                   String::from("prediction"),
+                                                                   None,
+                                                                   None,
+                                                                   self.clone()))
+  }
+}
+
+impl<T: TreeModel> Ensemble<T> {
+  pub fn try_new(trees: Vec<T>, weights: Vec<f64>) -> Option<Ensemble<T>> {
+    if trees.is_empty() || trees.len() != weights.len() {
+      return None;
+    }
+
+    let feature_count = trees.iter().map(|t| t.tree().feature_count()).max().unwrap_or(0);
+
+    Some(Ensemble {
+      trees: trees,
+      weights: weights,
+      feature_count: feature_count
+    })
+  }
+
+  pub fn trees(&self) -> &[T] { &self.trees }
+  pub fn weights(&self) -> &[f64] { &self.weights }
+
+  /// Checks the features once so the trees can be evaluated unchecked.
+  pub fn try_features<'b>(&self, features: &'b dsl::DenseTensor<f64>) -> frame::Result<&'b [f64]> {
+    if features.values().len() < self.feature_count {
+      Err(frame::Error::TransformError(format!("Expected at least {} features, got {}",
+                                               self.feature_count,
+                                               features.values().len())))
+    } else {
+      Ok(features.values())
+    }
+  }
+
+  pub fn store(&self, model: &mut dsl::Model, ctx: &Context<Box<DefaultNode>>) -> Result<()> {
+    let names: Vec<String> = (0..self.trees.len()).map(|i| format!("tree{}", i)).collect();
+
+    for (name, tree) in names.iter().zip(self.trees.iter()) {
+      let r = ctx.try_next(&format!("{}.node", name)).and_then(|ctx| {
+        ctx.write_node_and_model(&tree.to_node(name.clone()))
+      });
+
+      match r {
+        Ok(_) => { },
+        Err(err) => return Err(err)
+      }
+    }
+
+    model.with_attr("trees", dsl::Attribute::Array(dsl::VectorValue::String(names))).
+      with_attr("tree_weights", dsl::Attribute::Array(dsl::VectorValue::Double(self.weights.clone())));
+    Ok(())
+  }
+
+  pub fn load(model: &dsl::Model, ctx: &Context<Box<DefaultNode>>) -> Result<Ensemble<T>> {
+    model.get_string_vector("trees").map(|names| {
+      let mut trees: Vec<T> = Vec::with_capacity(names.len());
+
+      for name in names.iter() {
+        let r = ctx.try_next(&format!("{}.node", name)).and_then(|ctx| ctx.read_node()).and_then(|node| {
+          DefaultNode::model(node.as_ref()).downcast_ref::<T>().
+            map(|t| Ok(t.clone())).
+            unwrap_or_else(|| Err(Error::InvalidModel(format!("Unexpected tree type for {}", name))))
+        });
+
+        match r {
+          Ok(tree) => trees.push(tree),
+          Err(err) => return Err(err)
+        }
+      }
+
+      let weights = model.get_double_vector("tree_weights").map(|w| w.to_vec()).unwrap_or_else(|| vec![1.0; trees.len()]);
+      Self::try_new(trees, weights).
+        map(|e| Ok(e)).
+        unwrap_or_else(|| Err(Error::InvalidModel(String::from("Ensemble needs one weight per tree"))))
+    }).unwrap_or_else(|| Err(Error::InvalidModel(String::from("Invalid ensemble, no trees"))))
+  }
+}
@@ -66,9 +66,17 @@ impl DefaultNode for External {
   fn model(&self) -> &Any { &self.model as &Any }
 
   fn create_shape(&self) -> dsl::Shape {
-    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("feautres"))],
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))],
     vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))])
   }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, None)
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(vec![frame::StructField::required(&self.prediction_col, frame::DataType::scalar(frame::BasicType::Double))])
+  }
 }
 
 impl Op for ExternalOp {
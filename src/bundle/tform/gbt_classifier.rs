@@ -0,0 +1,179 @@
+use std::any::*;
+
+use bundle::tform::{self, DefaultNode};
+use bundle::tform::decision_tree_regression::DecisionTreeRegressionModel;
+use bundle::tform::ensemble::Ensemble;
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+pub const OP: &'static GbtClassifierOp = &GbtClassifierOp { };
+
+pub struct GbtClassifierModel {
+  ensemble: Ensemble<DecisionTreeRegressionModel>,
+  num_features: usize
+}
+
+pub struct GbtClassifier {
+  name: String,
+  features_col: String,
+  prediction_col: String,
+  probability_col: Option<String>,
+  raw_prediction_col: Option<String>,
+  model: GbtClassifierModel
+}
+
+pub struct GbtClassifierOp { }
+
+impl GbtClassifierModel {
+  pub fn new(ensemble: Ensemble<DecisionTreeRegressionModel>, num_features: usize) -> GbtClassifierModel {
+    GbtClassifierModel {
+      ensemble: ensemble,
+      num_features: num_features
+    }
+  }
+
+  /// Weighted sum of the regression trees, the margin for class 1.
+  pub fn try_margin(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<f64> {
+    self.ensemble.try_features(features).map(|features| {
+      self.ensemble.trees().iter().zip(self.ensemble.weights().iter()).map(|(t, w)| {
+        w * t.tree().predict(features)[0]
+      }).sum()
+    })
+  }
+
+  // boosting with log loss doubles the margin in the sigmoid, as in Spark
+  pub fn margin_to_probability(margin: f64) -> f64 {
+    1.0 / (1.0 + (-2.0 * margin).exp())
+  }
+}
+
+impl GbtClassifier {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             probability_col: Option<String>,
+             raw_prediction_col: Option<String>,
+             model: GbtClassifierModel) -> GbtClassifier {
+    GbtClassifier {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      probability_col: probability_col,
+      raw_prediction_col: raw_prediction_col,
+      model: model
+    }
+  }
+}
+
+impl OpNode for GbtClassifier {
+  fn op(&self) -> &'static str { "gbt_classifier" }
+}
+
+impl frame::Transformer for GbtClassifier {
+  fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
+    tform::classify(frame,
+                    &self.features_col,
+                    &self.prediction_col,
+                    self.probability_col.as_ref(),
+                    self.raw_prediction_col.as_ref(),
+                    |features| {
+      self.model.try_margin(features).map(|margin| {
+        let p = GbtClassifierModel::margin_to_probability(margin);
+        (if margin > 0.0 { 1.0 } else { 0.0 }, vec![1.0 - p, p], vec![-margin, margin])
+      })
+    })
+  }
+}
+
+impl DefaultNode for GbtClassifier {
+  fn name(&self) -> &str { &self.name }
+  fn model(&self) -> &Any { &self.model as &Any }
+
+  fn create_shape(&self) -> dsl::Shape {
+    let mut outputs = vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))];
+
+    if let Some(ref col) = self.probability_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("probability")));
+    }
+
+    if let Some(ref col) = self.raw_prediction_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("raw_prediction")));
+    }
+
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))], outputs)
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    tform::classifier_output_schema(&self.prediction_col,
+                                    self.probability_col.as_ref(),
+                                    self.raw_prediction_col.as_ref(),
+                                    2)
+  }
+}
+
+impl Op for GbtClassifierOp {
+  type Node = Box<tform::DefaultNode>;
+
+  fn type_id(&self) -> TypeId { TypeId::of::<GbtClassifier>() }
+  fn op(&self) -> &'static str { "gbt_classifier" }
+
+  fn name<'a>(&self, node: &'a Self::Node) -> &'a str { node.name() }
+
+  fn model<'a>(&self, node: &'a Self::Node) -> &'a Any { DefaultNode::model(node.as_ref()) }
+
+  fn store_model(&self,
+                 obj: &Any,
+                 model: &mut dsl::Model,
+                 ctx: &Context<Self::Node>) -> Result<()> {
+    obj.downcast_ref::<GbtClassifierModel>().map(|gbt| {
+      model.with_attr("num_features", dsl::Attribute::Basic(dsl::BasicValue::Long(gbt.num_features as i64))).
+        with_attr("num_classes", dsl::Attribute::Basic(dsl::BasicValue::Long(2)));
+      gbt.ensemble.store(model, ctx)
+    }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a GbtClassifierModel".to_string())))
+  }
+
+  fn load_model(&self,
+                model: &dsl::Model,
+                ctx: &Context<Self::Node>) -> Result<Box<Any>> {
+    match (model.get_long("num_features"), model.get_long("num_classes").unwrap_or(2)) {
+      (Some(num_features), 2) => {
+        Ensemble::load(model, ctx).map(|ensemble| {
+          Box::new(GbtClassifierModel::new(ensemble, num_features as usize)) as Box<Any>
+        })
+      },
+      (Some(_), _) => Err(Error::InvalidModel("GbtClassifier only supports binary classification".to_string())),
+      _ => Err(Error::InvalidModel("Invalid GbtClassifierModel".to_string()))
+    }
+  }
+
+  fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
+    node.create_node()
+  }
+
+  fn load(&self,
+          node: &dsl::Node,
+          model: Box<Any>,
+          _ctx: &Context<Self::Node>) -> Result<Self::Node> {
+    model.downcast::<GbtClassifierModel>().
+      map_err(|_| Error::DowncastError(String::from(""))).
+      and_then(|gbt| {
+      node.shape().get_io("features", "prediction").map(move |(i, o)| {
+        let shape = node.shape();
+
+        Ok(Box::new(GbtClassifier {
+          name: node.name().to_string(),
+          features_col: i.name().to_string(),
+          prediction_col: o.name().to_string(),
+          probability_col: shape.get_output("probability").map(|s| s.name().to_string()),
+          raw_prediction_col: shape.get_output("raw_prediction").map(|s| s.name().to_string()),
+          model: *gbt
+        }) as Box<DefaultNode>)
+      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading GbtClassifier"))))
+    })
+  }
+}
@@ -0,0 +1,149 @@
+use std::any::*;
+
+use bundle::tform::{self, DefaultNode};
+use bundle::tform::decision_tree_regression::DecisionTreeRegressionModel;
+use bundle::tform::ensemble::Ensemble;
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+pub const OP: &'static GbtRegressionOp = &GbtRegressionOp { };
+
+pub struct GbtRegressionModel {
+  ensemble: Ensemble<DecisionTreeRegressionModel>,
+  num_features: usize
+}
+
+pub struct GbtRegression {
+  name: String,
+  features_col: String,
+  prediction_col: String,
+  model: GbtRegressionModel
+}
+
+pub struct GbtRegressionOp { }
+
+impl GbtRegressionModel {
+  pub fn new(ensemble: Ensemble<DecisionTreeRegressionModel>, num_features: usize) -> GbtRegressionModel {
+    GbtRegressionModel {
+      ensemble: ensemble,
+      num_features: num_features
+    }
+  }
+
+  pub fn try_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<f64> {
+    self.ensemble.try_features(features).map(|features| {
+      self.ensemble.trees().iter().zip(self.ensemble.weights().iter()).map(|(t, w)| {
+        w * t.tree().predict(features)[0]
+      }).sum()
+    })
+  }
+}
+
+impl GbtRegression {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             model: GbtRegressionModel) -> GbtRegression {
+    GbtRegression {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      model: model
+    }
+  }
+}
+
+impl OpNode for GbtRegression {
+  fn op(&self) -> &'static str { "gbt_regression" }
+}
+
+impl frame::Transformer for GbtRegression {
+  fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
+    frame.try_dense_double_tensors(&self.features_col).and_then(|features_data| {
+      let mut predictions: Vec<f64> = Vec::with_capacity(features_data.len());
+
+      for features in features_data.iter() {
+        match self.model.try_predict(features) {
+          Ok(p) => predictions.push(p),
+          Err(err) => return Err(err)
+        }
+      }
+
+      Ok(predictions)
+    }).and_then(|predictions| {
+      frame.try_with_doubles(self.prediction_col.clone(), predictions).map(|_| ())
+    })
+  }
+}
+
+impl DefaultNode for GbtRegression {
+  fn name(&self) -> &str { &self.name }
+  fn model(&self) -> &Any { &self.model as &Any }
+
+  fn create_shape(&self) -> dsl::Shape {
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))],
+    vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))])
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(vec![frame::StructField::required(&self.prediction_col, frame::DataType::scalar(frame::BasicType::Double))])
+  }
+}
+
+impl Op for GbtRegressionOp {
+  type Node = Box<tform::DefaultNode>;
+
+  fn type_id(&self) -> TypeId { TypeId::of::<GbtRegression>() }
+  fn op(&self) -> &'static str { "gbt_regression" }
+
+  fn name<'a>(&self, node: &'a Self::Node) -> &'a str { node.name() }
+
+  fn model<'a>(&self, node: &'a Self::Node) -> &'a Any { DefaultNode::model(node.as_ref()) }
+
+  fn store_model(&self,
+                 obj: &Any,
+                 model: &mut dsl::Model,
+                 ctx: &Context<Self::Node>) -> Result<()> {
+    obj.downcast_ref::<GbtRegressionModel>().map(|gbt| {
+      model.with_attr("num_features", dsl::Attribute::Basic(dsl::BasicValue::Long(gbt.num_features as i64)));
+      gbt.ensemble.store(model, ctx)
+    }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a GbtRegressionModel".to_string())))
+  }
+
+  fn load_model(&self,
+                model: &dsl::Model,
+                ctx: &Context<Self::Node>) -> Result<Box<Any>> {
+    model.get_long("num_features").map(|num_features| {
+      Ensemble::load(model, ctx).map(|ensemble| {
+        Box::new(GbtRegressionModel::new(ensemble, num_features as usize)) as Box<Any>
+      })
+    }).unwrap_or_else(|| Err(Error::InvalidModel("Invalid GbtRegressionModel".to_string())))
+  }
+
+  fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
+    node.create_node()
+  }
+
+  fn load(&self,
+          node: &dsl::Node,
+          model: Box<Any>,
+          _ctx: &Context<Self::Node>) -> Result<Self::Node> {
+    model.downcast::<GbtRegressionModel>().
+      map_err(|_| Error::DowncastError(String::from(""))).
+      and_then(|gbt| {
+      node.shape().get_io("features", "prediction").map(move |(i, o)| {
+        Ok(Box::new(GbtRegression {
+          name: node.name().to_string(),
+          features_col: i.name().to_string(),
+          prediction_col: o.name().to_string(),
+          model: *gbt
+        }) as Box<DefaultNode>)
+      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading GbtRegression"))))
+    })
+  }
+}
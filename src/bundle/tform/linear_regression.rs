@@ -22,21 +22,71 @@ pub struct LinearRegression {
 pub struct LinearRegressionOp { }
 
 impl LinearRegressionModel {
+  pub fn new(coefficients: dsl::DenseTensor<f64>, intercept: f64) -> LinearRegressionModel {
+    LinearRegressionModel {
+      coefficients: coefficients,
+      intercept: intercept
+    }
+  }
+
+  pub fn num_features(&self) -> usize { self.coefficients.values().len() }
+
+  pub fn try_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<f64> {
+    self.check_features(features.values().len()).map(|_| self.predict(features))
+  }
+
+  pub fn try_predict_sparse(&self, features: &dsl::SparseTensor<f64>) -> frame::Result<f64> {
+    self.check_features(features.dense_len()).map(|_| self.predict_sparse(features))
+  }
+
+  fn check_features(&self, len: usize) -> frame::Result<()> {
+    if len == self.num_features() {
+      Ok(())
+    } else {
+      Err(frame::Error::TransformError(format!("Expected {} features, got {}", self.num_features(), len)))
+    }
+  }
+
   pub fn predict(&self, features: &dsl::DenseTensor<f64>) -> f64 {
     let dot: f64 = features.values().iter().zip(self.coefficients.values().iter()).map(|(a, b)| a * b).sum();
     dot + self.intercept
   }
+
+  pub fn predict_sparse(&self, features: &dsl::SparseTensor<f64>) -> f64 {
+    let coefficients = self.coefficients.values();
+    let dot: f64 = features.indices().iter().zip(features.values().iter()).map(|(i, v)| {
+      coefficients.get(*i).map(|c| c * v).unwrap_or(0.0)
+    }).sum();
+    dot + self.intercept
+  }
+}
+
+impl LinearRegression {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             model: LinearRegressionModel) -> LinearRegression {
+    LinearRegression {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      model: model
+    }
+  }
 }
+
 impl OpNode for LinearRegression {
   fn op(&self) -> &'static str { "linear_regression" }
 }
 
 impl frame::Transformer for LinearRegression {
   fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
-    frame.try_double_tensors(&self.features_col).map(|features_data| {
-      features_data.iter().map(|features| {
-        self.model.predict(features)
-      }).collect::<Vec<f64>>()
+    frame.try_col(&self.features_col).and_then(|col| col.try_non_null()).and_then(|col| {
+      match col.data() {
+        &frame::ColData::DoubleTensor(ref v) => v.iter().map(|features| self.model.try_predict(features)).collect::<frame::Result<Vec<f64>>>(),
+        &frame::ColData::SparseDoubleTensor(ref v) => v.iter().map(|features| self.model.try_predict_sparse(features)).collect::<frame::Result<Vec<f64>>>(),
+        _ => Err(frame::Error::InvalidType(String::from("LinearRegression: Expecting double tensor features")))
+      }
     }).and_then(|predictions| {
       frame.try_with_doubles(self.prediction_col.clone(), predictions).map(|_| ())
     })
@@ -48,9 +98,17 @@ impl DefaultNode for LinearRegression {
   fn model(&self) -> &Any { &self.model as &Any }
 
   fn create_shape(&self) -> dsl::Shape {
-    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("feautres"))],
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))],
     vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))])
   }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features()))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(vec![frame::StructField::required(&self.prediction_col, frame::DataType::scalar(frame::BasicType::Double))])
+  }
 }
 
 impl Op for LinearRegressionOp {
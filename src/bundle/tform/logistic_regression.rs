@@ -0,0 +1,254 @@
+use std::any::*;
+
+use bundle::tform::{self, DefaultNode};
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+pub const OP: &'static LogisticRegressionOp = &LogisticRegressionOp { };
+
+pub struct LogisticRegressionModel {
+  coefficients: dsl::DenseTensor<f64>,
+  intercepts: Vec<f64>,
+  threshold: f64
+}
+
+pub struct LogisticRegression {
+  name: String,
+  features_col: String,
+  prediction_col: String,
+  probability_col: Option<String>,
+  raw_prediction_col: Option<String>,
+  model: LogisticRegressionModel
+}
+
+pub struct LogisticRegressionOp { }
+
+impl LogisticRegressionModel {
+  pub fn binary(coefficients: dsl::DenseTensor<f64>, intercept: f64, threshold: f64) -> LogisticRegressionModel {
+    LogisticRegressionModel {
+      coefficients: coefficients,
+      intercepts: vec![intercept],
+      threshold: threshold
+    }
+  }
+
+  pub fn multinomial(coefficient_matrix: dsl::DenseTensor<f64>, intercept_vector: Vec<f64>) -> LogisticRegressionModel {
+    LogisticRegressionModel {
+      coefficients: coefficient_matrix,
+      intercepts: intercept_vector,
+      threshold: 0.5
+    }
+  }
+
+  pub fn is_multinomial(&self) -> bool { self.coefficients.dimensions().len() == 2 }
+
+  pub fn num_classes(&self) -> usize {
+    if self.is_multinomial() { self.intercepts.len() } else { 2 }
+  }
+
+  pub fn num_features(&self) -> usize {
+    if self.is_multinomial() { self.coefficients.dimensions()[1] } else { self.coefficients.values().len() }
+  }
+
+  pub fn try_raw_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<Vec<f64>> {
+    let num_features = self.num_features();
+
+    if features.values().len() != num_features {
+      return Err(frame::Error::TransformError(format!("Expected {} features, got {}",
+                                                      num_features,
+                                                      features.values().len())));
+    }
+
+    if self.is_multinomial() {
+      // a matrix without columns has no rows to chunk, every margin is 0
+      if num_features == 0 {
+        return Ok(self.intercepts.clone());
+      }
+
+      Ok(self.coefficients.values().chunks(num_features).zip(self.intercepts.iter()).map(|(row, intercept)| {
+        Self::margin(features.values(), row) + intercept
+      }).collect())
+    } else {
+      let margin = Self::margin(features.values(), self.coefficients.values()) + self.intercepts[0];
+      Ok(vec![-margin, margin])
+    }
+  }
+
+  pub fn raw_to_probability(&self, raw: &[f64]) -> Vec<f64> {
+    if self.is_multinomial() {
+      let max = raw.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+      let exps: Vec<f64> = raw.iter().map(|r| (r - max).exp()).collect();
+      let sum: f64 = exps.iter().sum();
+      exps.iter().map(|e| e / sum).collect()
+    } else {
+      let p = 1.0 / (1.0 + (-raw[1]).exp());
+      vec![1.0 - p, p]
+    }
+  }
+
+  pub fn probability_to_prediction(&self, probability: &[f64]) -> f64 {
+    if self.is_multinomial() {
+      tform::tree::argmax(probability) as f64
+    } else if probability[1] > self.threshold {
+      1.0
+    } else {
+      0.0
+    }
+  }
+
+  fn margin(features: &[f64], coefficients: &[f64]) -> f64 {
+    features.iter().zip(coefficients.iter()).map(|(a, b)| a * b).sum()
+  }
+}
+
+impl LogisticRegression {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             probability_col: Option<String>,
+             raw_prediction_col: Option<String>,
+             model: LogisticRegressionModel) -> LogisticRegression {
+    LogisticRegression {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      probability_col: probability_col,
+      raw_prediction_col: raw_prediction_col,
+      model: model
+    }
+  }
+}
+
+impl OpNode for LogisticRegression {
+  fn op(&self) -> &'static str { "logistic_regression" }
+}
+
+impl frame::Transformer for LogisticRegression {
+  fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
+    tform::classify(frame,
+                    &self.features_col,
+                    &self.prediction_col,
+                    self.probability_col.as_ref(),
+                    self.raw_prediction_col.as_ref(),
+                    |features| {
+      self.model.try_raw_predict(features).map(|raw| {
+        let probability = self.model.raw_to_probability(&raw);
+        (self.model.probability_to_prediction(&probability), probability, raw)
+      })
+    })
+  }
+}
+
+impl DefaultNode for LogisticRegression {
+  fn name(&self) -> &str { &self.name }
+  fn model(&self) -> &Any { &self.model as &Any }
+
+  fn create_shape(&self) -> dsl::Shape {
+    let mut outputs = vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))];
+
+    if let Some(ref col) = self.probability_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("probability")));
+    }
+
+    if let Some(ref col) = self.raw_prediction_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("raw_prediction")));
+    }
+
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))], outputs)
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features()))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    tform::classifier_output_schema(&self.prediction_col,
+                                    self.probability_col.as_ref(),
+                                    self.raw_prediction_col.as_ref(),
+                                    self.model.num_classes())
+  }
+}
+
+impl Op for LogisticRegressionOp {
+  type Node = Box<tform::DefaultNode>;
+
+  fn type_id(&self) -> TypeId { TypeId::of::<LogisticRegression>() }
+  fn op(&self) -> &'static str { "logistic_regression" }
+
+  fn name<'a>(&self, node: &'a Self::Node) -> &'a str { node.name() }
+
+  fn model<'a>(&self, node: &'a Self::Node) -> &'a Any { DefaultNode::model(node.as_ref()) }
+
+  fn store_model(&self,
+                 obj: &Any,
+                 model: &mut dsl::Model,
+                 _ctx: &Context<Self::Node>) -> Result<()> {
+    obj.downcast_ref::<LogisticRegressionModel>().map(|lr| {
+      model.with_attr("num_classes", dsl::Attribute::Basic(dsl::BasicValue::Long(lr.num_classes() as i64)));
+
+      if lr.is_multinomial() {
+        model.with_attr("coefficient_matrix", dsl::Attribute::Tensor(dsl::TensorValue::Double(lr.coefficients.clone()))).
+          with_attr("intercept_vector", dsl::Attribute::Tensor(dsl::TensorValue::Double(dsl::DenseTensor::new(vec![lr.intercepts.len()], lr.intercepts.clone()))));
+      } else {
+        model.with_attr("coefficients", dsl::Attribute::Tensor(dsl::TensorValue::Double(lr.coefficients.clone()))).
+          with_attr("intercept", dsl::Attribute::Basic(dsl::BasicValue::Double(lr.intercepts[0]))).
+          with_attr("threshold", dsl::Attribute::Basic(dsl::BasicValue::Double(lr.threshold)));
+      }
+
+      Ok(())
+    }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a LogisticRegressionModel".to_string())))
+  }
+
+  fn load_model(&self,
+                model: &dsl::Model,
+                _ctx: &Context<Self::Node>) -> Result<Box<Any>> {
+    let binary = model.get_double("intercept").and_then(|i| {
+      model.get_double_tensor("coefficients").map(|c| {
+        LogisticRegressionModel::binary(c.clone(), i, model.get_double("threshold").unwrap_or(0.5))
+      })
+    });
+
+    let multinomial = || {
+      model.get_double_tensor("intercept_vector").and_then(|i| {
+        model.get_double_tensor("coefficient_matrix").and_then(|c| {
+          if c.dimensions().len() == 2 && c.dimensions()[0] == i.values().len() {
+            Some(LogisticRegressionModel::multinomial(c.clone(), i.values().to_vec()))
+          } else {
+            None
+          }
+        })
+      })
+    };
+
+    binary.or_else(multinomial).
+      map(|x| Ok(Box::new(x) as Box<Any>)).
+      unwrap_or_else(|| Err(Error::InvalidModel("Invalid LogisticRegressionModel".to_string())))
+  }
+
+  fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
+    node.create_node()
+  }
+
+  fn load(&self,
+          node: &dsl::Node,
+          model: Box<Any>,
+          _ctx: &Context<Self::Node>) -> Result<Self::Node> {
+    model.downcast::<LogisticRegressionModel>().
+      map_err(|_| Error::DowncastError(String::from(""))).
+      and_then(|lr| {
+      node.shape().get_io("features", "prediction").map(move |(i, o)| {
+        let shape = node.shape();
+
+        Ok(Box::new(LogisticRegression {
+          name: node.name().to_string(),
+          features_col: i.name().to_string(),
+          prediction_col: o.name().to_string(),
+          probability_col: shape.get_output("probability").map(|s| s.name().to_string()),
+          raw_prediction_col: shape.get_output("raw_prediction").map(|s| s.name().to_string()),
+          model: *lr
+        }) as Box<DefaultNode>)
+      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading LogisticRegression"))))
+    })
+  }
+}
@@ -1,14 +1,23 @@
 pub mod linear_regression;
+pub mod logistic_regression;
 pub mod string_indexer;
 pub mod vector_assembler;
 pub mod one_hot_encoder;
 pub mod standard_scaler;
 pub mod pipeline;
+pub mod tree;
+pub mod decision_tree_regression;
+pub mod decision_tree_classifier;
+pub mod ensemble;
+pub mod random_forest_regression;
+pub mod random_forest_classifier;
+pub mod gbt_regression;
+pub mod gbt_classifier;
 pub mod external;
 
 use std::any::{Any, TypeId};
 
-use bundle::ser::OpNode;
+use bundle::ser::{self, OpNode};
 use bundle::frame;
 use bundle::dsl;
 
@@ -17,12 +26,133 @@ pub trait DefaultNode: OpNode + frame::Transformer {
   fn model(&self) -> &Any;
 
   fn create_shape(&self) -> dsl::Shape;
+
+  /// Columns the node reads from the frame.
+  fn input_schema(&self) -> frame::Schema;
+  /// Columns the node adds to the frame.
+  fn output_schema(&self) -> frame::Schema;
+
   fn create_node(&self) -> dsl::Node {
     dsl::Node::new(self.name().to_string(), self.create_shape())
   }
 }
 
+/// Prediction column plus the optional probability and raw prediction
+/// columns of classifiers.
+pub fn classifier_output_schema(prediction_col: &str,
+                                probability_col: Option<&String>,
+                                raw_prediction_col: Option<&String>,
+                                num_classes: usize) -> frame::Schema {
+  let mut fields = vec![frame::StructField::required(prediction_col, frame::DataType::scalar(frame::BasicType::Double))];
+
+  for col in probability_col.iter().chain(raw_prediction_col.iter()) {
+    fields.push(frame::StructField::required(col, frame::DataType::tensor(frame::BasicType::Double, Some(vec![num_classes]))));
+  }
+
+  frame::Schema::new(fields)
+}
+
+/// Adds the columns of `classifier_output_schema`, `predict` returns the
+/// prediction, probability and raw prediction of one row of features.
+pub fn classify<F>(frame: &mut frame::LeapFrame,
+                   features_col: &str,
+                   prediction_col: &str,
+                   probability_col: Option<&String>,
+                   raw_prediction_col: Option<&String>,
+                   predict: F) -> frame::Result<()>
+  where F: Fn(&dsl::DenseTensor<f64>) -> frame::Result<(f64, Vec<f64>, Vec<f64>)> {
+  let mut predictions: Vec<f64> = Vec::with_capacity(frame.size());
+  let mut probabilities: Vec<dsl::DenseTensor<f64>> = Vec::with_capacity(frame.size());
+  let mut raw_predictions: Vec<dsl::DenseTensor<f64>> = Vec::with_capacity(frame.size());
+
+  match frame.try_dense_double_tensors(features_col) {
+    Ok(features_data) => {
+      for features in features_data.iter() {
+        let (prediction, probability, raw) = match predict(features) {
+          Ok(p) => p,
+          Err(err) => return Err(err)
+        };
+
+        predictions.push(prediction);
+        probabilities.push(dsl::DenseTensor::new(vec![probability.len()], probability));
+        raw_predictions.push(dsl::DenseTensor::new(vec![raw.len()], raw));
+      }
+    },
+    Err(err) => return Err(err)
+  }
+
+  frame.try_with_doubles(prediction_col.to_string(), predictions).and_then(|frame| {
+    match probability_col {
+      Some(col) => frame.try_with_double_tensors(col.clone(), probabilities),
+      None => Ok(frame)
+    }
+  }).and_then(|frame| {
+    match raw_prediction_col {
+      Some(col) => frame.try_with_double_tensors(col.clone(), raw_predictions),
+      None => Ok(frame)
+    }
+  }).map(|_| ())
+}
+
+/// A single tensor of doubles input, as read by regressions and classifiers.
+pub fn features_schema(features_col: &str, num_features: Option<usize>) -> frame::Schema {
+  frame::Schema::new(vec![frame::StructField::required(features_col,
+                                                       frame::DataType::tensor(frame::BasicType::Double, num_features.map(|n| vec![n])))])
+}
+
 impl OpNode for Box<DefaultNode> {
   fn type_id(&self) -> TypeId { DefaultNode::type_id(self.as_ref()) }
   fn op(&self) -> &'static str { DefaultNode::op(self.as_ref()) }
 }
+
+/// Registry with every op of this crate, external ops are added by the caller.
+pub fn default_registry() -> ser::Registry<'static, Box<DefaultNode>> {
+  let mut registry = ser::Registry::new();
+
+  registry.insert_op(linear_regression::OP);
+  registry.insert_op(logistic_regression::OP);
+  registry.insert_op(string_indexer::OP);
+  registry.insert_op(one_hot_encoder::OP);
+  registry.insert_op(pipeline::OP);
+  registry.insert_op(vector_assembler::OP);
+  registry.insert_op(standard_scaler::OP);
+  registry.insert_op(decision_tree_regression::OP);
+  registry.insert_op(decision_tree_classifier::OP);
+  registry.insert_op(random_forest_regression::OP);
+  registry.insert_op(random_forest_classifier::OP);
+  registry.insert_op(gbt_regression::OP);
+  registry.insert_op(gbt_classifier::OP);
+
+  registry
+}
+
+/// How a transformer treats values it did not see during training.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HandleInvalid {
+  Error,
+  Skip,
+  Keep
+}
+
+impl HandleInvalid {
+  pub fn from_str(s: &str) -> Option<HandleInvalid> {
+    match s {
+      "error" => Some(HandleInvalid::Error),
+      "skip" => Some(HandleInvalid::Skip),
+      "keep" => Some(HandleInvalid::Keep),
+      _ => None
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match *self {
+      HandleInvalid::Error => "error",
+      HandleInvalid::Skip => "skip",
+      HandleInvalid::Keep => "keep"
+    }
+  }
+}
+
+impl Default for HandleInvalid {
+  fn default() -> HandleInvalid { HandleInvalid::Error }
+}
@@ -1,67 +1,168 @@
 use std::any::*;
 
 use bundle::ser::*;
-use bundle::tform::{self, DefaultNode};
+use bundle::tform::{self, DefaultNode, HandleInvalid};
 use bundle::frame;
 use bundle::dsl;
 
 pub const OP: &'static OneHotEncoderOp = &OneHotEncoderOp { };
 
 pub struct OneHotEncoderModel {
-  size: usize
+  sizes: Vec<usize>,
+  drop_last: bool,
+  handle_invalid: HandleInvalid
 }
 
 pub struct OneHotEncoder {
   name: String,
-  input_col: String,
-  output_col: String,
+  input_cols: Vec<String>,
+  output_cols: Vec<String>,
   model: OneHotEncoderModel
 }
 
 pub struct OneHotEncoderOp { }
 
+impl OneHotEncoder {
+  pub fn new(name: String,
+             input_col: String,
+             output_col: String,
+             model: OneHotEncoderModel) -> OneHotEncoder {
+    OneHotEncoder::with_cols(name, vec![input_col], vec![output_col], model)
+  }
+
+  pub fn with_cols(name: String,
+                   input_cols: Vec<String>,
+                   output_cols: Vec<String>,
+                   model: OneHotEncoderModel) -> OneHotEncoder {
+    OneHotEncoder {
+      name: name,
+      input_cols: input_cols,
+      output_cols: output_cols,
+      model: model
+    }
+  }
+}
+
 impl OpNode for OneHotEncoder {
   fn op(&self) -> &'static str { "one_hot_encoder" }
 }
 
 impl OneHotEncoderModel {
-  pub fn try_encode_col_data(&self, data: &frame::ColData) -> frame::Result<frame::ColData> {
-    (match data {
-      &frame::ColData::Byte(ref v) => self.try_encode(v, |x| x as usize),
-      &frame::ColData::Short(ref v) => self.try_encode(v, |x| x as usize),
-      &frame::ColData::Int(ref v) => self.try_encode(v, |x| x as usize),
-      &frame::ColData::Long(ref v) => self.try_encode(v, |x| x as usize),
+  pub fn new(size: usize) -> OneHotEncoderModel {
+    OneHotEncoderModel::with_sizes(vec![size])
+  }
+
+  /// One category size per encoded column.
+  pub fn with_sizes(sizes: Vec<usize>) -> OneHotEncoderModel {
+    OneHotEncoderModel::with_options(sizes, false, HandleInvalid::Error)
+  }
+
+  pub fn with_options(sizes: Vec<usize>, drop_last: bool, handle_invalid: HandleInvalid) -> OneHotEncoderModel {
+    OneHotEncoderModel {
+      sizes: sizes,
+      drop_last: drop_last,
+      handle_invalid: handle_invalid
+    }
+  }
+
+  pub fn sizes(&self) -> &[usize] { &self.sizes }
+  pub fn drop_last(&self) -> bool { self.drop_last }
+  pub fn handle_invalid(&self) -> HandleInvalid { self.handle_invalid }
+
+  /// Length of the encoded vectors for a column: `keep` adds a category
+  /// for invalid indices and `drop_last` removes the last category.
+  pub fn output_size(&self, col: usize) -> usize {
+    let size = match self.handle_invalid {
+      HandleInvalid::Keep => self.sizes[col] + 1,
+      _ => self.sizes[col]
+    };
+
+    if self.drop_last { size - 1 } else { size }
+  }
+
+  /// Category of every row, `None` for rows that are skipped.
+  /// Null rows are handled like invalid indices.
+  pub fn try_indices(&self, col: usize, input: &frame::Col) -> frame::Result<Vec<Option<usize>>> {
+    let validity = input.validity();
+
+    match input.data() {
+      &frame::ColData::Byte(ref v) => self.try_index(col, validity, v, |x| if x >= 0 { Some(x as usize) } else { None }),
+      &frame::ColData::Short(ref v) => self.try_index(col, validity, v, |x| if x >= 0 { Some(x as usize) } else { None }),
+      &frame::ColData::Int(ref v) => self.try_index(col, validity, v, |x| if x >= 0 { Some(x as usize) } else { None }),
+      &frame::ColData::Long(ref v) => self.try_index(col, validity, v, |x| if x >= 0 { Some(x as usize) } else { None }),
+      &frame::ColData::Float(ref v) => self.try_index(col, validity, v, |x| if x >= 0.0 && x.fract() == 0.0 { Some(x as usize) } else { None }),
+      &frame::ColData::Double(ref v) => self.try_index(col, validity, v, |x| if x >= 0.0 && x.fract() == 0.0 { Some(x as usize) } else { None }),
       _ => Err(frame::Error::TransformError(String::from("OneHotEncoder: Expecting numeric input")))
-    }).map(|v| frame::ColData::LongTensor(v))
+    }
   }
 
-  fn try_encode<T: Copy, F>(&self, v: &[T], f: F) -> frame::Result<Vec<dsl::DenseTensor<i64>>>
-    where F: Fn(T) -> usize {
-      let mut tensors: Vec<dsl::DenseTensor<i64>> = Vec::with_capacity(v.len());
+  fn try_index<T: Copy, F>(&self, col: usize, validity: Option<&[bool]>, v: &[T], f: F) -> frame::Result<Vec<Option<usize>>>
+    where F: Fn(T) -> Option<usize> {
+      let size = self.sizes[col];
+      let mut indices: Vec<Option<usize>> = Vec::with_capacity(v.len());
 
-      for raw_v in v.iter() {
-        let index: usize = f(*raw_v);
-        let mut values: Vec<i64> = (0..self.size).map(|_| 0).collect();
+      for (row, raw_v) in v.iter().enumerate() {
+        let index = if validity.map(|v| v[row]).unwrap_or(true) { f(*raw_v) } else { None };
 
-        if index < values.len() {
-          values[index] = 1;
-          tensors.push(dsl::DenseTensor::new(vec![values.len()], values));
-        } else {
-          tensors.push(dsl::DenseTensor::new(vec![values.len()], values));
+        match (index.and_then(|i| if i < size { Some(i) } else { None }), self.handle_invalid) {
+          (Some(i), _) => indices.push(Some(i)),
+          (None, HandleInvalid::Keep) => indices.push(Some(size)),
+          (None, HandleInvalid::Skip) => indices.push(None),
+          (None, HandleInvalid::Error) => return Err(frame::Error::TransformError(format!("OneHotEncoder: Invalid index for column {}", col)))
         }
       }
 
-      Ok(tensors)
+      Ok(indices)
+    }
+
+  pub fn encode(&self, col: usize, index: usize) -> dsl::SparseTensor<f64> {
+    let size = self.output_size(col);
+
+    // the dropped last category encodes as all zeros
+    if index < size {
+      dsl::SparseTensor::new(vec![size], vec![index], vec![1.0])
+    } else {
+      dsl::SparseTensor::new(vec![size], vec![], vec![])
     }
+  }
 }
 
 impl frame::Transformer for OneHotEncoder {
   fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
-    frame.try_col(&self.input_col).and_then(|col| {
-      self.model.try_encode_col_data(col.data())
-    }).and_then(|oh_col| {
-      frame.try_with_col(frame::Col::new(self.output_col.clone(), oh_col)).map(|_| ())
-    })
+    let mut keep: Vec<bool> = vec![true; frame.size()];
+    let mut indices_array: Vec<Vec<Option<usize>>> = Vec::with_capacity(self.input_cols.len());
+
+    for (col, input_col) in self.input_cols.iter().enumerate() {
+      match frame.try_col(input_col).and_then(|c| self.model.try_indices(col, c)) {
+        Ok(indices) => {
+          for (k, i) in keep.iter_mut().zip(indices.iter()) {
+            *k = *k && i.is_some();
+          }
+
+          indices_array.push(indices);
+        },
+        Err(err) => return Err(err)
+      }
+    }
+
+    if keep.iter().any(|k| !k) {
+      if let Err(err) = frame.retain_rows(&keep) {
+        return Err(err);
+      }
+    }
+
+    for (col, (output_col, indices)) in self.output_cols.iter().zip(indices_array.into_iter()).enumerate() {
+      let tensors: Vec<dsl::SparseTensor<f64>> = indices.into_iter().zip(keep.iter()).
+        filter(|&(_, k)| *k).
+        map(|(i, _)| self.model.encode(col, i.unwrap())).
+        collect();
+
+      if let Err(err) = frame.try_with_sparse_double_tensors(output_col.clone(), tensors) {
+        return Err(err);
+      }
+    }
+
+    Ok(())
   }
 }
 
@@ -70,7 +171,25 @@ impl DefaultNode for OneHotEncoder {
   fn model(&self) -> &Any { &self.model as &Any }
 
   fn create_shape(&self) -> dsl::Shape {
-    dsl::Shape::with_standard_io(self.input_col.clone(), self.output_col.clone())
+    if self.input_cols.len() == 1 {
+      dsl::Shape::with_standard_io(self.input_cols[0].clone(), self.output_cols[0].clone())
+    } else {
+      dsl::Shape::with_multi_io(self.input_cols.clone(), self.output_cols.clone())
+    }
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    frame::Schema::new(self.input_cols.iter().map(|col| {
+      // whole numbers of any base, nulls are invalid values handled as
+      // handle_invalid says
+      frame::StructField::optional(col, frame::DataType::numeric(frame::DataShape::Scalar))
+    }).collect())
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(self.output_cols.iter().enumerate().map(|(i, col)| {
+      frame::StructField::required(col, frame::DataType::tensor(frame::BasicType::Double, Some(vec![self.model.output_size(i)])))
+    }).collect())
   }
 }
 
@@ -89,7 +208,15 @@ impl Op for OneHotEncoderOp {
                  model: &mut dsl::Model,
                  _ctx: &Context<Self::Node>) -> Result<()> {
     obj.downcast_ref::<OneHotEncoderModel>().map(|oh| {
-      model.with_attr("size", dsl::Attribute::Basic(dsl::BasicValue::Long(oh.size as i64)));
+      if oh.sizes.len() == 1 {
+        model.with_attr("size", dsl::Attribute::Basic(dsl::BasicValue::Long(oh.sizes[0] as i64)));
+      } else {
+        let sizes = oh.sizes.iter().map(|s| *s as i32).collect();
+        model.with_attr("category_sizes", dsl::Attribute::Array(dsl::VectorValue::Int(sizes)));
+      }
+
+      model.with_attr("drop_last", dsl::Attribute::Basic(dsl::BasicValue::Bool(oh.drop_last))).
+        with_attr("handle_invalid", dsl::Attribute::Basic(dsl::BasicValue::String(oh.handle_invalid.as_str().to_string())));
       Ok(())
     }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a OneHotEncoderModel".to_string())))
   }
@@ -97,11 +224,24 @@ impl Op for OneHotEncoderOp {
   fn load_model(&self,
                 model: &dsl::Model,
                 _ctx: &Context<Self::Node>) -> Result<Box<Any>> {
-    model.get_long("size").map(|i| {
-      Box::new(OneHotEncoderModel {
-        size: i as usize,
-      }) as Box<Any>
-    }).map(|x| Ok(x)).unwrap_or_else(|| Err(Error::InvalidModel("Expected size long".to_string())))
+    let handle_invalid = match model.get_string("handle_invalid") {
+      Some(s) => match HandleInvalid::from_str(s) {
+        Some(h) => h,
+        None => return Err(Error::InvalidModel(format!("Unknown handle_invalid: {}", s)))
+      },
+      None => HandleInvalid::default()
+    };
+    let drop_last = model.get_bool("drop_last").unwrap_or(false);
+
+    model.get_int_vector("category_sizes").map(|sizes| {
+      sizes.iter().map(|s| *s as usize).collect::<Vec<usize>>()
+    }).or_else(|| model.get_long("size").map(|i| vec![i as usize])).map(|sizes| {
+      if sizes.iter().all(|s| *s > 0) {
+        Ok(Box::new(OneHotEncoderModel::with_options(sizes, drop_last, handle_invalid)) as Box<Any>)
+      } else {
+        Err(Error::InvalidModel("OneHotEncoder category sizes must be positive".to_string()))
+      }
+    }).unwrap_or_else(|| Err(Error::InvalidModel("Expected size long or category_sizes".to_string())))
   }
 
   fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
@@ -115,14 +255,19 @@ impl Op for OneHotEncoderOp {
     model.downcast::<OneHotEncoderModel>().
       map_err(|_| Error::DowncastError(String::from(""))).
       and_then(|oh| {
-      node.shape().get_standard_io().map(move |(i, o)| {
-        Ok(Box::new(OneHotEncoder {
-          name: node.name().to_string(),
-          input_col: i.name().to_string(),
-          output_col: o.name().to_string(),
-          model: *oh
-        }) as Box<DefaultNode>)
-      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading OneHotEncoder"))))
+      let io = node.shape().get_standard_io().map(|io| vec![io]).or_else(|| node.shape().get_multi_io());
+
+      match io {
+        Some(ref io) if io.len() == oh.sizes.len() => {
+          Ok(Box::new(OneHotEncoder {
+            name: node.name().to_string(),
+            input_cols: io.iter().map(|&(i, _)| i.name().to_string()).collect(),
+            output_cols: io.iter().map(|&(_, o)| o.name().to_string()).collect(),
+            model: *oh
+          }) as Box<DefaultNode>)
+        },
+        _ => Err(Error::InvalidOp(String::from("Error loading OneHotEncoder")))
+      }
     })
   }
 }
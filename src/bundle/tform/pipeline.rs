@@ -1,4 +1,5 @@
 use std::any::*;
+use std::collections::HashSet;
 
 use bundle::tform::{self, DefaultNode};
 use bundle::ser::*;
@@ -18,6 +19,25 @@ pub struct Pipeline {
 
 pub struct PipelineOp { }
 
+impl PipelineModel {
+  pub fn new(children: Vec<Box<DefaultNode>>) -> PipelineModel {
+    PipelineModel {
+      children: children
+    }
+  }
+
+  pub fn children(&self) -> &[Box<DefaultNode>] { &self.children }
+}
+
+impl Pipeline {
+  pub fn new(name: String, model: PipelineModel) -> Pipeline {
+    Pipeline {
+      name: name,
+      model: model
+    }
+  }
+}
+
 impl OpNode for Pipeline {
   fn op(&self) -> &'static str { "pipeline" }
 }
@@ -42,6 +62,32 @@ impl DefaultNode for Pipeline {
   fn create_shape(&self) -> dsl::Shape {
     dsl::Shape::empty()
   }
+
+  /// Inputs of the children that no earlier child produces.
+  fn input_schema(&self) -> frame::Schema {
+    let mut fields: Vec<frame::StructField> = Vec::new();
+    let mut produced: HashSet<String> = HashSet::new();
+
+    for child in self.model.children.iter() {
+      for field in child.input_schema().fields() {
+        if !produced.contains(field.name()) && !fields.iter().any(|f| f.name() == field.name()) {
+          fields.push(field.clone());
+        }
+      }
+
+      for field in child.output_schema().fields() {
+        produced.insert(field.name().to_string());
+      }
+    }
+
+    frame::Schema::new(fields)
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(self.model.children.iter().flat_map(|child| {
+      child.output_schema().fields().to_vec()
+    }).collect())
+  }
 }
 
 impl Op for PipelineOp {
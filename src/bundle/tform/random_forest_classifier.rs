@@ -0,0 +1,194 @@
+use std::any::*;
+
+use bundle::tform::{self, tree, DefaultNode};
+use bundle::tform::decision_tree_classifier::DecisionTreeClassifierModel;
+use bundle::tform::ensemble::Ensemble;
+use bundle::ser::*;
+use bundle::frame;
+use bundle::dsl;
+
+pub const OP: &'static RandomForestClassifierOp = &RandomForestClassifierOp { };
+
+pub struct RandomForestClassifierModel {
+  ensemble: Ensemble<DecisionTreeClassifierModel>,
+  num_features: usize,
+  num_classes: usize
+}
+
+pub struct RandomForestClassifier {
+  name: String,
+  features_col: String,
+  prediction_col: String,
+  probability_col: Option<String>,
+  raw_prediction_col: Option<String>,
+  model: RandomForestClassifierModel
+}
+
+pub struct RandomForestClassifierOp { }
+
+impl RandomForestClassifierModel {
+  pub fn try_new(ensemble: Ensemble<DecisionTreeClassifierModel>,
+                 num_features: usize,
+                 num_classes: usize) -> Option<RandomForestClassifierModel> {
+    if ensemble.trees().iter().all(|t| t.num_classes() == num_classes) {
+      Some(RandomForestClassifierModel {
+        ensemble: ensemble,
+        num_features: num_features,
+        num_classes: num_classes
+      })
+    } else {
+      None
+    }
+  }
+
+  /// Sums the class probabilities of every tree, scaled by tree weight.
+  pub fn try_raw_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<Vec<f64>> {
+    self.ensemble.try_features(features).map(|features| {
+      let mut raw = vec![0.0; self.num_classes];
+
+      for (t, w) in self.ensemble.trees().iter().zip(self.ensemble.weights().iter()) {
+        let stats = t.tree().predict(features);
+        let total: f64 = stats.iter().sum();
+
+        if total != 0.0 {
+          for (r, s) in raw.iter_mut().zip(stats.iter()) {
+            *r += w * s / total;
+          }
+        }
+      }
+
+      raw
+    })
+  }
+}
+
+impl RandomForestClassifier {
+  pub fn new(name: String,
+             features_col: String,
+             prediction_col: String,
+             probability_col: Option<String>,
+             raw_prediction_col: Option<String>,
+             model: RandomForestClassifierModel) -> RandomForestClassifier {
+    RandomForestClassifier {
+      name: name,
+      features_col: features_col,
+      prediction_col: prediction_col,
+      probability_col: probability_col,
+      raw_prediction_col: raw_prediction_col,
+      model: model
+    }
+  }
+}
+
+impl OpNode for RandomForestClassifier {
+  fn op(&self) -> &'static str { "random_forest_classifier" }
+}
+
+impl frame::Transformer for RandomForestClassifier {
+  fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
+    tform::classify(frame,
+                    &self.features_col,
+                    &self.prediction_col,
+                    self.probability_col.as_ref(),
+                    self.raw_prediction_col.as_ref(),
+                    |features| {
+      self.model.try_raw_predict(features).map(|raw| {
+        let probability = tree::probability(&raw);
+        (tree::argmax(&probability) as f64, probability, raw)
+      })
+    })
+  }
+}
+
+impl DefaultNode for RandomForestClassifier {
+  fn name(&self) -> &str { &self.name }
+  fn model(&self) -> &Any { &self.model as &Any }
+
+  fn create_shape(&self) -> dsl::Shape {
+    let mut outputs = vec![dsl::Socket::new(self.prediction_col.clone(), String::from("prediction"))];
+
+    if let Some(ref col) = self.probability_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("probability")));
+    }
+
+    if let Some(ref col) = self.raw_prediction_col {
+      outputs.push(dsl::Socket::new(col.clone(), String::from("raw_prediction")));
+    }
+
+    dsl::Shape::new(vec![dsl::Socket::new(self.features_col.clone(), String::from("features"))], outputs)
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    tform::features_schema(&self.features_col, Some(self.model.num_features))
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    tform::classifier_output_schema(&self.prediction_col,
+                                    self.probability_col.as_ref(),
+                                    self.raw_prediction_col.as_ref(),
+                                    self.model.num_classes)
+  }
+}
+
+impl Op for RandomForestClassifierOp {
+  type Node = Box<tform::DefaultNode>;
+
+  fn type_id(&self) -> TypeId { TypeId::of::<RandomForestClassifier>() }
+  fn op(&self) -> &'static str { "random_forest_classifier" }
+
+  fn name<'a>(&self, node: &'a Self::Node) -> &'a str { node.name() }
+
+  fn model<'a>(&self, node: &'a Self::Node) -> &'a Any { DefaultNode::model(node.as_ref()) }
+
+  fn store_model(&self,
+                 obj: &Any,
+                 model: &mut dsl::Model,
+                 ctx: &Context<Self::Node>) -> Result<()> {
+    obj.downcast_ref::<RandomForestClassifierModel>().map(|rf| {
+      model.with_attr("num_features", dsl::Attribute::Basic(dsl::BasicValue::Long(rf.num_features as i64))).
+        with_attr("num_classes", dsl::Attribute::Basic(dsl::BasicValue::Long(rf.num_classes as i64)));
+      rf.ensemble.store(model, ctx)
+    }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a RandomForestClassifierModel".to_string())))
+  }
+
+  fn load_model(&self,
+                model: &dsl::Model,
+                ctx: &Context<Self::Node>) -> Result<Box<Any>> {
+    match (model.get_long("num_features"), model.get_long("num_classes")) {
+      (Some(num_features), Some(num_classes)) => {
+        Ensemble::load(model, ctx).and_then(|ensemble| {
+          RandomForestClassifierModel::try_new(ensemble, num_features as usize, num_classes as usize).
+            map(|x| Ok(Box::new(x) as Box<Any>)).
+            unwrap_or_else(|| Err(Error::InvalidModel("Trees do not match num_classes".to_string())))
+        })
+      },
+      _ => Err(Error::InvalidModel("Invalid RandomForestClassifierModel".to_string()))
+    }
+  }
+
+  fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
+    node.create_node()
+  }
+
+  fn load(&self,
+          node: &dsl::Node,
+          model: Box<Any>,
+          _ctx: &Context<Self::Node>) -> Result<Self::Node> {
+    model.downcast::<RandomForestClassifierModel>().
+      map_err(|_| Error::DowncastError(String::from(""))).
+      and_then(|rf| {
+      node.shape().get_io("features", "prediction").map(move |(i, o)| {
+        let shape = node.shape();
+
+        Ok(Box::new(RandomForestClassifier {
+          name: node.name().to_string(),
+          features_col: i.name().to_string(),
+          prediction_col: o.name().to_string(),
+          probability_col: shape.get_output("probability").map(|s| s.name().to_string()),
+          raw_prediction_col: shape.get_output("raw_prediction").map(|s| s.name().to_string()),
+          model: *rf
+        }) as Box<DefaultNode>)
+      }).unwrap_or_else(|| Err(Error::InvalidOp(String::from("Error loading RandomForestClassifier"))))
+    })
+  }
+}
@@ -9,7 +9,8 @@ pub const OP: &'static StandardScalerOp = &StandardScalerOp { };
 
 pub struct StandardScalerModel {
   mean: Option<dsl::DenseTensor<f64>>,
-  std: Option<dsl::DenseTensor<f64>>
+  std: Option<dsl::DenseTensor<f64>>,
+  size: usize
 }
 
 pub struct StandardScaler {
@@ -22,32 +23,89 @@ pub struct StandardScaler {
 pub struct StandardScalerOp { }
 
 impl StandardScalerModel {
-  pub fn try_scale(&self, data: &frame::ColData) -> frame::Result<frame::ColData> {
+  /// Builds a model from Spark's withMean and withStd vectors. At least one
+  /// must be present, and both must be vectors of the same size.
+  pub fn try_new(mean: Option<dsl::DenseTensor<f64>>,
+                 std: Option<dsl::DenseTensor<f64>>) -> Option<StandardScalerModel> {
+    let is_vector = |t: &dsl::DenseTensor<f64>| t.dimensions() == &[t.values().len()];
+    let size = match (&mean, &std) {
+      (&Some(ref m), &Some(ref s)) if m.values().len() == s.values().len() => Some(m.values().len()),
+      (&Some(ref m), &None) => Some(m.values().len()),
+      (&None, &Some(ref s)) => Some(s.values().len()),
+      _ => None
+    };
+    let vectors = mean.iter().chain(std.iter()).all(is_vector);
+
+    size.and_then(|size| {
+      if vectors {
+        Some(StandardScalerModel {
+          mean: mean,
+          std: std,
+          size: size
+        })
+      } else {
+        None
+      }
+    })
+  }
+
+  pub fn size(&self) -> usize { self.size }
+
+  /// Null rows scale to NaN.
+  pub fn try_scale(&self, data: &frame::ColData, validity: Option<&[bool]>) -> frame::Result<frame::ColData> {
     match data {
       &frame::ColData::DoubleTensor(ref data) => {
-        match (&self.mean, &self.std) {
-          (&None, &Some(ref std)) => {
-            let col_data: Vec<dsl::DenseTensor<f64>> = data.iter().map(|features| {
-              let vs: Vec<f64> = features.values().iter().zip(std.values().iter()).map(|(f, s)| {
-                if *s != 0.0 {
-                  f * (1.0 / *s)
-                } else {
-                  0.0
-                }
-              }).collect();
-
-              dsl::DenseTensor::new(vec![vs.len()], vs)
-            }).collect();
-
-            Ok(frame::ColData::DoubleTensor(col_data))
-          },
-          _ => Err(frame::Error::TransformError(String::from("Must provide a mean or stddev for standard scaler")))
+        let mut col_data: Vec<dsl::DenseTensor<f64>> = Vec::with_capacity(data.len());
+
+        for (row, features) in data.iter().enumerate() {
+          if !validity.map(|v| v[row]).unwrap_or(true) {
+            col_data.push(dsl::DenseTensor::new(vec![self.size], vec![f64::NAN; self.size]));
+            continue;
+          }
+
+          if features.values().len() != self.size {
+            return Err(frame::Error::TransformError(format!("StandardScaler: expected vectors of size {}, got {}",
+                                                            self.size,
+                                                            features.values().len())));
+          }
+
+          let vs: Vec<f64> = features.values().iter().enumerate().map(|(i, f)| {
+            let centered = match self.mean {
+              Some(ref mean) => f - mean.values()[i],
+              None => *f
+            };
+
+            match self.std {
+              Some(ref std) if std.values()[i] != 0.0 => centered * (1.0 / std.values()[i]),
+              Some(_) => 0.0,
+              None => centered
+            }
+          }).collect();
+
+          col_data.push(dsl::DenseTensor::new(vec![vs.len()], vs));
         }
+
+        Ok(frame::ColData::DoubleTensor(col_data))
       },
       _ => Err(frame::Error::InvalidType(String::from("Expected double tensors")))
     }
   }
 }
+
+impl StandardScaler {
+  pub fn new(name: String,
+             input_col: String,
+             output_col: String,
+             model: StandardScalerModel) -> StandardScaler {
+    StandardScaler {
+      name: name,
+      input_col: input_col,
+      output_col: output_col,
+      model: model
+    }
+  }
+}
+
 impl OpNode for StandardScaler {
   fn op(&self) -> &'static str { "standard_scaler" }
 }
@@ -55,9 +113,16 @@ impl OpNode for StandardScaler {
 impl frame::Transformer for StandardScaler {
   fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
     frame.try_col(&self.input_col).and_then(|features_col| {
-      self.model.try_scale(features_col.data())
-    }).and_then(|scaled| {
-      frame.try_with_col(frame::Col::new(self.output_col.clone(), scaled)).map(|_| ())
+      self.model.try_scale(features_col.data(), features_col.validity()).map(|scaled| {
+        let col = frame::Col::new(self.output_col.clone(), scaled);
+
+        match features_col.validity() {
+          Some(validity) => col.with_validity(validity.to_vec()),
+          None => col
+        }
+      })
+    }).and_then(|col| {
+      frame.try_with_col(col).map(|_| ())
     })
   }
 }
@@ -69,6 +134,17 @@ impl DefaultNode for StandardScaler {
   fn create_shape(&self) -> dsl::Shape {
     dsl::Shape::with_standard_io(self.input_col.clone(), self.output_col.clone())
   }
+
+  // nulls stay null
+  fn input_schema(&self) -> frame::Schema {
+    let data_type = frame::DataType::tensor(frame::BasicType::Double, Some(vec![self.model.size]));
+    frame::Schema::new(vec![frame::StructField::optional(&self.input_col, data_type)])
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    let data_type = frame::DataType::tensor(frame::BasicType::Double, Some(vec![self.model.size]));
+    frame::Schema::new(vec![frame::StructField::optional(&self.output_col, data_type)])
+  }
 }
 
 impl Op for StandardScalerOp {
@@ -103,10 +179,9 @@ impl Op for StandardScalerOp {
     let mean = model.get_double_tensor("mean").map(|m| m.clone());
     let std = model.get_double_tensor("std").map(|m| m.clone());
 
-    Ok(Box::new(StandardScalerModel {
-      mean: mean,
-      std: std
-    }) as Box<Any>)
+    StandardScalerModel::try_new(mean, std).
+      map(|ss| Ok(Box::new(ss) as Box<Any>)).
+      unwrap_or_else(|| Err(Error::InvalidModel(String::from("StandardScaler needs a mean or std vector of matching size"))))
   }
 
   fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
@@ -1,7 +1,7 @@
 use std::any::*;
 use std::collections::HashMap;
 
-use bundle::tform::{self, DefaultNode};
+use bundle::tform::{self, DefaultNode, HandleInvalid};
 use bundle::ser::*;
 use bundle::frame;
 use bundle::dsl;
@@ -10,14 +10,15 @@ pub const OP: &'static StringIndexerOp = &StringIndexerOp { };
 
 #[derive(Clone)]
 pub struct StringIndexerModel {
-  labels: Vec<String>,
-  label_to_index: HashMap<String, usize>
+  labels_array: Vec<Vec<String>>,
+  label_to_index: Vec<HashMap<String, usize>>,
+  handle_invalid: HandleInvalid
 }
 
 pub struct StringIndexer {
   name: String,
-  input_col: String,
-  output_col: String,
+  input_cols: Vec<String>,
+  output_cols: Vec<String>,
   model: StringIndexerModel
 }
 
@@ -25,43 +26,125 @@ pub struct StringIndexerOp { }
 
 impl StringIndexerModel {
   pub fn new(labels: Vec<String>) -> StringIndexerModel {
-    let label_to_index = labels.iter().
-      enumerate().
-      map(|(i, s)| (s.clone(), i)).
-      collect();
+    StringIndexerModel::with_handle_invalid(labels, HandleInvalid::Error)
+  }
+
+  pub fn with_handle_invalid(labels: Vec<String>, handle_invalid: HandleInvalid) -> StringIndexerModel {
+    StringIndexerModel::with_labels_array(vec![labels], handle_invalid)
+  }
+
+  /// One list of labels per indexed column.
+  pub fn with_labels_array(labels_array: Vec<Vec<String>>, handle_invalid: HandleInvalid) -> StringIndexerModel {
+    let label_to_index = labels_array.iter().map(|labels| {
+      labels.iter().
+        enumerate().
+        map(|(i, s)| (s.clone(), i)).
+        collect()
+    }).collect();
 
     StringIndexerModel {
-      labels: labels,
-      label_to_index: label_to_index
+      labels_array: labels_array,
+      label_to_index: label_to_index,
+      handle_invalid: handle_invalid
     }
   }
 
-  pub fn try_encode(&self, label: &str) -> frame::Result<usize> {
-    self.label_to_index.get(label).map(|x| Ok(*x)).unwrap_or_else(|| {
-      Err(frame::Error::TransformError(String::from(format!("Invalid label: {}", label))))
-    })
+  pub fn labels_array(&self) -> &[Vec<String>] { &self.labels_array }
+  pub fn handle_invalid(&self) -> HandleInvalid { self.handle_invalid }
+
+  /// Unseen labels are an error, unless the model keeps them as
+  /// `labels.len()` or skips them, which returns `None`.
+  pub fn try_encode(&self, col: usize, label: &str) -> frame::Result<Option<usize>> {
+    match self.label_to_index[col].get(label) {
+      Some(i) => Ok(Some(*i)),
+      None => self.try_encode_invalid(col).map_err(|_| frame::Error::TransformError(format!("Invalid label: {}", label)))
+    }
+  }
+
+  /// Null labels are handled like unseen ones.
+  pub fn try_encode_invalid(&self, col: usize) -> frame::Result<Option<usize>> {
+    match self.handle_invalid {
+      HandleInvalid::Keep => Ok(Some(self.labels_array[col].len())),
+      HandleInvalid::Skip => Ok(None),
+      HandleInvalid::Error => Err(frame::Error::TransformError(String::from("Null label")))
+    }
+  }
+}
+
+impl StringIndexer {
+  pub fn new(name: String,
+             input_col: String,
+             output_col: String,
+             model: StringIndexerModel) -> StringIndexer {
+    StringIndexer::with_cols(name, vec![input_col], vec![output_col], model)
+  }
+
+  pub fn with_cols(name: String,
+                   input_cols: Vec<String>,
+                   output_cols: Vec<String>,
+                   model: StringIndexerModel) -> StringIndexer {
+    StringIndexer {
+      name: name,
+      input_cols: input_cols,
+      output_cols: output_cols,
+      model: model
+    }
   }
 }
+
 impl OpNode for StringIndexer {
   fn op(&self) -> &'static str { "string_indexer" }
 }
 
 impl frame::Transformer for StringIndexer {
   fn transform(&self, frame: &mut frame::LeapFrame) -> frame::Result<()> {
-    frame.try_strings(&self.input_col).and_then(|labels| {
-      let mut indices: Vec<i32> = Vec::with_capacity(labels.len());
+    let mut keep: Vec<bool> = vec![true; frame.size()];
+    let mut indices_array: Vec<Vec<Option<i32>>> = Vec::with_capacity(self.input_cols.len());
 
-      for label in labels.iter() {
-        match self.model.try_encode(label) {
-          Ok(r) => indices.push(r as i32),
+    for (col, input_col) in self.input_cols.iter().enumerate() {
+      let (input, labels) = match frame.try_col(input_col).and_then(|c| c.try_strings().map(|labels| (c, labels))) {
+        Ok(r) => r,
+        Err(err) => return Err(err)
+      };
+      let mut indices: Vec<Option<i32>> = Vec::with_capacity(labels.len());
+
+      for (row, (label, k)) in labels.iter().zip(keep.iter_mut()).enumerate() {
+        let encoded = if input.is_valid(row) {
+          self.model.try_encode(col, label)
+        } else {
+          self.model.try_encode_invalid(col)
+        };
+
+        match encoded {
+          Ok(r) => {
+            *k = *k && r.is_some();
+            indices.push(r.map(|i| i as i32));
+          },
           Err(err) => return Err(err)
         }
       }
 
-      Ok(indices)
-    }).and_then(|indices| {
-      frame.try_with_ints(self.output_col.clone(), indices).map(|_| ())
-    })
+      indices_array.push(indices);
+    }
+
+    if keep.iter().any(|k| !k) {
+      if let Err(err) = frame.retain_rows(&keep) {
+        return Err(err);
+      }
+    }
+
+    for (output_col, indices) in self.output_cols.iter().zip(indices_array.into_iter()) {
+      let indices: Vec<i32> = indices.into_iter().zip(keep.iter()).
+        filter(|&(_, k)| *k).
+        map(|(i, _)| i.unwrap()).
+        collect();
+
+      if let Err(err) = frame.try_with_ints(output_col.clone(), indices) {
+        return Err(err);
+      }
+    }
+
+    Ok(())
   }
 }
 
@@ -70,7 +153,24 @@ impl DefaultNode for StringIndexer {
   fn model(&self) -> &Any { &self.model as &Any }
 
   fn create_shape(&self) -> dsl::Shape {
-    dsl::Shape::with_standard_io(String::from(self.input_col.as_ref()), String::from(self.output_col.as_ref()))
+    if self.input_cols.len() == 1 {
+      dsl::Shape::with_standard_io(self.input_cols[0].clone(), self.output_cols[0].clone())
+    } else {
+      dsl::Shape::with_multi_io(self.input_cols.clone(), self.output_cols.clone())
+    }
+  }
+
+  fn input_schema(&self) -> frame::Schema {
+    frame::Schema::new(self.input_cols.iter().map(|col| {
+      // nulls are invalid values, handled as handle_invalid says
+      frame::StructField::optional(col, frame::DataType::scalar(frame::BasicType::String))
+    }).collect())
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(self.output_cols.iter().map(|col| {
+      frame::StructField::required(col, frame::DataType::scalar(frame::BasicType::Int))
+    }).collect())
   }
 }
 
@@ -89,7 +189,17 @@ impl Op for StringIndexerOp {
                  model: &mut dsl::Model,
                  _ctx: &Context<Self::Node>) -> Result<()> {
     obj.downcast_ref::<StringIndexerModel>().map(|si| {
-      model.with_attr("labels", dsl::Attribute::Array(dsl::VectorValue::String(si.labels.clone())));
+      if si.labels_array.len() == 1 {
+        model.with_attr("labels", dsl::Attribute::Array(dsl::VectorValue::String(si.labels_array[0].clone())));
+      } else {
+        model.with_attr("labels_length", dsl::Attribute::Basic(dsl::BasicValue::Int(si.labels_array.len() as i32)));
+
+        for (i, labels) in si.labels_array.iter().enumerate() {
+          model.with_attr(&format!("labels_array_{}", i), dsl::Attribute::Array(dsl::VectorValue::String(labels.clone())));
+        }
+      }
+
+      model.with_attr("handle_invalid", dsl::Attribute::Basic(dsl::BasicValue::String(si.handle_invalid.as_str().to_string())));
       Ok(())
     }).unwrap_or_else(|| Err(Error::InvalidOp("Expected a StringIndexerModel".to_string())))
   }
@@ -97,9 +207,27 @@ impl Op for StringIndexerOp {
   fn load_model(&self,
                 model: &dsl::Model,
                 _ctx: &Context<Self::Node>) -> Result<Box<Any>> {
-    model.get_string_vector("labels").map(|labels| {
-      Box::new(StringIndexerModel::new(labels.to_vec())) as Box<Any>
-    }).map(|x| Ok(x)).unwrap_or_else(|| Err(Error::InvalidModel("".to_string())))
+    let handle_invalid = match model.get_string("handle_invalid") {
+      Some(s) => match HandleInvalid::from_str(s) {
+        Some(h) => h,
+        None => return Err(Error::InvalidModel(format!("Unknown handle_invalid: {}", s)))
+      },
+      None => HandleInvalid::default()
+    };
+
+    let labels_array: Result<Vec<Vec<String>>> = match model.get_int("labels_length") {
+      Some(n) => (0..n).map(|i| {
+        let name = format!("labels_array_{}", i);
+        model.get_string_vector(&name).map(|labels| labels.to_vec()).
+          ok_or_else(|| Error::InvalidModel(format!("Missing string list attribute {}", name)))
+      }).collect(),
+      None => model.get_string_vector("labels").map(|labels| vec![labels.to_vec()]).
+        ok_or_else(|| Error::InvalidModel("Missing string list attribute labels or labels_length".to_string()))
+    };
+
+    labels_array.map(|labels_array| {
+      Box::new(StringIndexerModel::with_labels_array(labels_array, handle_invalid)) as Box<Any>
+    })
   }
 
   fn node(&self, node: &Self::Node, _ctx: &Context<Self::Node>) -> dsl::Node {
@@ -110,16 +238,20 @@ impl Op for StringIndexerOp {
           node: &dsl::Node,
           model: Box<Any>,
           _ctx: &Context<Self::Node>) -> Result<Self::Node> {
-    model.downcast_ref::<StringIndexerModel>().and_then(|si| {
-      node.shape().get_standard_io().map(|(i, o)| {
-        StringIndexer {
-          name: node.name().to_string(),
-          input_col: i.name().to_string(),
-          output_col: o.name().to_string(),
-          model: si.clone()
-        }
-      })
-    }).map(|x| Ok(Box::new(x) as Box<DefaultNode>)).
-    unwrap_or_else(|| Err(Error::DowncastError(String::from(""))))
+    model.downcast_ref::<StringIndexerModel>().map(|si| {
+      let io = node.shape().get_standard_io().map(|io| vec![io]).or_else(|| node.shape().get_multi_io());
+
+      match io {
+        Some(ref io) if io.len() == si.labels_array.len() => {
+          Ok(Box::new(StringIndexer {
+            name: node.name().to_string(),
+            input_cols: io.iter().map(|&(i, _)| i.name().to_string()).collect(),
+            output_cols: io.iter().map(|&(_, o)| o.name().to_string()).collect(),
+            model: si.clone()
+          }) as Box<DefaultNode>)
+        },
+        _ => Err(Error::InvalidOp(String::from("StringIndexer sockets do not match its labels")))
+      }
+    }).unwrap_or_else(|| Err(Error::DowncastError(String::from(""))))
   }
 }
@@ -0,0 +1,196 @@
+use bundle::dsl;
+use bundle::frame;
+
+#[derive(Clone)]
+enum Node {
+  Internal {
+    split: dsl::Split,
+    right: usize
+  },
+  Leaf(Vec<f64>)
+}
+
+/// A decision tree flattened in pre-order. Internal nodes keep the index
+/// of their right child, the left child is always the next node.
+#[derive(Clone)]
+pub struct Tree {
+  nodes: Vec<Node>,
+  feature_count: usize
+}
+
+impl Tree {
+  pub fn try_new(nodes: &[dsl::TreeNode]) -> Option<Tree> {
+    let mut rights: Vec<usize> = vec![0; nodes.len()];
+
+    Self::link(nodes, &mut rights).and_then(|end| {
+      if end != nodes.len() {
+        return None;
+      }
+
+      let mut feature_count = Some(0);
+      let t_nodes = nodes.iter().zip(rights.into_iter()).map(|(node, right)| {
+        match node {
+          &dsl::TreeNode::Internal(ref split) => {
+            feature_count = feature_count.and_then(|n| split.feature_index().checked_add(1).map(|c| n.max(c)));
+            Node::Internal { split: split.clone(), right: right }
+          },
+          &dsl::TreeNode::Leaf(ref values) => Node::Leaf(values.clone())
+        }
+      }).collect();
+
+      feature_count.map(|feature_count| Tree {
+        nodes: t_nodes,
+        feature_count: feature_count
+      })
+    })
+  }
+
+  // fills in the right child of every internal node and returns the index
+  // just past the tree, without recursing so deep trees can't overflow
+  fn link(nodes: &[dsl::TreeNode], rights: &mut Vec<usize>) -> Option<usize> {
+    // internal nodes whose subtree is not finished yet, a right of 0 means
+    // their left subtree is still being read
+    let mut open: Vec<usize> = Vec::new();
+    let mut i = 0;
+
+    loop {
+      match nodes.get(i) {
+        Some(&dsl::TreeNode::Internal(_)) => {
+          open.push(i);
+          i += 1;
+        },
+        Some(&dsl::TreeNode::Leaf(_)) => {
+          i += 1;
+
+          while let Some(&parent) = open.last() {
+            if rights[parent] == 0 {
+              rights[parent] = i;
+              break;
+            }
+            open.pop();
+          }
+
+          if open.is_empty() {
+            return Some(i);
+          }
+        },
+        None => return None
+      }
+    }
+  }
+
+  pub fn to_dsl(&self) -> Vec<dsl::TreeNode> {
+    self.nodes.iter().map(|node| {
+      match node {
+        &Node::Internal { ref split, .. } => dsl::TreeNode::Internal(split.clone()),
+        &Node::Leaf(ref values) => dsl::TreeNode::Leaf(values.clone())
+      }
+    }).collect()
+  }
+
+  /// Number of features a vector needs for every split to be evaluated.
+  pub fn feature_count(&self) -> usize { self.feature_count }
+
+  pub fn all_leaves<F: Fn(&[f64]) -> bool>(&self, f: F) -> bool {
+    self.nodes.iter().all(|node| {
+      match node {
+        &Node::Leaf(ref values) => f(values),
+        _ => true
+      }
+    })
+  }
+
+  pub fn predict(&self, features: &[f64]) -> &[f64] {
+    let mut i = 0;
+
+    loop {
+      match self.nodes[i] {
+        Node::Leaf(ref values) => return values,
+        Node::Internal { ref split, right } => {
+          i = if split.goes_left(features[split.feature_index()]) { i + 1 } else { right };
+        }
+      }
+    }
+  }
+
+  pub fn try_predict(&self, features: &dsl::DenseTensor<f64>) -> frame::Result<&[f64]> {
+    if features.values().len() < self.feature_count {
+      Err(frame::Error::TransformError(format!("Expected at least {} features, got {}",
+                                               self.feature_count,
+                                               features.values().len())))
+    } else {
+      Ok(self.predict(features.values()))
+    }
+  }
+}
+
+pub fn probability(raw: &[f64]) -> Vec<f64> {
+  let sum: f64 = raw.iter().sum();
+
+  if sum != 0.0 {
+    raw.iter().map(|r| r / sum).collect()
+  } else {
+    raw.to_vec()
+  }
+}
+
+pub fn argmax(values: &[f64]) -> usize {
+  let mut index = 0;
+
+  for (i, v) in values.iter().enumerate() {
+    if *v > values[index] {
+      index = i;
+    }
+  }
+
+  index
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn continuous(feature_index: usize, threshold: f64) -> dsl::TreeNode {
+    dsl::TreeNode::Internal(dsl::Split::Continuous { feature_index: feature_index, threshold: threshold })
+  }
+
+  #[test]
+  fn test_predict() {
+    // x0 <= 1.0 ? (x2 in {1, 3} ? 10 : 20) : 30
+    let nodes = vec![continuous(0, 1.0),
+                     dsl::TreeNode::Internal(dsl::Split::Categorical { feature_index: 2,
+                                                                       num_categories: 4,
+                                                                       categories: vec![1.0, 3.0],
+                                                                       is_left: true }),
+                     dsl::TreeNode::Leaf(vec![10.0]),
+                     dsl::TreeNode::Leaf(vec![20.0]),
+                     dsl::TreeNode::Leaf(vec![30.0])];
+    let tree = Tree::try_new(&nodes).unwrap();
+
+    assert_eq!(tree.feature_count(), 3);
+    assert_eq!(tree.predict(&[1.0, 0.0, 3.0]), &[10.0]);
+    assert_eq!(tree.predict(&[0.5, 0.0, 2.0]), &[20.0]);
+    assert_eq!(tree.predict(&[1.5, 0.0, 3.0]), &[30.0]);
+    assert_eq!(tree.to_dsl(), nodes);
+  }
+
+  #[test]
+  fn test_incomplete_tree() {
+    assert!(Tree::try_new(&[continuous(0, 1.0), dsl::TreeNode::Leaf(vec![1.0])]).is_none());
+    assert!(Tree::try_new(&[dsl::TreeNode::Leaf(vec![1.0]), dsl::TreeNode::Leaf(vec![2.0])]).is_none());
+    assert!(Tree::try_new(&[]).is_none());
+  }
+
+  #[test]
+  fn test_deep_tree() {
+    let depth = 100000;
+    let mut nodes: Vec<dsl::TreeNode> = (0..depth).map(|_| continuous(0, 0.0)).collect();
+    nodes.extend((0..depth + 1).map(|i| dsl::TreeNode::Leaf(vec![i as f64])));
+    let tree = Tree::try_new(&nodes).unwrap();
+
+    assert_eq!(tree.predict(&[0.0]), &[0.0]);
+    assert_eq!(tree.predict(&[1.0]), &[depth as f64]);
+    assert!(Tree::try_new(&nodes[1..]).is_none());
+    assert!(Tree::try_new(&[continuous(usize::max_value(), 0.0), dsl::TreeNode::Leaf(vec![]), dsl::TreeNode::Leaf(vec![])]).is_none());
+  }
+}
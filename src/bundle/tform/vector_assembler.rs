@@ -17,92 +17,270 @@ pub struct VectorAssembler {
 
 pub struct VectorAssemblerOp { }
 
+impl VectorAssembler {
+  pub fn new(name: String, input_cols: Vec<String>, output_col: String) -> VectorAssembler {
+    VectorAssembler {
+      name: name,
+      input_cols: input_cols,
+      output_col: output_col
+    }
+  }
+}
+
 impl OpNode for VectorAssembler {
   fn op(&self) -> &'static str { "vector_assembler" }
 }
 
 impl VectorAssemblerModel {
+  /// Concatenates the input columns into double tensors, which are sparse
+  /// when any of the inputs is.
   pub fn try_assemble(col_names: &[String],
                       frame: &frame::LeapFrame) -> frame::Result<frame::ColData> {
     match frame.try_cols(col_names) {
       Ok(cols) => {
-        let mut t_size: usize = 0;
+        let mut sizes: Vec<usize> = Vec::with_capacity(cols.len());
         for col in cols.iter() {
-          let m_size: Option<usize> = match col.data() {
-            &frame::ColData::Bool(_) => Some(1),
-            &frame::ColData::Byte(_) => Some(1),
-            &frame::ColData::Short(_) => Some(1),
-            &frame::ColData::Int(_) => Some(1),
-            &frame::ColData::Long(_) => Some(1),
-            &frame::ColData::Float(_) => Some(1),
-            &frame::ColData::Double(_) => Some(1),
-
-            &frame::ColData::BoolTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-            &frame::ColData::ByteTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-            &frame::ColData::ShortTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-            &frame::ColData::IntTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-            &frame::ColData::LongTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-            &frame::ColData::FloatTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-            &frame::ColData::DoubleTensor(ref v) => v.first().and_then(|f| f.dimensions().first().map(|x| *x)),
-
-            _ => None
-          };
+          let r_size: frame::Result<usize> = match col.data() {
+            &frame::ColData::Bool(_) => Ok(1),
+            &frame::ColData::Byte(_) => Ok(1),
+            &frame::ColData::Short(_) => Ok(1),
+            &frame::ColData::Int(_) => Ok(1),
+            &frame::ColData::Long(_) => Ok(1),
+            &frame::ColData::Float(_) => Ok(1),
+            &frame::ColData::Double(_) => Ok(1),
 
-          match m_size {
-            Some(size) => t_size += size,
-            None => return Err(frame::Error::TransformError(String::from("")))
-          }
-        }
+            &frame::ColData::BoolTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
+            &frame::ColData::ByteTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
+            &frame::ColData::ShortTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
+            &frame::ColData::IntTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
+            &frame::ColData::LongTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
+            &frame::ColData::FloatTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
+            &frame::ColData::DoubleTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), Some(t.values().len())))),
 
-        let mut vs: Vec<Vec<f64>> = (0..frame.size()).map(|_| {
-          Vec::with_capacity(t_size)
-        }).collect();
+            &frame::ColData::SparseBoolTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
+            &frame::ColData::SparseByteTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
+            &frame::ColData::SparseShortTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
+            &frame::ColData::SparseIntTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
+            &frame::ColData::SparseLongTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
+            &frame::ColData::SparseFloatTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
+            &frame::ColData::SparseDoubleTensor(ref v) => Self::tensor_size(col, v.iter().map(|t| (t.dimensions(), None))),
 
-        for col in cols.iter() {
-          match col.data() {
-            &frame::ColData::Bool(ref v) => Self::assemble_scalar(&mut vs, &v, |x| { if x { 1.0 } else { 0.0 } }),
-            &frame::ColData::Byte(ref v) => Self::assemble_scalar(&mut vs, &v, f64::from),
-            &frame::ColData::Short(ref v) => Self::assemble_scalar(&mut vs, &v, f64::from),
-            &frame::ColData::Int(ref v) => Self::assemble_scalar(&mut vs, &v, f64::from),
-            &frame::ColData::Long(ref v) => Self::assemble_scalar(&mut vs, &v, |x| { x as f64 }),
-            &frame::ColData::Float(ref v) => Self::assemble_scalar(&mut vs, &v, f64::from),
-            &frame::ColData::Double(ref v) => Self::assemble_scalar(&mut vs, &v, f64::from),
-
-            &frame::ColData::BoolTensor(ref v) => Self::assemble_tensor(&mut vs, &v, |x| { if x { 1.0 } else { 0.0 } }),
-            &frame::ColData::ByteTensor(ref v) => Self::assemble_tensor(&mut vs, &v, f64::from),
-            &frame::ColData::ShortTensor(ref v) => Self::assemble_tensor(&mut vs, &v, f64::from),
-            &frame::ColData::IntTensor(ref v) => Self::assemble_tensor(&mut vs, &v, f64::from),
-            &frame::ColData::LongTensor(ref v) => Self::assemble_tensor(&mut vs, &v, |x| { x as f64 }),
-            &frame::ColData::FloatTensor(ref v) => Self::assemble_tensor(&mut vs, &v, f64::from),
-            &frame::ColData::DoubleTensor(ref v) => Self::assemble_tensor(&mut vs, &v, f64::from),
-
-            _ => { } // do nothing
+            _ => Err(frame::Error::TransformError(format!("Column {} is not numeric or a tensor of numbers", col.name())))
+          };
+
+          match r_size {
+            Ok(size) => sizes.push(size),
+            Err(err) => return Err(err)
           }
         }
 
-        // TODO: this to_vec is very inefficient
-        let tensors = vs.drain(0..).map(|v| dsl::DenseTensor::new(vec![t_size], v)).collect();
-        Ok(frame::ColData::DoubleTensor(tensors))
+        if cols.iter().any(|col| Self::is_sparse(col.data())) {
+          Ok(Self::assemble_sparse(&cols, &sizes, frame.size()))
+        } else {
+          Ok(Self::assemble_dense(&cols, &sizes, frame.size()))
+        }
       },
       Err(err) => return Err(err)
     }
   }
 
-  fn assemble_scalar<T: Copy, F>(vs: &mut [Vec<f64>], vi: &[T], f: F)
+  // every valid row of a column must hold a tensor of the size of the first
+  // one, columns without valid rows take it from their schema; dense tensors
+  // also pass the number of their values
+  fn tensor_size<'a, I>(col: &frame::Col, tensors: I) -> frame::Result<usize>
+    where I: Iterator<Item=(&'a [usize], Option<usize>)> {
+      let mut size: Option<usize> = None;
+      let mut rows = 0;
+
+      for (row, (dims, len)) in tensors.enumerate() {
+        rows += 1;
+        if !col.is_valid(row) { continue }
+
+        let n = match dims.iter().fold(Some(1usize), |acc, d| acc.and_then(|n| n.checked_mul(*d))) {
+          Some(n) => n,
+          None => return Err(frame::Error::TransformError(format!("Column {} has a tensor with dimensions {:?} that are too large in row {}", col.name(), dims, row)))
+        };
+
+        if len.map(|l| l != n).unwrap_or(false) {
+          return Err(frame::Error::TransformError(format!("Column {} has a tensor with dimensions {:?} and {} values in row {}", col.name(), dims, len.unwrap_or(0), row)));
+        }
+
+        match size {
+          Some(s) if s != n => return Err(frame::Error::TransformError(format!("Column {} has a tensor of size {} in row {}, expected {}", col.name(), n, row, s))),
+          _ => size = Some(n)
+        }
+      }
+
+      if rows == 0 {
+        return Ok(0)
+      }
+
+      let schema_size = match col.field().data_type().shape() {
+        &frame::DataShape::Tensor(Some(ref dims)) => dims.iter().fold(Some(1usize), |acc, d| acc.and_then(|n| n.checked_mul(*d))),
+        _ => None
+      };
+      size.or(schema_size).map(Ok).
+        unwrap_or_else(|| Err(frame::Error::TransformError(format!("Column {} has no valid rows or tensor dimensions in its schema", col.name()))))
+    }
+
+  fn is_sparse(data: &frame::ColData) -> bool {
+    match data {
+      &frame::ColData::SparseBoolTensor(_) => true,
+      &frame::ColData::SparseByteTensor(_) => true,
+      &frame::ColData::SparseShortTensor(_) => true,
+      &frame::ColData::SparseIntTensor(_) => true,
+      &frame::ColData::SparseLongTensor(_) => true,
+      &frame::ColData::SparseFloatTensor(_) => true,
+      &frame::ColData::SparseDoubleTensor(_) => true,
+      _ => false
+    }
+  }
+
+  fn assemble_dense(cols: &[&frame::Col], sizes: &[usize], rows: usize) -> frame::ColData {
+    let t_size: usize = sizes.iter().sum();
+    let mut vs: Vec<Vec<f64>> = (0..rows).map(|_| {
+      Vec::with_capacity(t_size)
+    }).collect();
+
+    for (col, size) in cols.iter().zip(sizes.iter()) {
+      match col.data() {
+        &frame::ColData::Bool(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, |x| { if x { 1.0 } else { 0.0 } }),
+        &frame::ColData::Byte(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, f64::from),
+        &frame::ColData::Short(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, f64::from),
+        &frame::ColData::Int(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, f64::from),
+        &frame::ColData::Long(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, |x| { x as f64 }),
+        &frame::ColData::Float(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, f64::from),
+        &frame::ColData::Double(ref v) => Self::assemble_scalar(&mut vs, col.validity(), &v, f64::from),
+
+        &frame::ColData::BoolTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, |x| { if x { 1.0 } else { 0.0 } }),
+        &frame::ColData::ByteTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, f64::from),
+        &frame::ColData::ShortTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, f64::from),
+        &frame::ColData::IntTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, f64::from),
+        &frame::ColData::LongTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, |x| { x as f64 }),
+        &frame::ColData::FloatTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, f64::from),
+        &frame::ColData::DoubleTensor(ref v) => Self::assemble_tensor(&mut vs, col.validity(), *size, &v, f64::from),
+
+        _ => { } // do nothing
+      }
+    }
+
+    // TODO: this to_vec is very inefficient
+    let tensors = vs.drain(0..).map(|v| dsl::DenseTensor::new(vec![t_size], v)).collect();
+    frame::ColData::DoubleTensor(tensors)
+  }
+
+  fn assemble_sparse(cols: &[&frame::Col], sizes: &[usize], rows: usize) -> frame::ColData {
+    let mut vs: Vec<(Vec<usize>, Vec<f64>)> = (0..rows).map(|_| (Vec::new(), Vec::new())).collect();
+    let mut offset = 0;
+
+    for (col, size) in cols.iter().zip(sizes.iter()) {
+      match col.data() {
+        &frame::ColData::Bool(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, |x| { if x { 1.0 } else { 0.0 } }),
+        &frame::ColData::Byte(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, f64::from),
+        &frame::ColData::Short(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, f64::from),
+        &frame::ColData::Int(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, f64::from),
+        &frame::ColData::Long(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, |x| { x as f64 }),
+        &frame::ColData::Float(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, f64::from),
+        &frame::ColData::Double(ref v) => Self::assemble_sparse_scalar(&mut vs, offset, col.validity(), &v, f64::from),
+
+        &frame::ColData::BoolTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, |x| { if x { 1.0 } else { 0.0 } }),
+        &frame::ColData::ByteTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::ShortTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::IntTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::LongTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, |x| { x as f64 }),
+        &frame::ColData::FloatTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::DoubleTensor(ref v) => Self::assemble_dense_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+
+        &frame::ColData::SparseBoolTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, |x| { if x { 1.0 } else { 0.0 } }),
+        &frame::ColData::SparseByteTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::SparseShortTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::SparseIntTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::SparseLongTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, |x| { x as f64 }),
+        &frame::ColData::SparseFloatTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+        &frame::ColData::SparseDoubleTensor(ref v) => Self::assemble_sparse_tensor(&mut vs, offset, col.validity(), *size, &v, f64::from),
+
+        _ => { } // do nothing
+      }
+
+      offset += *size;
+    }
+
+    let tensors = vs.drain(0..).map(|(indices, values)| dsl::SparseTensor::new(vec![offset], indices, values)).collect();
+    frame::ColData::SparseDoubleTensor(tensors)
+  }
+
+  fn is_valid(validity: Option<&[bool]>, row: usize) -> bool {
+    validity.map(|v| v[row]).unwrap_or(true)
+  }
+
+  // null values are assembled as NaN
+  fn assemble_scalar<T: Copy, F>(vs: &mut [Vec<f64>], validity: Option<&[bool]>, vi: &[T], f: F)
+    where F: Fn(T) -> f64 {
+      for (row, (a, b)) in vs.iter_mut().zip(vi.iter().map(|x| f(*x))).enumerate() {
+        a.push(if Self::is_valid(validity, row) { b } else { f64::NAN })
+      }
+    }
+
+  fn assemble_tensor<T: Copy, F>(vs: &mut [Vec<f64>], validity: Option<&[bool]>, size: usize, vt: &[dsl::DenseTensor<T>], f: F)
+    where F: Fn(T) -> f64 {
+      for (row, (a, b)) in vs.iter_mut().zip(vt.iter()).enumerate() {
+        if Self::is_valid(validity, row) {
+          for t in b.values().iter() {
+            a.push(f(*t))
+          }
+        } else {
+          a.extend((0..size).map(|_| f64::NAN));
+        }
+      }
+    }
+
+  fn assemble_sparse_scalar<T: Copy, F>(vs: &mut [(Vec<usize>, Vec<f64>)], offset: usize, validity: Option<&[bool]>, vi: &[T], f: F)
+    where F: Fn(T) -> f64 {
+      for (row, (&mut (ref mut indices, ref mut values), b)) in vs.iter_mut().zip(vi.iter().map(|x| f(*x))).enumerate() {
+        let b = if Self::is_valid(validity, row) { b } else { f64::NAN };
+
+        if b != 0.0 {
+          indices.push(offset);
+          values.push(b);
+        }
+      }
+    }
+
+  fn assemble_dense_tensor<T: Copy, F>(vs: &mut [(Vec<usize>, Vec<f64>)], offset: usize, validity: Option<&[bool]>, size: usize, vt: &[dsl::DenseTensor<T>], f: F)
     where F: Fn(T) -> f64 {
-      for (a, b) in vs.iter_mut().zip(vi.iter().map(|x| f(*x))) {
-        a.push(b)
+      for (row, (&mut (ref mut indices, ref mut values), b)) in vs.iter_mut().zip(vt.iter()).enumerate() {
+        if Self::is_valid(validity, row) {
+          for (i, t) in b.values().iter().enumerate() {
+            let v = f(*t);
+            if v != 0.0 {
+              indices.push(offset + i);
+              values.push(v);
+            }
+          }
+        } else {
+          Self::push_nans(indices, values, offset, size);
+        }
       }
     }
 
-  fn assemble_tensor<T: Copy, F>(vs: &mut [Vec<f64>], vt: &[dsl::DenseTensor<T>], f: F)
+  fn assemble_sparse_tensor<T: Copy, F>(vs: &mut [(Vec<usize>, Vec<f64>)], offset: usize, validity: Option<&[bool]>, size: usize, vt: &[dsl::SparseTensor<T>], f: F)
     where F: Fn(T) -> f64 {
-      for (a, b) in vs.iter_mut().zip(vt.iter()) {
-        for t in b.values().iter() {
-          a.push(f(*t))
+      for (row, (&mut (ref mut indices, ref mut values), b)) in vs.iter_mut().zip(vt.iter()).enumerate() {
+        if Self::is_valid(validity, row) {
+          for (i, t) in b.indices().iter().zip(b.values().iter()) {
+            indices.push(offset + i);
+            values.push(f(*t));
+          }
+        } else {
+          Self::push_nans(indices, values, offset, size);
         }
       }
     }
+
+  fn push_nans(indices: &mut Vec<usize>, values: &mut Vec<f64>, offset: usize, size: usize) {
+    indices.extend(offset..(offset + size));
+    values.extend((0..size).map(|_| f64::NAN));
+  }
 }
 
 impl frame::Transformer for VectorAssembler {
@@ -127,6 +305,18 @@ impl DefaultNode for VectorAssembler {
 
     dsl::Shape::new(inputs, outputs)
   }
+
+  // inputs may be scalars or tensors, which only the frame knows
+  fn input_schema(&self) -> frame::Schema {
+    frame::Schema::new(self.input_cols.iter().map(|col| {
+      // numbers and tensors of numbers of any size, nulls are assembled as NaN
+      frame::StructField::optional(col, frame::DataType::numeric(frame::DataShape::Tensor(None)))
+    }).collect())
+  }
+
+  fn output_schema(&self) -> frame::Schema {
+    frame::Schema::new(vec![frame::StructField::required(&self.output_col, frame::DataType::tensor(frame::BasicType::Double, None))])
+  }
 }
 
 impl Op for VectorAssemblerOp {
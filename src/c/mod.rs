@@ -2,7 +2,7 @@ use bundle::*;
 use std::slice;
 use std::ffi;
 use std::ptr;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
 
 #[no_mangle]
 pub extern fn mleap_frame_with_size(c_size: usize) -> *mut frame::LeapFrame {
@@ -43,6 +43,82 @@ pub extern fn mleap_frame_with_strings(c_frame: *mut frame::LeapFrame,
   }
 }
 
+/// `c_validity` holds one byte per row, zero for null. Returns zero on
+/// success and -1 on a null frame, an invalid name or a duplicate column.
+#[no_mangle]
+pub extern fn mleap_frame_with_doubles_nullable(c_frame: *mut frame::LeapFrame,
+                                                c_name: *const i8,
+                                                c_values: *const f64,
+                                                c_validity: *const u8) -> c_int {
+  unsafe {
+    let frame = match c_frame.as_mut() {
+      Some(frame) => frame,
+      None => return -1
+    };
+    let name = match c_string_to_option(c_name) {
+      Some(name) => name,
+      None => return -1
+    };
+    if c_values.is_null() || c_validity.is_null() { return -1 }
+
+    let values = slice::from_raw_parts(c_values, frame.size()).to_vec();
+    let validity = c_validity_to_rust(c_validity, frame.size());
+    c_result(frame.try_with_col(frame::Col::from_doubles(name, values).with_validity(validity)))
+  }
+}
+
+/// Null rows may pass a null pointer as their string.
+#[no_mangle]
+pub extern fn mleap_frame_with_strings_nullable(c_frame: *mut frame::LeapFrame,
+                                                c_name: *const i8,
+                                                c_values: *const *const i8,
+                                                c_validity: *const u8) -> c_int {
+  unsafe {
+    let frame = match c_frame.as_mut() {
+      Some(frame) => frame,
+      None => return -1
+    };
+    let name = match c_string_to_option(c_name) {
+      Some(name) => name,
+      None => return -1
+    };
+    if c_values.is_null() || c_validity.is_null() { return -1 }
+
+    let validity = c_validity_to_rust(c_validity, frame.size());
+    let values: Option<Vec<String>> = slice::from_raw_parts(c_values, frame.size()).iter().zip(validity.iter()).map(|(s, valid)| {
+      if *valid { c_string_to_option(*s) } else { Some(String::new()) }
+    }).collect();
+    match values {
+      Some(values) => c_result(frame.try_with_col(frame::Col::from_strings(name, values).with_validity(validity))),
+      None => -1
+    }
+  }
+}
+
+/// Writes one byte per row, zero for null. Returns -1 for a missing column.
+#[no_mangle]
+pub extern fn mleap_frame_get_validity(c_frame: *mut frame::LeapFrame,
+                                       c_name: *const i8,
+                                       c_buffer: *mut u8) -> c_int {
+  unsafe {
+    let frame = match c_frame.as_ref() {
+      Some(frame) => frame,
+      None => return -1
+    };
+    let col = match c_string_to_option(c_name).and_then(|name| frame.get_col(&name)) {
+      Some(col) => col,
+      None => return -1
+    };
+    if c_buffer.is_null() { return -1 }
+
+    let buffer = slice::from_raw_parts_mut(c_buffer, frame.size());
+    for (row, b) in buffer.iter_mut().enumerate() {
+      *b = col.is_valid(row) as u8;
+    }
+    0
+  }
+}
+
 #[no_mangle]
 pub extern fn mleap_frame_get_doubles(c_frame: *mut frame::LeapFrame,
                                       c_name: *const i8,
@@ -55,19 +131,25 @@ pub extern fn mleap_frame_get_doubles(c_frame: *mut frame::LeapFrame,
   }
 }
 
+/// Sparse tensors are read densified. Returns -1 for a missing column, a
+/// column that doesn't hold double tensors, or a null or out of range row.
 #[no_mangle]
 pub extern fn mleap_frame_get_double_tensor_len(c_frame: *mut frame::LeapFrame,
                                                 c_name: *const i8,
                                                 c_index: usize,
                                                 c_dimensions_len: *mut usize,
-                                                c_values_len: *mut usize)
+                                                c_values_len: *mut usize) -> c_int
 {
   unsafe {
-    let frame = c_frame.as_mut().unwrap();
-    let name = c_string_to_rust(c_name);
-    let tensor = &frame.get_double_tensors(&name).unwrap()[c_index];
+    let tensor = match c_double_tensor(c_frame, c_name, c_index) {
+      Some(tensor) => tensor,
+      None => return -1
+    };
+    if c_dimensions_len.is_null() || c_values_len.is_null() { return -1 }
+
     *c_dimensions_len = tensor.dimensions().len();
     *c_values_len = tensor.values().len();
+    0
   }
 }
 
@@ -76,59 +158,98 @@ pub extern fn mleap_frame_get_double_tensor(c_frame: *mut frame::LeapFrame,
                                             c_name: *const i8,
                                             c_index: usize,
                                             c_dimensions: *mut usize,
-                                            c_values: *mut f64)
+                                            c_values: *mut f64) -> c_int
 {
   unsafe {
-    let frame = c_frame.as_mut().unwrap();
-    let name = c_string_to_rust(c_name);
-    let tensor = &frame.get_double_tensors(&name).unwrap()[c_index];
+    let tensor = match c_double_tensor(c_frame, c_name, c_index) {
+      Some(tensor) => tensor,
+      None => return -1
+    };
+    if c_dimensions.is_null() || c_values.is_null() { return -1 }
+
     ptr::copy_nonoverlapping(tensor.dimensions().as_ptr(), c_dimensions, tensor.dimensions().len());
     ptr::copy_nonoverlapping(tensor.values().as_ptr(), c_values, tensor.values().len());
+    0
   }
 }
 
+/// A null frame has no rows.
 #[no_mangle]
-pub extern fn mleap_transformer_load(c_path: *const i8) -> *mut Box<tform::DefaultNode> {
-  let path = c_string_to_rust(c_path);
-  let builder = ser::FileBuilder::try_new(path).unwrap();
-  let mut registry: ser::Registry<Box<tform::DefaultNode>> = ser::Registry::new();
+pub extern fn mleap_frame_get_size(c_frame: *mut frame::LeapFrame) -> usize {
+  unsafe {
+    c_frame.as_ref().map(|f| f.size()).unwrap_or(0)
+  }
+}
 
-  registry.insert_op(tform::linear_regression::OP);
-  registry.insert_op(tform::string_indexer::OP);
-  registry.insert_op(tform::one_hot_encoder::OP);
-  registry.insert_op(tform::pipeline::OP);
-  registry.insert_op(tform::vector_assembler::OP);
-  registry.insert_op(tform::standard_scaler::OP);
+#[no_mangle]
+pub extern fn mleap_frame_get_skipped_rows_len(c_frame: *mut frame::LeapFrame) -> usize {
+  unsafe {
+    c_frame.as_ref().map(|f| f.skipped_rows().len()).unwrap_or(0)
+  }
+}
 
-  let ctx = ser::Context::new(Box::new(builder), &registry);
+#[no_mangle]
+pub extern fn mleap_frame_get_skipped_rows(c_frame: *mut frame::LeapFrame,
+                                           c_buffer: *mut usize) -> c_int {
+  unsafe {
+    let rows = match c_frame.as_ref() {
+      Some(frame) => frame.skipped_rows(),
+      None => return -1
+    };
+    if c_buffer.is_null() && !rows.is_empty() { return -1 }
+
+    ptr::copy_nonoverlapping(rows.as_ptr(), c_buffer, rows.len());
+    0
+  }
+}
+
+#[no_mangle]
+pub extern fn mleap_transformer_load(c_path: *const i8) -> *mut Box<tform::DefaultNode> {
+  let path = c_string_to_rust(c_path);
+  let builder = ser::open_builder(path).unwrap();
+  let registry = tform::default_registry();
+  let ctx = ser::Context::new(builder, &registry);
 
   let (_, transformer) = ctx.read_bundle().unwrap();
   let r = Box::new(transformer);
   Box::into_raw(r)
 }
 
+/// Returns a null pointer if the bytes aren't a readable bundle zip.
+#[no_mangle]
+pub extern fn mleap_transformer_load_from_bytes(c_bytes: *const u8,
+                                                c_len: usize) -> *mut Box<tform::DefaultNode> {
+  if c_bytes.is_null() { return ptr::null_mut() }
+
+  let bytes = unsafe { slice::from_raw_parts(c_bytes, c_len).to_vec() };
+  let builder = match ser::MemoryBuilder::try_from_zip(bytes) {
+    Ok(builder) => builder,
+    Err(_) => return ptr::null_mut()
+  };
+  let registry = tform::default_registry();
+  let ctx = ser::Context::new(Box::new(builder), &registry);
+
+  match ctx.read_bundle() {
+    Ok((_, transformer)) => Box::into_raw(Box::new(transformer)),
+    Err(_) => ptr::null_mut()
+  }
+}
+
 #[no_mangle]
 pub extern fn mleap_transformer_load_ex(c_path: *const i8,
                                         c_load_model: tform::external::LoadModel,
                                         c_transform: tform::external::Transform)
                                         -> *mut Box<tform::DefaultNode> {
     let path = c_string_to_rust(c_path);
-    let builder = ser::FileBuilder::try_new(path).unwrap();
-    let mut registry: ser::Registry<Box<tform::DefaultNode>> = ser::Registry::new();
-
-    registry.insert_op(tform::linear_regression::OP);
-    registry.insert_op(tform::string_indexer::OP);
-    registry.insert_op(tform::one_hot_encoder::OP);
-    registry.insert_op(tform::pipeline::OP);
-    registry.insert_op(tform::vector_assembler::OP);
-    registry.insert_op(tform::standard_scaler::OP);
+    let builder = ser::open_builder(path).unwrap();
+    let mut registry = tform::default_registry();
     // UNSAFE: modifying the singleton
     unsafe {
       tform::external::OP = tform::external::ExternalOp::new(c_load_model, c_transform);
       registry.insert_op(&tform::external::OP);
     }
 
-    let ctx = ser::Context::new(Box::new(builder), &registry);
+    let ctx = ser::Context::new(builder, &registry);
 
     let (_, transformer) = ctx.read_bundle().unwrap();
     let r = Box::new(transformer);
@@ -191,6 +312,47 @@ pub extern fn mleap_model_get_double_tensor(c_model: *const dsl::Model,
   }
 }
 
+fn c_result<T, E>(r: Result<T, E>) -> c_int {
+  match r {
+    Ok(_) => 0,
+    Err(_) => -1
+  }
+}
+
+fn c_double_tensor(c_frame: *mut frame::LeapFrame,
+                   c_name: *const i8,
+                   row: usize) -> Option<dsl::DenseTensor<f64>> {
+  let frame = match unsafe { c_frame.as_ref() } {
+    Some(frame) => frame,
+    None => return None
+  };
+  let col = match c_string_to_option(c_name).and_then(|name| frame.get_col(&name)) {
+    Some(col) => col,
+    None => return None
+  };
+  if row >= frame.size() || !col.is_valid(row) { return None }
+
+  match *col.data() {
+    frame::ColData::DoubleTensor(ref v) => v.get(row).cloned(),
+    frame::ColData::SparseDoubleTensor(ref v) => v.get(row).map(|t| t.to_dense()),
+    _ => None
+  }
+}
+
+fn c_validity_to_rust(c_validity: *const u8, size: usize) -> Vec<bool> {
+  unsafe {
+    slice::from_raw_parts(c_validity, size).iter().map(|b| *b != 0).collect()
+  }
+}
+
+fn c_string_to_option(null_terminated_string: *const c_char) -> Option<String> {
+  if null_terminated_string.is_null() { return None }
+
+  unsafe {
+    ffi::CStr::from_ptr(null_terminated_string).to_str().ok().map(String::from)
+  }
+}
+
 pub fn c_string_to_rust(null_terminated_string: *const c_char) -> String {
   unsafe {
     let c_str = ffi::CStr::from_ptr(null_terminated_string);
@@ -3,9 +3,19 @@ extern crate semver;
 extern crate serde;
 extern crate serde_json;
 extern crate base64;
+extern crate zip;
+extern crate csv;
+#[macro_use]
+extern crate prost;
 extern crate core;
 //extern crate blas_sys;
 extern crate libc;
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "arrow")]
+extern crate arrow_buffer;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
 
 pub mod bundle;
 pub mod c;